@@ -158,5 +158,10 @@ ListItem {
             visible: theEntry.hasTOTP
             onClicked: keepassrx.getTotp(uuid)
         }
+        MenuItem {
+            text: Tr.tr("Next 2FA code")
+            visible: theEntry.hasHOTP
+            onClicked: keepassrx.advanceHotp(uuid)
+        }
     }
 }
@@ -100,14 +100,24 @@ MainView {
         }
 
         function onTotpReceived(totp) {
+            // Pushed codes only refresh what is on screen.
+            if (totp.pushed) {
+                return;
+            }
+
             if (!totp.error) {
                 Clipboard.push(totp.digits);
-                toast.show(
-                    i18n.ctr(
-                        "The totp.validFor field has an s after it. e.g. 30s (seconds)",
-                        "Token '%1' copied. Valid for %2"
-                    ).arg(totp.digits).arg(totp.validFor)
-                );
+                if (totp.counter !== undefined) {
+                    // TRANSLATORS: HOTP codes are counted instead of timed. %2 is the counter of the copied code.
+                    toast.show(i18n.tr("Token '%1' copied. Counter %2").arg(totp.digits).arg(totp.counter));
+                } else {
+                    toast.show(
+                        i18n.ctr(
+                            "The totp.validFor field has an s after it. e.g. 30s (seconds)",
+                            "Token '%1' copied. Valid for %2"
+                        ).arg(totp.digits).arg(totp.validFor)
+                    );
+                }
                 clearClipboardTimer.start();
             } else {
                 toast.show(totp.error);
@@ -176,6 +186,11 @@ MainView {
 	    visible: false
 	    id: aboutPage
 	}
+
+	GeneratorPage {
+	    visible: false
+	    id: generatorPage
+	}
     }
 
     Popup {
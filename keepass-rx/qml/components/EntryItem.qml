@@ -33,6 +33,10 @@ ListItem {
             return 'folder';
         } else if (theEntry.itemType == 'Tag') {
             return 'tag';
+        } else if (theEntry.itemType == 'SavedSearch') {
+            return 'find';
+        } else if (theEntry.itemType == 'Category') {
+            return 'security-alert';
         } else {
             return 'up';
         }
@@ -62,12 +66,19 @@ ListItem {
                     entriesPage,
                     Qt.resolvedUrl("../pages/SingleEntry.qml"),
                     {
+                        entryUuid: entry.uuid,
                         entryTitle: entry.title ? entry.title : null,
                         entryUsername: entry.username ? entry.username : null,
                         entryPassword: entry.password ? entry.password : null,
                         entryUrl: entry.url ? entry.url : null,
                         entryNotes: entry.notes ? entry.notes : null,
-                        entryCustomFields: entry.customFields ? entry.customFields : null
+                        entryCustomFields: entry.customFields ? entry.customFields : null,
+                        entryAttachmentCount: entry.attachmentCount ? entry.attachmentCount : 0,
+                        entryHasTotp: entry.hasTotp ? true : false,
+                        entryOtpAlgorithm: entry.otpAlgorithm ? entry.otpAlgorithm : "",
+                        entryOtpDigits: entry.otpDigits ? entry.otpDigits : 0,
+                        entryOtpPeriod: entry.otpPeriod ? entry.otpPeriod : 0,
+                        entryOtpError: entry.otpError ? entry.otpError : ""
                     }
                 )
             }
@@ -117,6 +128,16 @@ ListItem {
                     keepassrx.getTotp(uuid);
                 }
             },
+            Action {
+                visible: theEntry.hasHOTP
+                name: i18n.tr('Next 2FA Code')
+                iconName: "media-skip-forward"
+                onTriggered: {
+                    // HOTP codes are used up one at a time, so move
+                    // on to the next one and copy it.
+                    keepassrx.advanceHotp(uuid);
+                }
+            },
             Action {
                 visible: hasFeature('DisplayTwoFactorAuth')
                 name: i18n.tr('View Entry')
@@ -147,7 +168,7 @@ ListItem {
             id: imgLoader
             width: units.gu(5)
             height: parent.height
-            sourceComponent: theEntry.itemType == 'Group' || theEntry.itemType == 'Tag' ? folderImgComponent : entryImgComponent
+            sourceComponent: theEntry.itemType == 'Group' || theEntry.itemType == 'Tag' || theEntry.itemType == 'SavedSearch' || theEntry.itemType == 'Category' ? folderImgComponent : entryImgComponent
 
             Component {
                 id: folderImgComponent
@@ -167,7 +188,7 @@ ListItem {
                     // Icon of the group/folder, if it has one.
                     Image {
                         id: groupEntryImg
-                        visible: theEntry.itemType !== 'Tag' // no tiny images for tags.
+                        visible: theEntry.itemType !== 'Tag' && theEntry.itemType !== 'SavedSearch' && theEntry.itemType !== 'Category' // no tiny images for tags.
                         fillMode: Image.PreserveAspectFit
                         source: '../../assets/placeholder.png'
                         width: units.gu(2.75)
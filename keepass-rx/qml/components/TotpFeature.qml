@@ -8,28 +8,57 @@ import keepassrx 1.0
 Item {
     property string entryId
 
+    // Pushed by the backend every time the code rolls over.
+    property string currentTotp
+    property int period: 0
+
+    // Fraction of the period that the code is still valid for. Runs
+    // down to 0 between pushes.
+    property real timeLeft: 0
+
     width: parent.width
     height: parent.height
 
-    RxUiEntry {
-        id: theEntry
-        entryUuid: uuid
-        app: AppState
+    Component.onCompleted: {
+        if (entryId) {
+            keepassrx.watchTotp(entryId);
+        }
+    }
+
+    Component.onDestruction: {
+        if (entryId) {
+            keepassrx.unwatchTotp(entryId);
+        }
     }
 
-    Timer {
-        id: current2FATimer
-        repeat: true
-        interval: 1000
-        running: true
-        triggeredOnStart: true
-        onTriggered: {
-            if (entryId) {
-                theEntry.updateTotp();
+    Connections {
+        target: keepassrx
+
+        function onTotpReceived(totp) {
+            if (!totp.pushed || totp.entryUuid != entryId) {
+                return;
+            }
+
+            currentTotp = totp.digits;
+            period = totp.period;
+
+            countdown.stop();
+            if (period > 0) {
+                timeLeft = totp.remaining / period;
+                countdown.duration = totp.remaining * 1000;
+                countdown.start();
             }
         }
     }
 
+    NumberAnimation on timeLeft {
+        id: countdown
+        running: false
+        to: 0
+    }
+
+    onTimeLeftChanged: countdownRing.requestPaint()
+
     Rectangle {
         id: featuresColumn
         visible: true
@@ -44,7 +73,6 @@ Item {
             width: parent.height
             height: parent.width
             onClicked: {
-                // TODO migrate to actorify?
                 keepassrx.getTotp(entryId);
             }
         }
@@ -56,8 +84,7 @@ Item {
             width: parent.width
             verticalAlignment: Text.AlignVCenter
             color: theme.palette.normal.backgroundTertiaryText
-            //text: "------"
-            text: theEntry.currentTotp
+            text: currentTotp
         }
 
         Text {
@@ -68,19 +95,29 @@ Item {
             width: parent.width
             verticalAlignment: Text.AlignVCenter
             color: theme.palette.normal.backgroundTertiaryText
-            text: theEntry.currentTotpValidFor
+            text: period > 0 ? Math.ceil(timeLeft * period) + "s" : ""
         }
 
-        Icon {
-            id: clockIcon
-            name: 'clock'
+        Canvas {
+            id: countdownRing
+            visible: period > 0
             width: units.gu(2)
             height: units.gu(2)
 
-            anchors.bottom: current2FAValidFor.bottom
             anchors.right: current2FAValidFor.right
-            anchors.rightMargin: clockIcon.width * 1.025
+            anchors.rightMargin: width * 1.025
             anchors.verticalCenter: current2FAValidFor.verticalCenter
+
+            onPaint: {
+                var ctx = getContext("2d");
+                var radius = width / 2 - 1;
+                ctx.reset();
+                ctx.lineWidth = 2;
+                ctx.strokeStyle = theme.palette.normal.backgroundTertiaryText;
+                ctx.beginPath();
+                ctx.arc(width / 2, height / 2, radius, -Math.PI / 2, -Math.PI / 2 + 2 * Math.PI * timeLeft, false);
+                ctx.stroke();
+            }
         }
     }
 }
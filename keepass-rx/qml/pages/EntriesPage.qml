@@ -65,6 +65,10 @@ Page {
         onViewModeChanged: (mode) => {
             entriesListModel.clear();
             resetListView = true;
+
+            if (mode == 'PasswordHealth') {
+                keepassrx.getPasswordHealth();
+            }
         }
 
         onContainerChanged: (newContainerId) => {
@@ -198,6 +202,114 @@ Page {
                 }
             },
 
+            Action {
+                name: "Generator"
+                // TRANSLATORS: Open the password generator.
+                text: i18n.tr("Password Generator")
+                iconName: "reload"
+                onTriggered: {
+                    pageStack.addPageToNextColumn(adaptiveLayout.primaryPage, generatorPage)
+                }
+            },
+
+            Action {
+                name: "ImportOtp"
+                // TRANSLATORS: Import 2FA secrets exported from the Google Authenticator app.
+                text: i18n.tr("Import from Google Authenticator")
+                iconName: "import"
+                onTriggered: {
+                    PopupUtils.open(otpMigrationDialog);
+                }
+            },
+
+            Action {
+                name: "ImportCsv"
+                // TRANSLATORS: Import entries from a CSV file exported by another password manager.
+                text: i18n.tr("Import from CSV")
+                iconName: "document-open"
+                visible: keepassrx.viewMode == 'All'
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        adaptiveLayout.primaryPage,
+                        Qt.resolvedUrl("ImportPage.qml"),
+                        {
+                            groupUuid: containerStack.containerUuid,
+                            groupName: containerStack.containerName || headerTitle()
+                        }
+                    );
+                }
+            },
+
+            Action {
+                name: "ImportBitwarden"
+                // TRANSLATORS: Import entries from an unencrypted Bitwarden JSON export.
+                text: i18n.tr("Import from Bitwarden")
+                iconName: "document-open"
+                visible: keepassrx.viewMode == 'All'
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        adaptiveLayout.primaryPage,
+                        Qt.resolvedUrl("ImportPage.qml"),
+                        {
+                            groupUuid: containerStack.containerUuid,
+                            groupName: containerStack.containerName || headerTitle(),
+                            format: "bitwarden"
+                        }
+                    );
+                }
+            },
+
+            Action {
+                name: "Import1Pux"
+                // TRANSLATORS: Import entries from a 1Password 1PUX export.
+                text: i18n.tr("Import from 1Password")
+                iconName: "document-open"
+                visible: keepassrx.viewMode == 'All'
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        adaptiveLayout.primaryPage,
+                        Qt.resolvedUrl("ImportPage.qml"),
+                        {
+                            groupUuid: containerStack.containerUuid,
+                            groupName: containerStack.containerName || headerTitle(),
+                            format: "1pux"
+                        }
+                    );
+                }
+            },
+
+            Action {
+                name: "ExportCsv"
+                // TRANSLATORS: Export the entries of the open group to a CSV file.
+                text: i18n.tr("Export to CSV")
+                iconName: "document-save-as"
+                visible: keepassrx.viewMode == 'All'
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        adaptiveLayout.primaryPage,
+                        Qt.resolvedUrl("ExportPage.qml"),
+                        {
+                            containerUuid: containerStack.containerUuid,
+                            containerName: containerStack.containerName || headerTitle()
+                        }
+                    );
+                }
+            },
+
+            Action {
+                name: "ExportXml"
+                // TRANSLATORS: Export the whole database to an unencrypted KeePass XML file.
+                text: i18n.tr("Export to KeePass XML")
+                iconName: "document-save-as"
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        adaptiveLayout.primaryPage,
+                        Qt.resolvedUrl("ExportPage.qml"),
+                        { format: "xml" }
+                    );
+                }
+            },
+
             Action {
                 name: "Settings"
                 text: i18n.tr("Settings")
@@ -279,6 +391,24 @@ Page {
                         // TRANSLATORS: Lists of tagged entries. Keep sentence as short as possible.
                         description: QT_TR_NOOP("Entries grouped by tag.")
                     }
+                    ListElement {
+                        name: "SavedSearches";
+                        menuText: QT_TR_NOOP("Saved Searches")
+                        // TRANSLATORS: Searches saved by KeePassXC. Keep sentence as short as possible.
+                        description: QT_TR_NOOP("Entries grouped by saved search.")
+                    }
+                    ListElement {
+                        name: "PasswordHealth";
+                        menuText: QT_TR_NOOP("Password Health")
+                        // TRANSLATORS: Password strength report. Keep sentence as short as possible.
+                        description: QT_TR_NOOP("Entries grouped by password strength.")
+                    }
+                    ListElement {
+                        name: "ReusedPasswords";
+                        menuText: QT_TR_NOOP("Reused Passwords")
+                        // TRANSLATORS: Entries sharing the same password. Keep sentence as short as possible.
+                        description: QT_TR_NOOP("Entries grouped by shared password.")
+                    }
                 }
 
                 Component {
@@ -328,6 +458,48 @@ Page {
         }
     }
 
+    Component {
+        id: otpMigrationDialog
+
+        Dialog {
+            id: otpMigrationDialogInner
+            title: i18n.tr("Import from Google Authenticator")
+            text: i18n.tr("Paste the otpauth-migration link from the QR code of a Google Authenticator export. An entry is created for each 2FA secret.")
+
+            TextField {
+                id: migrationUriField
+                placeholderText: "otpauth-migration://offline?data=..."
+                inputMethodHints: Qt.ImhNoPredictiveText | Qt.ImhSensitiveData
+            }
+
+            Button {
+                text: i18n.tr("Import")
+                color: LomiriColors.green
+                enabled: migrationUriField.text.length > 0
+                onClicked: {
+                    // Entries go into the open group, or the root
+                    // group when not browsing groups.
+                    const groupUuid = keepassrx.viewMode == 'All'
+                        ? containerStack.containerUuid
+                        : keepassrx.rootGroupUuid;
+
+                    keepassrx.importOtpMigration(groupUuid, migrationUriField.text);
+                    migrationUriField.text = '';
+                    PopupUtils.close(otpMigrationDialogInner);
+                }
+            }
+
+            Button {
+                text: i18n.tr("Cancel")
+                color: LomiriColors.silk
+                onClicked: {
+                    migrationUriField.text = '';
+                    PopupUtils.close(otpMigrationDialogInner);
+                }
+            }
+        }
+    }
+
     Row {
         id: containerInstructionsLabel
         height: containerInstructionsText.height + containerInstructionsBottom.height
@@ -419,6 +591,37 @@ Page {
             }
         }
 
+        function onEntriesImported(result) {
+            containerStack.refresh();
+        }
+
+        function onOtpEntriesImported(count) {
+            containerStack.refresh();
+            // TRANSLATORS: After importing 2FA secrets from Google Authenticator.
+            toast.show(i18n.tr("%1 2FA entries imported").arg(count));
+        }
+
+        function onPasswordHealthReceived(health) {
+            if (health.pwnedChecked) {
+                // Entries were flagged, so the view was rebuilt.
+                containerStack.refresh();
+            }
+
+            if (health.pwned > 0) {
+                // TRANSLATORS: Summary of the password health report, when passwords were found in data breaches.
+                toast.show(i18n.tr("%1 passwords found in data breaches").arg(health.pwned));
+            } else if (health.weakTotal > 0) {
+                // TRANSLATORS: Summary of the password health report. Bad, Poor, and Weak are password quality ratings.
+                toast.show(i18n.tr("%1 weak passwords (Bad: %2, Poor: %3, Weak: %4)")
+                    .arg(health.weakTotal)
+                    .arg(health.bad)
+                    .arg(health.poor)
+                    .arg(health.weak));
+            } else {
+                toast.show(i18n.tr("No weak passwords"));
+            }
+        }
+
 	// List of entries for this container. It's an array of uuids.
 	// It includes both immediate subgroupings and immediate child
 	// entries in the container.
@@ -0,0 +1,112 @@
+import QtQuick 2.12
+import Lomiri.Components 1.3
+import keepassrx 1.0
+
+Page {
+    id: entryHistoryPage
+    property string entryUuid
+
+    Component.onCompleted: {
+        keepassrx.getEntryHistory(entryUuid);
+    }
+
+    Connections {
+        target: keepassrx
+
+        function onEntryHistoryReceived(uuid, history) {
+            if (uuid !== entryUuid) {
+                return;
+            }
+
+            historyModel.clear();
+
+            // Newest revision first.
+            for (let i = history.length - 1; i >= 0; i--) {
+                historyModel.append({ revision: history[i] });
+            }
+        }
+
+        function onEntryUpdated(uuid) {
+            if (uuid === entryUuid) {
+                keepassrx.getEntryHistory(entryUuid);
+            }
+        }
+    }
+
+    header: PageHeader {
+        id: header
+        title: i18n.tr("Entry History")
+
+        leadingActionBar.actions: [
+            Action {
+                name: "Close"
+                text: i18n.tr("Close")
+                iconName: "close"
+                onTriggered: {
+                    pageStack.removePages(entryHistoryPage);
+                }
+            }
+        ]
+    }
+
+    ListModel {
+        id: historyModel
+    }
+
+    Label {
+        anchors.centerIn: parent
+        visible: historyModel.count === 0
+        text: i18n.tr("This entry has no history.")
+    }
+
+    LomiriListView {
+        anchors.top: header.bottom
+        anchors.bottom: parent.bottom
+        anchors.left: parent.left
+        anchors.right: parent.right
+        model: historyModel
+
+        delegate: ListItem {
+            height: historyLayout.height + (divider.visible ? divider.height : 0)
+
+            ListItemLayout {
+                id: historyLayout
+                title.text: revision.title || i18n.ctr('Page header for single entry', 'Untitled Entry')
+                subtitle.text: revision.lastModified
+                    ? new Date(revision.lastModified).toLocaleString()
+                    : ""
+                subtitle.visible: !!revision.lastModified
+            }
+
+            onClicked: {
+                pageStack.addPageToNextColumn(
+                    entryHistoryPage,
+                    Qt.resolvedUrl("SingleEntry.qml"),
+                    {
+                        entryUuid: revision.uuid,
+                        entryTitle: revision.title ? revision.title : null,
+                        entryUsername: revision.username ? revision.username : null,
+                        entryPassword: revision.password ? revision.password : null,
+                        entryUrl: revision.url ? revision.url : null,
+                        entryNotes: revision.notes ? revision.notes : null,
+                        entryCustomFields: revision.customFields ? revision.customFields : null,
+                        isRevision: true
+                    }
+                );
+            }
+
+            trailingActions: ListItemActions {
+                actions: [
+                    Action {
+                        name: i18n.tr('Restore')
+                        iconName: "undo"
+                        onTriggered: {
+                            keepassrx.restoreEntryRevision(entryUuid, revision.historyIndex);
+                            toast.show(i18n.tr('Revision restored'));
+                        }
+                    }
+                ]
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+import QtQuick 2.12
+import Lomiri.Components 1.3
+import Lomiri.Content 1.3
+import keepassrx 1.0
+
+Page {
+    id: exportPage
+    property string containerUuid
+    property string containerName
+
+    // csv exports the group, xml the whole database.
+    property string format: "csv"
+
+    property var activeTransfer: null
+    property string exportPath: ""
+    property string exportUrl: ""
+    property string exportFileName: ""
+
+    function cleanupExportFile() {
+        if (exportPath !== "") {
+            keepassrx.cleanupDatabaseExport(exportPath);
+        }
+    }
+
+    function clearExportState(cleanup) {
+        if (cleanup) {
+            cleanupExportFile();
+        }
+        exportPath = "";
+        exportUrl = "";
+        exportFileName = "";
+        activeTransfer = null;
+        exportPeerPicker.visible = false;
+        exportTransferConnection.target = null;
+    }
+
+    function beginExport() {
+        clearExportState(true);
+        if (format === "xml") {
+            keepassrx.exportXml(masterPasswordField.text);
+        } else {
+            keepassrx.exportCsv(containerUuid, masterPasswordField.text);
+        }
+        masterPasswordField.text = "";
+    }
+
+    function formatTitle() {
+        switch (format) {
+            // TRANSLATORS: Page for exporting the whole database to a KeePass XML file.
+            case "xml": return i18n.tr("Export to KeePass XML");
+            // TRANSLATORS: Page for exporting entries to a CSV file.
+            default: return i18n.tr("Export to CSV");
+        }
+    }
+
+    Component.onDestruction: {
+        masterPasswordField.text = "";
+        clearExportState(true);
+    }
+
+    Connections {
+        target: keepassrx
+
+        function onDatabaseExported(result) {
+            exportPath = result.path;
+            exportUrl = result.url;
+            exportFileName = result.fileName;
+            exportPeerPicker.visible = true;
+        }
+
+        function onDatabaseExportFailed(error) {
+            toast.show(error);
+        }
+    }
+
+    header: PageHeader {
+        id: header
+        title: formatTitle()
+
+        leadingActionBar.actions: [
+            Action {
+                name: "Close"
+                text: i18n.tr("Close")
+                iconName: "close"
+                onTriggered: {
+                    clearExportState(true);
+                    pageStack.removePages(exportPage);
+                }
+            }
+        ]
+    }
+
+    Column {
+        anchors.top: header.bottom
+        anchors.left: parent.left
+        anchors.right: parent.right
+        anchors.margins: units.gu(2)
+        spacing: units.gu(2)
+
+        Label {
+            width: parent.width
+            wrapMode: Text.WordWrap
+            textSize: Label.Large
+            color: LomiriColors.red
+            text: i18n.tr("The exported file is not encrypted.")
+        }
+
+        Label {
+            width: parent.width
+            wrapMode: Text.WordWrap
+            text: format === "xml"
+                ? i18n.tr("The whole database is written in plain text, in the KeePass XML format: every password, note, 2FA secret, attachment and old revision of every entry. Anyone with the file can read them. Delete it once you are done with it.")
+                : i18n.tr("All usernames, passwords, notes and 2FA secrets in %1 and its subgroups are written in plain text, in the KeePassXC CSV format. Anyone with the file can read them. Delete it once you are done with it.").arg(containerName)
+        }
+
+        TextField {
+            id: masterPasswordField
+            width: parent.width
+            echoMode: TextInput.Password
+            placeholderText: i18n.tr("Master password")
+            inputMethodHints: Qt.ImhNoPredictiveText | Qt.ImhSensitiveData
+            onAccepted: {
+                if (text.length > 0) {
+                    beginExport();
+                }
+            }
+        }
+
+        Button {
+            anchors.horizontalCenter: parent.horizontalCenter
+            color: LomiriColors.red
+            // TRANSLATORS: Write the entries to an unencrypted file.
+            text: i18n.tr("Export")
+            enabled: masterPasswordField.text.length > 0
+            onClicked: beginExport()
+        }
+    }
+
+    ContentPeerPicker {
+        id: exportPeerPicker
+        visible: false
+        showTitle: true
+        headerText: formatTitle()
+        z: 10
+        handler: ContentHandler.Destination
+        contentType: ContentType.Documents
+
+        onPeerSelected: {
+            peer.selectionType = ContentTransfer.Single;
+            activeTransfer = peer.request();
+            exportTransferConnection.target = activeTransfer;
+        }
+
+        onCancelPressed: {
+            clearExportState(true);
+        }
+    }
+
+    ContentTransferHint {
+        anchors.fill: parent
+        activeTransfer: exportPage.activeTransfer
+    }
+
+    Component {
+        id: exportContentItem
+        ContentItem {}
+    }
+
+    Connections {
+        id: exportTransferConnection
+        target: null
+
+        function onStateChanged() {
+            if (!activeTransfer) {
+                return;
+            }
+
+            if (activeTransfer.state === ContentTransfer.InProgress) {
+                activeTransfer.items = [
+                    exportContentItem.createObject(exportPage, {
+                        "url": exportUrl
+                    })
+                ];
+                activeTransfer.state = ContentTransfer.Charged;
+                exportPeerPicker.visible = false;
+                return;
+            }
+
+            if (activeTransfer.state === ContentTransfer.Charged) {
+                exportPeerPicker.visible = false;
+                toast.show(i18n.tr("%1 ready to export.").arg(exportFileName));
+                return;
+            }
+
+            if (activeTransfer.state === ContentTransfer.Collected) {
+                const fileName = exportFileName;
+                clearExportState(true);
+                toast.show(i18n.tr("%1 exported.").arg(fileName));
+                return;
+            }
+
+            if (activeTransfer.state === ContentTransfer.Aborted ||
+                    activeTransfer.state === ContentTransfer.Finalized) {
+                clearExportState(true);
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+import QtQuick 2.12
+import Lomiri.Components 1.3
+import "../components"
+
+Page {
+    id: generatorPage
+    property bool passphraseMode: false
+    property var generated
+
+    // Password options
+    property int passwordLength: 20
+    property bool useLowercase: true
+    property bool useUppercase: true
+    property bool useDigits: true
+    property bool useSymbols: true
+    property bool excludeLookAlikes: false
+    property int minPerClass: 1
+
+    // Passphrase options
+    property int wordCount: 7
+    property string wordSeparator: " "
+    property bool capitalizeWords: false
+
+    function generate() {
+        if (passphraseMode) {
+            generated = keepassrx.generatePassphrase(wordCount, wordSeparator, capitalizeWords);
+        } else {
+            generated = keepassrx.generatePassword(
+                passwordLength,
+                useLowercase,
+                useUppercase,
+                useDigits,
+                useSymbols,
+                excludeLookAlikes,
+                minPerClass
+            );
+        }
+
+        if (generated.error) {
+            toast.show(generated.error);
+        }
+    }
+
+    Component.onCompleted: generate()
+
+    header: PageHeader {
+        id: header
+        // TRANSLATORS: Page header for the password generator.
+        title: i18n.tr("Password Generator")
+
+        trailingActionBar.actions: [
+            Action {
+                name: "Copy"
+                text: i18n.tr("Copy")
+                iconName: "edit-copy"
+                enabled: generated && !generated.error
+                onTriggered: {
+                    Clipboard.push(generated.value);
+                    toast.show(i18n.tr('%1 copied to clipboard (30 secs)').arg(i18n.tr("Password")));
+                    clearClipboardTimer.start();
+                }
+            },
+            Action {
+                name: "Generate"
+                text: i18n.tr("Generate")
+                iconName: "reload"
+                onTriggered: generate()
+            }
+        ]
+    }
+
+    Flickable {
+        anchors.top: header.bottom
+        anchors.left: parent.left
+        anchors.right: parent.right
+        anchors.bottom: parent.bottom
+        anchors.topMargin: units.gu(2)
+        contentHeight: col.height
+        contentWidth: width
+
+        Column {
+            id: col
+            width: parent.width
+            spacing: units.gu(2)
+
+            Label {
+                anchors.horizontalCenter: parent.horizontalCenter
+                width: parent.width - units.gu(2)
+                horizontalAlignment: Text.AlignHCenter
+                wrapMode: Text.WrapAnywhere
+                font.family: "Ubuntu Mono"
+                textSize: Label.Large
+                text: generated && !generated.error ? generated.value : ""
+            }
+
+            Label {
+                anchors.horizontalCenter: parent.horizontalCenter
+                visible: generated && !generated.error
+                // TRANSLATORS: Strength of a generated password. %1 is a rating (e.g. Good), %2 is bits of entropy.
+                text: generated && !generated.error
+                    ? i18n.tr("%1 (%2 bits)").arg(i18n.tr(generated.quality)).arg(Math.round(generated.entropy))
+                    : ""
+            }
+
+            SettingsItem {
+                // TRANSLATORS: Generate a passphrase of random words instead of random characters.
+                title: i18n.tr("Passphrase")
+                description: i18n.tr("Random words from the EFF wordlist")
+                control: Switch {
+                    checked: passphraseMode
+                    onCheckedChanged: {
+                        passphraseMode = checked;
+                        generate();
+                    }
+                }
+            }
+
+            // Password options
+            Column {
+                width: parent.width
+                spacing: units.gu(2)
+                visible: !passphraseMode
+
+                SettingsItem {
+                    title: i18n.tr("Length: %1").arg(passwordLength)
+                    control: Slider {
+                        minimumValue: 4
+                        maximumValue: 64
+                        value: passwordLength
+                        stepSize: 1
+                        live: false
+                        onValueChanged: {
+                            passwordLength = value;
+                            generate();
+                        }
+                    }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Lower case letters")
+                    control: Switch { checked: useLowercase; onCheckedChanged: { useLowercase = checked; generate(); } }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Upper case letters")
+                    control: Switch { checked: useUppercase; onCheckedChanged: { useUppercase = checked; generate(); } }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Numbers")
+                    control: Switch { checked: useDigits; onCheckedChanged: { useDigits = checked; generate(); } }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Symbols")
+                    control: Switch { checked: useSymbols; onCheckedChanged: { useSymbols = checked; generate(); } }
+                }
+
+                SettingsItem {
+                    // TRANSLATORS: Leave out characters that look alike, e.g. 0 and O.
+                    title: i18n.tr("Exclude look-alike characters")
+                    control: Switch { checked: excludeLookAlikes; onCheckedChanged: { excludeLookAlikes = checked; generate(); } }
+                }
+
+                SettingsItem {
+                    // TRANSLATORS: Minimum number of characters of each enabled type (letters, numbers, symbols).
+                    title: i18n.tr("At least %1 of each type").arg(minPerClass)
+                    control: Slider {
+                        minimumValue: 0
+                        maximumValue: 4
+                        value: minPerClass
+                        stepSize: 1
+                        live: false
+                        onValueChanged: {
+                            minPerClass = value;
+                            generate();
+                        }
+                    }
+                }
+            }
+
+            // Passphrase options
+            Column {
+                width: parent.width
+                spacing: units.gu(2)
+                visible: passphraseMode
+
+                SettingsItem {
+                    title: i18n.tr("Words: %1").arg(wordCount)
+                    control: Slider {
+                        minimumValue: 3
+                        maximumValue: 20
+                        value: wordCount
+                        stepSize: 1
+                        live: false
+                        onValueChanged: {
+                            wordCount = value;
+                            generate();
+                        }
+                    }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Word separator")
+                    control: TextField {
+                        text: wordSeparator
+                        onTextChanged: {
+                            wordSeparator = text;
+                            generate();
+                        }
+                    }
+                }
+
+                SettingsItem {
+                    title: i18n.tr("Capitalize words")
+                    control: Switch { checked: capitalizeWords; onCheckedChanged: { capitalizeWords = checked; generate(); } }
+                }
+            }
+        }
+    }
+}
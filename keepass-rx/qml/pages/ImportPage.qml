@@ -0,0 +1,300 @@
+import QtQuick 2.12
+import Lomiri.Components 1.3
+import Lomiri.Content 1.3
+import keepassrx 1.0
+
+Page {
+    id: importPage
+    property string groupUuid
+    property string groupName
+
+    // csv, or a format that needs no column mapping, like bitwarden or 1pux.
+    property string format: "csv"
+
+    property var activeTransfer: null
+    property string importPath: ""
+    property var columns: []
+    property var mapping: []
+    property var importErrors: []
+    property var importWarnings: []
+    property bool importing: false
+
+    readonly property var presets: ["keepassxc", "bitwarden", "chrome", "firefox", "custom"]
+    readonly property var columnKinds: [
+        "ignore", "group", "title", "username", "password",
+        "url", "notes", "totp", "fields", "custom"
+    ]
+
+    function formatTitle() {
+        switch (format) {
+            // TRANSLATORS: Page for importing entries from a Bitwarden JSON export.
+            case "bitwarden": return i18n.tr("Import from Bitwarden");
+            // TRANSLATORS: Page for importing entries from a 1Password 1PUX export.
+            case "1pux": return i18n.tr("Import from 1Password");
+            // TRANSLATORS: Page for importing entries from a CSV file.
+            default: return i18n.tr("Import from CSV");
+        }
+    }
+
+    function columnKindText(kind) {
+        switch (kind) {
+            case "ignore": return i18n.tr("Do not import");
+            case "group": return i18n.tr("Group");
+            case "title": return i18n.tr("Title");
+            case "username": return i18n.tr("Username");
+            case "password": return i18n.tr("Password");
+            case "url": return i18n.tr("URL");
+            case "notes": return i18n.tr("Notes");
+            // TRANSLATORS: CSV column holding 2FA secrets.
+            case "totp": return i18n.tr("2FA");
+            // TRANSLATORS: CSV column holding several "name: value" lines, as Bitwarden writes them.
+            case "fields": return i18n.tr("Custom fields (one per line)");
+            // TRANSLATORS: CSV column imported as a custom field named after the column.
+            case "custom": return i18n.tr("Custom field");
+            default: return kind;
+        }
+    }
+
+    // The incoming file is removed when the transfer is finalized,
+    // so that only happens once the import is done.
+    function finishTransfer() {
+        if (activeTransfer) {
+            activeTransfer.finalize();
+        }
+        activeTransfer = null;
+        importPath = "";
+        columns = [];
+        mapping = [];
+    }
+
+    function readColumns(preset) {
+        const result = keepassrx.readCsvColumns(importPath, preset);
+        if (!result.ok) {
+            toast.show(result.error);
+            finishTransfer();
+            return;
+        }
+
+        columns = result.columns;
+        mapping = result.mapping;
+        presetSelector.selectedIndex = presets.indexOf(result.preset);
+    }
+
+    function setMapping(index, kind) {
+        const newMapping = mapping.slice();
+        newMapping[index] = kind;
+        mapping = newMapping;
+    }
+
+    Component.onDestruction: {
+        finishTransfer();
+    }
+
+    Connections {
+        target: keepassrx
+
+        function onEntriesImported(result) {
+            importing = false;
+            importErrors = result.errors;
+            importWarnings = result.warnings;
+            finishTransfer();
+            // TRANSLATORS: After importing entries from another password manager.
+            toast.show(i18n.tr("%1 entries imported").arg(result.count));
+        }
+
+        function onEntryImportFailed(error) {
+            importing = false;
+            toast.show(error);
+        }
+    }
+
+    header: PageHeader {
+        id: header
+        title: formatTitle()
+
+        leadingActionBar.actions: [
+            Action {
+                name: "Close"
+                text: i18n.tr("Close")
+                iconName: "close"
+                onTriggered: {
+                    finishTransfer();
+                    pageStack.removePages(importPage);
+                }
+            }
+        ]
+    }
+
+    Flickable {
+        anchors.top: header.bottom
+        anchors.bottom: parent.bottom
+        anchors.left: parent.left
+        anchors.right: parent.right
+        contentHeight: importColumn.height + units.gu(4)
+        clip: true
+
+        Column {
+            id: importColumn
+            anchors.top: parent.top
+            anchors.left: parent.left
+            anchors.right: parent.right
+            anchors.margins: units.gu(2)
+            spacing: units.gu(2)
+
+            Label {
+                width: parent.width
+                wrapMode: Text.WordWrap
+                text: i18n.tr("Entries are imported into %1. Groups in the file are created inside it.").arg(groupName)
+            }
+
+            Button {
+                anchors.horizontalCenter: parent.horizontalCenter
+                visible: importPath === ""
+                text: i18n.tr("Choose File")
+                onClicked: importPeerPicker.visible = true
+            }
+
+            OptionSelector {
+                id: presetSelector
+                visible: importPath !== "" && format === "csv"
+                // TRANSLATORS: The password manager that wrote the CSV file.
+                text: i18n.tr("Exported by")
+                model: [
+                    "KeePassXC",
+                    "Bitwarden",
+                    "Chrome",
+                    "Firefox",
+                    // TRANSLATORS: CSV file from another program, columns are chosen by hand.
+                    i18n.tr("Other")
+                ]
+                onDelegateClicked: readColumns(presets[index])
+            }
+
+            Repeater {
+                model: columns
+
+                OptionSelector {
+                    // The index of the clicked delegate shadows the
+                    // column index in the handler.
+                    property int columnIndex: index
+
+                    width: importColumn.width
+                    text: modelData
+                    model: columnKinds.map(columnKindText)
+                    selectedIndex: columnKinds.indexOf(mapping[columnIndex])
+                    onDelegateClicked: setMapping(columnIndex, columnKinds[index])
+                }
+            }
+
+            Button {
+                anchors.horizontalCenter: parent.horizontalCenter
+                visible: importPath !== ""
+                color: LomiriColors.green
+                text: i18n.tr("Import")
+                enabled: !importing
+                onClicked: {
+                    importing = true;
+                    importErrors = [];
+                    importWarnings = [];
+                    if (format === "csv") {
+                        keepassrx.importCsv(groupUuid, importPath, mapping.join(','));
+                    } else {
+                        keepassrx.importFile(groupUuid, importPath, format);
+                    }
+                }
+            }
+
+            Label {
+                width: parent.width
+                wrapMode: Text.WordWrap
+                visible: importErrors.length > 0
+                textSize: Label.Large
+                color: LomiriColors.orange
+                // TRANSLATORS: Heading above the parts of an export that were not imported.
+                text: i18n.tr("Not imported")
+            }
+
+            Repeater {
+                model: importErrors
+
+                Label {
+                    width: importColumn.width
+                    wrapMode: Text.WordWrap
+                    text: format === "csv"
+                        // TRANSLATORS: A row of a CSV file that was not imported, and why.
+                        ? i18n.tr("Row %1: %2").arg(modelData.position).arg(modelData.reason)
+                        // TRANSLATORS: An item of an export that was not imported, and why.
+                        : i18n.tr("Item %1: %2").arg(modelData.position).arg(modelData.reason)
+                }
+            }
+
+            Label {
+                width: parent.width
+                wrapMode: Text.WordWrap
+                visible: importWarnings.length > 0
+                textSize: Label.Large
+                color: LomiriColors.orange
+                // TRANSLATORS: Heading above the parts of imported entries that were left out, like missing files.
+                text: i18n.tr("Imported incompletely")
+            }
+
+            Repeater {
+                model: importWarnings
+
+                Label {
+                    width: importColumn.width
+                    wrapMode: Text.WordWrap
+                    // TRANSLATORS: An item of an export that was imported without some of its parts, and why.
+                    text: i18n.tr("Item %1: %2").arg(modelData.position).arg(modelData.reason)
+                }
+            }
+        }
+    }
+
+    ContentPeerPicker {
+        id: importPeerPicker
+        visible: true
+        showTitle: true
+        headerText: formatTitle()
+        z: 10
+        handler: ContentHandler.Source
+        contentType: format === "csv" ? ContentType.Documents : ContentType.All
+
+        onPeerSelected: {
+            peer.selectionType = ContentTransfer.Single;
+            activeTransfer = peer.request();
+            importTransferConnection.target = activeTransfer;
+        }
+
+        onCancelPressed: importPeerPicker.visible = false
+    }
+
+    ContentTransferHint {
+        anchors.fill: parent
+        activeTransfer: importPage.activeTransfer
+    }
+
+    Connections {
+        id: importTransferConnection
+        target: null
+
+        function onStateChanged() {
+            if (!activeTransfer || activeTransfer.state !== ContentTransfer.Charged) {
+                return;
+            }
+
+            importPeerPicker.visible = false;
+            if (activeTransfer.items.length === 0) {
+                finishTransfer();
+                return;
+            }
+
+            importPath = String(activeTransfer.items[0].url).replace('file://', '');
+            importErrors = [];
+            importWarnings = [];
+            if (format === "csv") {
+                readColumns("");
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+import QtQuick 2.12
+import Lomiri.Components 1.3
+import keepassrx 1.0
+
+Page {
+    id: otpExportPage
+    property string entryUuid
+
+    // Only filled in after the user taps Reveal.
+    property string otpUri
+    property string qrCodeImage
+
+    function forget() {
+        otpUri = "";
+        qrCodeImage = "";
+    }
+
+    Component.onDestruction: forget()
+
+    Connections {
+        target: keepassrx
+
+        function onOtpQrCodeReceived(uuid, qrCode) {
+            if (uuid !== entryUuid) {
+                return;
+            }
+
+            if (qrCode.error) {
+                toast.show(qrCode.error);
+                return;
+            }
+
+            otpUri = qrCode.uri;
+            qrCodeImage = qrCode.image;
+        }
+    }
+
+    header: PageHeader {
+        id: header
+        // TRANSLATORS: Page for moving a 2FA secret to another device.
+        title: i18n.tr("Export 2FA Secret")
+
+        leadingActionBar.actions: [
+            Action {
+                name: "Close"
+                text: i18n.tr("Close")
+                iconName: "close"
+                onTriggered: {
+                    forget();
+                    pageStack.removePages(otpExportPage);
+                }
+            }
+        ]
+
+        trailingActionBar.actions: [
+            Action {
+                name: "Copy"
+                text: i18n.tr("Copy")
+                iconName: "edit-copy"
+                visible: otpUri !== ""
+                onTriggered: {
+                    Clipboard.push(otpUri);
+                    toast.show(i18n.tr('%1 copied to clipboard (30 secs)').arg(i18n.tr("2FA URI")));
+                    clearClipboardTimer.start();
+                }
+            },
+            Action {
+                name: "Hide"
+                text: i18n.tr("Hide")
+                iconName: "view-off"
+                visible: otpUri !== ""
+                onTriggered: forget()
+            }
+        ]
+    }
+
+    Column {
+        anchors.top: header.bottom
+        anchors.left: parent.left
+        anchors.right: parent.right
+        anchors.margins: units.gu(2)
+        spacing: units.gu(2)
+
+        Label {
+            width: parent.width
+            wrapMode: Text.WordWrap
+            text: i18n.tr("Anyone who sees this QR code can generate your 2FA codes. Only reveal it to scan it into another app.")
+        }
+
+        Button {
+            anchors.horizontalCenter: parent.horizontalCenter
+            visible: otpUri === ""
+            color: LomiriColors.orange
+            // TRANSLATORS: Show the QR code of a 2FA secret.
+            text: i18n.tr("Reveal QR Code")
+            onClicked: keepassrx.revealOtpQrCode(entryUuid, "png")
+        }
+
+        Image {
+            anchors.horizontalCenter: parent.horizontalCenter
+            visible: qrCodeImage !== ""
+            width: Math.min(parent.width, units.gu(40))
+            height: width
+            fillMode: Image.PreserveAspectFit
+            smooth: false
+            source: qrCodeImage
+        }
+
+        Label {
+            width: parent.width
+            visible: otpUri !== ""
+            wrapMode: Text.WrapAnywhere
+            font.family: "Ubuntu Mono"
+            textSize: Label.Small
+            text: otpUri
+        }
+    }
+}
@@ -92,7 +92,33 @@ Page {
                 )
                 control: Switch {
                     checked: SettingsBridge.searchType == 'Fuzzy'
-                    onCheckedChanged: SettingsBridge.searchType = checked ? 'Fuzzy' : 'CaseInsensitive';
+                    onCheckedChanged: {
+                        if (checked) {
+                            SettingsBridge.searchType = 'Fuzzy';
+                        } else if (SettingsBridge.searchType == 'Fuzzy') {
+                            SettingsBridge.searchType = 'CaseInsensitive';
+                        }
+                    }
+                }
+            }
+
+            SettingsItem {
+                // TRANSLATORS: Whether or not to use the KeePassXC search syntax.
+                title: i18n.tr('Advanced Search')
+                // TRANSLATORS: Description of the "Advanced Search" setting.
+                description: i18n.tr(
+                    'Search with KeePassXC query syntax, e.g. user:alice -url:test. ' +
+                        'Overrides fuzzy search.'
+                )
+                control: Switch {
+                    checked: SettingsBridge.searchType == 'Advanced'
+                    onCheckedChanged: {
+                        if (checked) {
+                            SettingsBridge.searchType = 'Advanced';
+                        } else if (SettingsBridge.searchType == 'Advanced') {
+                            SettingsBridge.searchType = 'CaseInsensitive';
+                        }
+                    }
                 }
             }
         }
@@ -6,18 +6,41 @@ import Lomiri.Components 1.3
 import "../components"
 
 Page {
+    id: singleEntryPage
+    property string entryUuid
     property string entryTitle
     property string entryUsername
     property string entryPassword
     property string entryUrl
     property string entryNotes
     property var entryCustomFields
+    property int entryAttachmentCount: 0
+    property bool entryHasTotp: false
+    property string entryOtpAlgorithm
+    property int entryOtpDigits: 0
+    property int entryOtpPeriod: 0
+    property string entryOtpError
+    property bool isRevision: false
     property var colorWashout
 
     function valueIsDefined(value) {
         return value !== undefined && value !== null && value !== ''
     }
 
+    function otpSettingsText() {
+        if (valueIsDefined(entryOtpError)) {
+            return entryOtpError;
+        }
+
+        // TRANSLATORS: Settings of a 2FA code, e.g. "SHA256, 8 digits".
+        const settings = i18n.tr("%1, %2 digits").arg(entryOtpAlgorithm).arg(entryOtpDigits);
+        return entryOtpPeriod > 0
+            // TRANSLATORS: Added to the 2FA settings, e.g. "SHA1, 6 digits, every 30s".
+            ? i18n.tr("%1, every %2s").arg(settings).arg(entryOtpPeriod)
+            // TRANSLATORS: Added to the 2FA settings of counter-based codes.
+            : i18n.tr("%1, counter-based").arg(settings);
+    }
+
     function copyToClipboard(fieldName, fieldValue) {
         Clipboard.push(fieldValue);
         toast.show(i18n.tr(`%1 copied to clipboard (30 secs)`).arg(fieldName));
@@ -83,6 +106,49 @@ Page {
                 }
             }
         ]
+
+        trailingActionBar.actions: [
+            Action {
+                visible: !isRevision
+                name: "History"
+                text: i18n.tr("History")
+                iconName: "history"
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        singleEntryPage,
+                        Qt.resolvedUrl("EntryHistoryPage.qml"),
+                        { entryUuid: entryUuid }
+                    );
+                }
+            },
+            Action {
+                visible: entryHasTotp && !isRevision
+                // TRANSLATORS: Move the 2FA secret of this entry to another device.
+                name: "Export2FA"
+                text: i18n.tr("Export 2FA")
+                iconName: "share"
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        singleEntryPage,
+                        Qt.resolvedUrl("OtpExportPage.qml"),
+                        { entryUuid: entryUuid }
+                    );
+                }
+            },
+            Action {
+                visible: entryAttachmentCount > 0
+                name: "Attachments"
+                text: i18n.tr("Attachments")
+                iconName: "attachment"
+                onTriggered: {
+                    pageStack.addPageToNextColumn(
+                        singleEntryPage,
+                        Qt.resolvedUrl("AttachmentPage.qml"),
+                        { uuid: entryUuid }
+                    );
+                }
+            }
+        ]
     }
 
     ListModel {
@@ -185,13 +251,18 @@ Page {
             visible: valueIsDefined(entryUsername)
                 || valueIsDefined(entryPassword)
                 || valueIsDefined(entryUrl)
+                || valueIsDefined(entryOtpAlgorithm)
+                || valueIsDefined(entryOtpError)
 
             DetailField {
                 title: i18n.tr("Username")
                 visible: valueIsDefined(entryUsername)
                 subtitle: entryUsername
                 onCopyClicked: copyToClipboard(i18n.tr("Username"), entryUsername)
-                showDivider: valueIsDefined(entryPassword) || valueIsDefined(entryUrl)
+                showDivider: valueIsDefined(entryPassword)
+                    || valueIsDefined(entryUrl)
+                    || valueIsDefined(entryOtpAlgorithm)
+                    || valueIsDefined(entryOtpError)
             }
 
             DetailField {
@@ -202,6 +273,8 @@ Page {
                 isContentVisible: false
                 onCopyClicked: copyToClipboard(i18n.tr("Password"), entryPassword)
                 showDivider: valueIsDefined(entryUrl)
+                    || valueIsDefined(entryOtpAlgorithm)
+                    || valueIsDefined(entryOtpError)
             }
 
             DetailField {
@@ -209,6 +282,15 @@ Page {
                 title: i18n.tr("URL")
                 subtitle: entryUrl
                 onCopyClicked: copyToClipboard(i18n.tr('URL'), entryUrl)
+                showDivider: valueIsDefined(entryOtpAlgorithm) || valueIsDefined(entryOtpError)
+            }
+
+            DetailField {
+                visible: valueIsDefined(entryOtpAlgorithm) || valueIsDefined(entryOtpError)
+                // TRANSLATORS: How the 2FA codes of this entry are generated.
+                title: i18n.tr("2FA Settings")
+                subtitle: otpSettingsText()
+                showCopyButton: false
             }
         }
     }
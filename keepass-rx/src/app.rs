@@ -4,6 +4,7 @@ use libsodium_rs::utils::{SecureVec, vec_utils};
 use qmeta_async::with_executor;
 use qmetaobject::{QObject, QObjectBox};
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -131,6 +132,11 @@ pub struct AppState {
 
     master_key: Option<MasterKey>,
     db_key: Option<KeyFile>,
+
+    // Needed to save the current database. The password is kept
+    // encrypted under the master key.
+    db_path: Option<PathBuf>,
+    db_password: Option<EncryptedValue>,
 }
 
 impl AppState {
@@ -176,6 +182,46 @@ impl AppState {
         self.db_key = key;
     }
 
+    pub fn db_path(&self) -> Option<&PathBuf> {
+        self.db_path.as_ref()
+    }
+
+    pub fn set_db_path(&mut self, path: Option<PathBuf>) {
+        self.db_path = path;
+    }
+
+    /// Keep the database password around (encrypted) so that the
+    /// database can be saved even after the app has been locked.
+    pub fn set_db_password(&mut self, password: Option<&[u8]>) -> Result<()> {
+        if let Some(mut old_password) = self.db_password.take() {
+            old_password.zeroize();
+        }
+
+        if let Some(password) = password {
+            if self.master_key.is_none() {
+                self.master_key = Some(MasterKey::new()?);
+            }
+
+            let master_key = self.master_key.as_ref().unwrap();
+            let id = KEY_FILE_COUNTER.fetch_add(1, Ordering::SeqCst);
+            let mut password_buf = vec_utils::secure_vec::<u8>(password.len())?;
+            password_buf.copy_from_slice(password);
+
+            self.db_password = Some(EncryptedValue::new(master_key, id, password_buf)?);
+        }
+
+        Ok(())
+    }
+
+    pub fn db_password(&self) -> Result<SecureVec<u8>> {
+        let master_key = self.master_key.as_ref().ok_or(anyhow!("No master key"))?;
+
+        self.db_password
+            .as_ref()
+            .ok_or(anyhow!("No database password stored"))
+            .and_then(|pw| pw.expose(master_key))
+    }
+
     pub fn curr_view(&self) -> Option<Rc<Box<dyn VirtualHierarchy>>> {
         self.current_view.clone()
     }
@@ -185,6 +231,15 @@ impl AppState {
         Ok(db)
     }
 
+    /// Mutable access to the current database. Fails if anything
+    /// else is holding on to the database.
+    pub fn curr_db_mut(&mut self) -> Result<&mut Zeroizing<RxDatabase>> {
+        let db = self.curr_db.as_mut().ok_or(anyhow!("No database set"))?;
+        let db =
+            Rc::get_mut(db).ok_or(anyhow!("Database Rc still has lingering references"))?;
+        Ok(db)
+    }
+
     pub fn take_db(&mut self) -> Result<Zeroizing<RxDatabase>> {
         let db = self
             .curr_db
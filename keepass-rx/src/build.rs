@@ -129,6 +129,18 @@ fn output_kpxc_icons() {
     fs::write(Path::new(&out_dir).join("kpxc_icons.rs"), out).unwrap();
 }
 
+/// Compile the bundled zxcvbn C library, used to rate password
+/// strength.
+fn build_zxcvbn() {
+    cc::Build::new()
+        .file("src/zxcvbn/zxcvbn.c")
+        .include("src/zxcvbn")
+        .warnings(false)
+        .compile("zxcvbn");
+
+    println!("cargo:rerun-if-changed=src/zxcvbn/");
+}
+
 /// Generate gettext translation files
 fn update_language_files() {
     let pot_file = "../po/keepassrx.projectmoon.pot";
@@ -230,6 +242,7 @@ fn walk_dir(dir: PathBuf, ext: &str) -> Vec<PathBuf> {
 fn main() {
     generate_licenses_rs().expect("Unable to generate licenses");
     output_kpxc_icons();
+    build_zxcvbn();
     update_language_files();
 
     let qmake_cmd = qmake_call();
@@ -269,6 +282,7 @@ fn main() {
 #[cfg(not(feature = "gui"))]
 fn main() {
     output_kpxc_icons();
+    build_zxcvbn();
     generate_licenses_rs().expect("Unable to generate licenses");
     println!("cargo:rerun-if-changed=../about.hbs");
 }
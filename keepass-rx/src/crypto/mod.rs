@@ -272,6 +272,14 @@ impl EncryptedValue {
         Ok(())
     }
 
+    /// Length of the plaintext, known without decrypting it.
+    pub fn len(&self) -> usize {
+        self.value
+            .borrow()
+            .len()
+            .saturating_sub(xchacha20poly1305::ABYTES)
+    }
+
     pub fn expose(&self, key: &MasterKey) -> Result<SecureVec<u8>> {
         let plaintext_value = self.decrypt(key)?;
         self.reencrypt(key)?;
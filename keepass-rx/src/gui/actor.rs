@@ -1,14 +1,17 @@
 use actix::prelude::*;
 use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
 use keepass::{Database, DatabaseKey};
 use libsodium_rs::utils::SecureVec;
 use qmetaobject::*;
-use secstr::SecUtf8;
+use secstr::{SecStr, SecUtf8};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{File, create_dir_all};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::task::{JoinHandle, spawn_blocking};
 use uuid::Uuid;
 use zeroize::{Zeroize, Zeroizing};
@@ -16,15 +19,40 @@ use zeroize::{Zeroize, Zeroizing};
 use super::{KeepassRx, RxDbType};
 use crate::app::AppState;
 use crate::crypto::{EncryptedPassword, MasterKey};
-use crate::gui::utils::synced_databases_path;
+use crate::gui::utils::{
+    exported_databases_path, pwned_passwords_path, synced_databases_path,
+    wipe_exported_databases, write_sensitive_file, write_sensitive_file_with,
+};
 use crate::rx::virtual_hierarchy::{
-    AllTags, AllTemplates, DefaultView, TotpEntries, VirtualHierarchy,
+    AllTags, AllTemplates, DefaultView, PasswordHealth, ReusedPasswords, SavedSearches,
+    TotpEntries, VirtualHierarchy,
 };
 use crate::{
     gui::{RxViewMode, utils::imported_databases_path},
-    rx::{RxDatabase, RxFieldName, ZeroableDatabase},
+    rx::{
+        RxDatabase, RxFieldName, RxImport, RxImportError, RxImportFormat, RxPasswordHealth,
+        RxPwnedChecker, RxQrFormat, RxTotp, ZeroableDatabase, current_time_step,
+        decode_otp_migration, export_csv, export_xml, qr_code, save_atomically,
+        until_rollover,
+    },
 };
 
+/// Codes roll over on the second, so wait a little past it to be
+/// sure the new code is out.
+const TOTP_TICK_SLACK: Duration = Duration::from_millis(50);
+
+/// An entry whose code is on screen. The same entry can be shown more
+/// than once (e.g. in a list and on its own page), so it is watched
+/// until the last of them goes away.
+#[derive(Default)]
+struct WatchedTotp {
+    watchers: usize,
+
+    /// The time step the code was last pushed for, or None if it has
+    /// not been pushed yet.
+    last_step: Option<u64>,
+}
+
 #[derive(Default)]
 pub struct KeepassRxActor {
     app_state: Rc<QObjectBox<AppState>>,
@@ -36,6 +64,11 @@ pub struct KeepassRxActor {
     // any in-progress operation on another thread pool that might
     // need to be aborted.
     current_operation: Option<JoinHandle<Result<()>>>,
+
+    /// TOTP entries that are on screen. Codes are pushed again when
+    /// they roll over.
+    watched_totps: HashMap<Uuid, WatchedTotp>,
+    totp_ticker: Option<SpawnHandle>,
 }
 
 impl KeepassRxActor {
@@ -60,6 +93,59 @@ impl KeepassRxActor {
         }
     }
 
+    /// Push the codes of watched entries that rolled over since they
+    /// were last pushed, then sleep until the next one rolls over.
+    /// Watching stops when the database is gone.
+    fn tick_totps(&mut self, ctx: &mut Context<Self>) {
+        if let Some(ticker) = self.totp_ticker.take() {
+            ctx.cancel_future(ticker);
+        }
+
+        let db = match self.app_state.pinned().borrow().curr_db() {
+            Ok(db) => db,
+            Err(_) => return self.watched_totps.clear(),
+        };
+
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+        let mut next_tick: Option<Duration> = None;
+
+        for (entry_uuid, watched) in self.watched_totps.iter_mut() {
+            let totp = match db.get_totp(&entry_uuid.to_string()) {
+                Ok(totp) => totp,
+                Err(_) => continue,
+            };
+
+            // HOTP codes only change when they are used, so they are
+            // pushed once.
+            if totp.period == 0 {
+                if watched.last_step.is_none() {
+                    watched.last_step = Some(0);
+                    gui.totpReceived(totp_map(*entry_uuid, Ok(totp), true));
+                }
+                continue;
+            }
+
+            let step = current_time_step(totp.period);
+            if watched.last_step != Some(step) {
+                watched.last_step = Some(step);
+                gui.totpReceived(totp_map(*entry_uuid, Ok(totp), true));
+            }
+
+            let until_next = until_rollover(totp.period);
+            next_tick = Some(next_tick.map_or(until_next, |tick| tick.min(until_next)));
+        }
+
+        if let Some(next_tick) = next_tick {
+            let ticker = ctx.run_later(next_tick + TOTP_TICK_SLACK, |actor, ctx| {
+                actor.tick_totps(ctx)
+            });
+
+            self.totp_ticker = Some(ticker);
+        }
+    }
+
     /// Extract key file bytes, if they exist.
     pub fn key_file_bytes(&self) -> Result<Option<SecureVec<u8>>> {
         let app_state = self.app_state.pinned();
@@ -112,6 +198,10 @@ pub struct DeleteDatabase {
 #[rtype(result = "()")]
 pub struct CloseDatabase;
 
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SaveDatabase;
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct GetMetadata;
@@ -130,6 +220,29 @@ pub struct GetSingleEntry {
     pub entry_uuid: Uuid,
 }
 
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct GetEntryHistory {
+    pub entry_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct GetPasswordHealth;
+
+/// Flag breached passwords once the database is open. Sent to the
+/// actor itself, so that unlocking does not wait for the lookups.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CheckPwnedPasswords;
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RestoreEntryRevision {
+    pub entry_uuid: Uuid,
+    pub index: usize,
+}
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct GetFieldValue {
@@ -173,6 +286,131 @@ pub struct GetTotp {
     pub entry_uuid: String,
 }
 
+/// Render the one-time password of an entry as a QR code, for moving
+/// it to another device. Only sent when the user asks to reveal it.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RevealOtpQrCode {
+    pub entry_uuid: Uuid,
+    pub format: RxQrFormat,
+}
+
+/// Start pushing the codes of an entry whenever they roll over.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct WatchTotp {
+    pub entry_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UnwatchTotp {
+    pub entry_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AdvanceHotp {
+    pub entry_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UpdateEntry {
+    pub entry_uuid: Uuid,
+
+    /// Fields to change. A value of None clears the field.
+    pub fields: Vec<(RxFieldName, Option<SecUtf8>)>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CreateEntry {
+    pub group_uuid: Uuid,
+    pub title: SecUtf8,
+}
+
+/// Create entries for the one-time passwords in a Google
+/// Authenticator export (otpauth-migration URI).
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ImportOtpMigration {
+    pub group_uuid: Uuid,
+    pub migration_uri: SecUtf8,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CreateGroup {
+    pub parent_uuid: Uuid,
+    pub name: String,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct DeleteEntry {
+    pub entry_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct DeleteGroup {
+    pub group_uuid: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct MoveEntry {
+    pub entry_uuid: Uuid,
+    pub new_parent: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct MoveGroup {
+    pub group_uuid: Uuid,
+    pub new_parent: Uuid,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ExportAttachment {
+    pub entry_uuid: Uuid,
+    pub attachment_name: String,
+
+    /// Either a directory (the attachment name is used as the file
+    /// name), or the full path of the file to write.
+    pub destination: PathBuf,
+}
+
+/// Export a group and everything below it as KeePassXC CSV. The
+/// master password must be entered again, as the export is plain
+/// text.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ExportCsv {
+    pub container_uuid: Uuid,
+    pub master_password: SecUtf8,
+}
+
+/// Export the whole database as KeePass 2.x XML. Like the CSV
+/// export, the master password must be entered again.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ExportXml {
+    pub master_password: SecUtf8,
+}
+
+/// Import the entries of another password manager's export into a
+/// group.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ImportEntries {
+    pub group_uuid: Uuid,
+    pub path: PathBuf,
+    pub format: RxImportFormat,
+}
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct StoreMasterPassword {
@@ -197,6 +435,67 @@ pub struct InvalidateMasterPassword;
 #[rtype(result = "()")]
 pub struct CheckLockingStatus;
 
+/// Build the virtual hierarchy for the given view mode.
+fn build_view(mode: RxViewMode, db: &RxDatabase) -> Box<dyn VirtualHierarchy> {
+    match mode {
+        RxViewMode::All => Box::new(DefaultView::new(db)),
+        RxViewMode::Templates => Box::new(AllTemplates::new(db)),
+        RxViewMode::Totp => Box::new(TotpEntries::new(db)),
+        RxViewMode::Tags => Box::new(AllTags::new(db)),
+        RxViewMode::SavedSearches => Box::new(SavedSearches::new(db)),
+        RxViewMode::PasswordHealth => Box::new(PasswordHealth::new(db)),
+        RxViewMode::ReusedPasswords => Box::new(ReusedPasswords::new(db)),
+    }
+}
+
+/// Flag the entries whose passwords are in the breached password
+/// list. The check only happens if the user has put the list on the
+/// device. Returns whether it happened.
+fn check_pwned_passwords(db: &mut RxDatabase) -> Result<bool> {
+    let pwned_path = pwned_passwords_path();
+    if !pwned_path.is_file() {
+        return Ok(false);
+    }
+
+    db.check_pwned(&RxPwnedChecker::open(&pwned_path)?)?;
+    Ok(true)
+}
+
+/// Check an edited entry again, as changing its password clears the
+/// breached flag. Failing to check does not fail the edit.
+fn recheck_pwned_password(db: &mut RxDatabase, entry_uuid: Uuid) {
+    let pwned_path = pwned_passwords_path();
+    if !pwned_path.is_file() {
+        return;
+    }
+
+    let result = RxPwnedChecker::open(&pwned_path)
+        .and_then(|checker| db.check_pwned_entry(entry_uuid, &checker));
+
+    if let Err(err) = result {
+        println!("Could not check for breached passwords: {}", err);
+    }
+}
+
+/// Apply a change to the current database. Afterwards, the current
+/// view is rebuilt, because views hold on to the old entries and
+/// groups.
+fn modify_db<T>(
+    app_state: &mut AppState,
+    gui: &mut KeepassRx,
+    modify: impl FnOnce(&mut RxDatabase) -> Result<T>,
+) -> Result<T> {
+    let result = modify(app_state.curr_db_mut()?)?;
+
+    let db = app_state.curr_db()?;
+    app_state.set_curr_view(build_view(gui.viewMode, &db));
+
+    gui.databaseDirty = db.is_dirty();
+    gui.databaseDirtyChanged();
+
+    Ok(result)
+}
+
 impl Handler<SetViewMode> for KeepassRxActor {
     type Result = ();
 
@@ -216,12 +515,7 @@ impl Handler<SetViewMode> for KeepassRxActor {
             Err(err) => return gui.errorReceived(format!("{}", err)),
         };
 
-        let view: Box<dyn VirtualHierarchy> = match mode {
-            RxViewMode::All => Box::new(DefaultView::new(&db)),
-            RxViewMode::Templates => Box::new(AllTemplates::new(&db)),
-            RxViewMode::Totp => Box::new(TotpEntries::new(&db)),
-            RxViewMode::Tags => Box::new(AllTags::new(&db)),
-        };
+        let view = build_view(mode, &db);
 
         app_state.set_curr_view(view);
         gui.viewMode = mode;
@@ -249,6 +543,7 @@ impl Handler<OpenDatabase> for KeepassRxActor {
 
         // Clone here so we can encrypt later.
         let stored_pw = self.stored_master_password.clone();
+        let opened_db_path = db_path.clone();
         let maybe_key_file_bytes = self.key_file_bytes();
 
         println!("Opening {} DB: {}", msg.db_type, db_path.display());
@@ -297,7 +592,7 @@ impl Handler<OpenDatabase> for KeepassRxActor {
                 Ok(open_result)
             }
             .into_actor(self)
-            .map(|result: Result<Database>, this, _| {
+            .map(|result: Result<Database>, this, ctx| {
                 let binding = this.gui.clone();
                 let binding = binding.pinned();
                 let mut gui = binding.borrow_mut();
@@ -323,6 +618,17 @@ impl Handler<OpenDatabase> for KeepassRxActor {
                             app_state.set_db_key(Some(kf));
                         }
 
+                        // Keep what is needed to save the database
+                        // later. Must come after the key file, which
+                        // replaces the master key.
+                        let stored_pw = this.stored_master_password.borrow();
+                        let stored_pw = stored_pw.as_ref().map(|pw| pw.unsecure().as_bytes());
+                        if let Err(err) = app_state.set_db_password(stored_pw) {
+                            println!("Could not store database password: {}", err);
+                        }
+
+                        app_state.set_db_path(Some(opened_db_path));
+
                         gui.rootGroupUuid = QString::from(rx_db.root_group().uuid.to_string());
                         gui.metadata = rx_db.metadata().into();
 
@@ -334,6 +640,8 @@ impl Handler<OpenDatabase> for KeepassRxActor {
 
                         gui.viewMode = RxViewMode::All;
                         gui.viewModeChanged(RxViewMode::All);
+
+                        ctx.notify(CheckPwnedPasswords);
                     }
                     Err(err) => gui.databaseOpenFailed(format!("{}", err)),
                 }
@@ -353,6 +661,16 @@ impl Handler<CloseDatabase> for KeepassRxActor {
         let mut app_state = app_state.borrow_mut();
         let db = app_state.take_db();
 
+        // Exports of the locked database must not outlive it.
+        if let Err(err) = wipe_exported_databases() {
+            println!("Could not wipe exported databases: {}", err);
+        }
+
+        app_state.set_db_path(None);
+        if let Err(err) = app_state.set_db_password(None) {
+            println!("Could not clear database password: {}", err);
+        }
+
         AtomicResponse::new(Box::pin(
             async move {
                 // Remove from option.
@@ -378,6 +696,71 @@ impl Handler<CloseDatabase> for KeepassRxActor {
     }
 }
 
+impl Handler<SaveDatabase> for KeepassRxActor {
+    type Result = AtomicResponse<Self, ()>;
+
+    fn handle(&mut self, _: SaveDatabase, _: &mut Self::Context) -> Self::Result {
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        // The RxDatabase cannot leave this thread, so it is converted
+        // to a KeePass database here. Only the actual writing happens
+        // on another thread.
+        let prepared = (|| -> Result<(Database, DatabaseKey, PathBuf)> {
+            let db = app_state.curr_db()?;
+            let db_path = app_state
+                .db_path()
+                .cloned()
+                .ok_or(anyhow!("[SaveDB] No database path"))?;
+
+            let password = app_state.db_password()?;
+            let db_key = DatabaseKey::new().with_password(std::str::from_utf8(&password)?);
+            let db_key = match self.key_file_bytes()? {
+                Some(bytes) => db_key.with_keyfile(&mut bytes.as_ref())?,
+                None => db_key,
+            };
+
+            Ok((db.to_keepass()?, db_key, db_path))
+        })();
+
+        AtomicResponse::new(Box::pin(
+            async move {
+                let (keepass_db, db_key, db_path) = prepared?;
+                println!("Saving DB: {}", db_path.display());
+
+                // Saving is synchronous I/O (and encryption), which
+                // means it must be done on a separate thread.
+                spawn_blocking(move || save_atomically(&keepass_db, db_key, &db_path))
+                    .await??;
+
+                Ok(())
+            }
+            .into_actor(self)
+            .map(|result: Result<()>, this, _| {
+                let binding = this.gui.clone();
+                let binding = binding.pinned();
+                let mut gui = binding.borrow_mut();
+
+                let app_state = this.app_state.pinned();
+                let mut app_state = app_state.borrow_mut();
+
+                match result {
+                    Ok(_) => {
+                        if let Ok(db) = app_state.curr_db_mut() {
+                            db.mark_clean();
+                        }
+
+                        gui.databaseDirty = false;
+                        gui.databaseDirtyChanged();
+                        gui.databaseSaved();
+                    }
+                    Err(err) => gui.databaseSaveFailed(format!("{}", err)),
+                }
+            }),
+        ))
+    }
+}
+
 impl Handler<DeleteDatabase> for KeepassRxActor {
     type Result = Result<()>;
 
@@ -470,13 +853,18 @@ impl Handler<GetEntries> for KeepassRxActor {
             .curr_view()
             .expect("GetEntries: Viewable not set.");
 
+        let db = match app_state.curr_db() {
+            Ok(db) => db,
+            Err(err) => return gui.errorReceived(format!("{}", err)),
+        };
+
         let container_uuid = match msg.container_uuid {
             Some(id) => id,
             None => viewable.root().uuid(),
         };
 
         let results: QStringList = viewable
-            .search(search_type, container_uuid, search_term)
+            .search(&db, search_type, container_uuid, search_term)
             .into_iter()
             .map(|container| container.uuid().to_string())
             .collect();
@@ -512,6 +900,115 @@ impl Handler<GetSingleEntry> for KeepassRxActor {
     }
 }
 
+impl Handler<GetPasswordHealth> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, _: GetPasswordHealth, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        // Check again, in case the list was put on the device or
+        // passwords changed since the database was opened.
+        let pwned_checked = match modify_db(&mut app_state, &mut gui, check_pwned_passwords) {
+            Ok(checked) => checked,
+            Err(err) => return gui.errorReceived(format!("{}", err)),
+        };
+
+        match app_state.curr_db() {
+            Ok(db) => {
+                let health = RxPasswordHealth::new(&db);
+                let mut health_map = QVariantMap::from(&health);
+                health_map.insert("pwnedChecked".into(), pwned_checked.into());
+                gui.passwordHealthReceived(health_map);
+            }
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<CheckPwnedPasswords> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, _: CheckPwnedPasswords, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        // The database may have been closed in the meantime.
+        if app_state.curr_db().is_err() {
+            return;
+        }
+
+        if let Err(err) = modify_db(&mut app_state, &mut gui, check_pwned_passwords) {
+            println!("Could not check for breached passwords: {}", err);
+        }
+    }
+}
+
+impl Handler<GetEntryHistory> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: GetEntryHistory, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        let db = match app_state.curr_db() {
+            Ok(db) => db,
+            Err(err) => return gui.errorReceived(format!("{}", err)),
+        };
+
+        let entry = match db.get_entry(msg.entry_uuid) {
+            Some(entry) => entry,
+            None => return gui.errorReceived(format!("No entry {}", msg.entry_uuid)),
+        };
+
+        // Each revision is a full entry map, plus where it sits in
+        // the history (for restoring).
+        let history: QVariantList = entry
+            .history()
+            .enumerate()
+            .map(|(index, revision)| {
+                let mut map = QVariantMap::from(revision);
+                map.insert("historyIndex".into(), (index as i32).into());
+                map.to_qvariant()
+            })
+            .collect();
+
+        gui.entryHistoryReceived(QString::from(msg.entry_uuid.to_string()), history);
+    }
+}
+
+impl Handler<RestoreEntryRevision> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: RestoreEntryRevision, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.restore_entry_revision(msg.entry_uuid, msg.index)?;
+            recheck_pwned_password(db, msg.entry_uuid);
+            Ok(())
+        });
+
+        match result {
+            Ok(_) => gui.entryUpdated(QString::from(msg.entry_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
 fn get_value(db: &RxDatabase, entry_uuid: Uuid, field_name: &RxFieldName) -> QString {
     db.get_entry(entry_uuid)
         .and_then(|entry| {
@@ -554,7 +1051,7 @@ impl Handler<GetFieldValue> for KeepassRxActor {
 
 impl Handler<GetTotp> for KeepassRxActor {
     type Result = ();
-    fn handle(&mut self, msg: GetTotp, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: GetTotp, ctx: &mut Self::Context) -> Self::Result {
         let binding = self.gui.clone();
         let binding = binding.pinned();
         let gui = binding.borrow();
@@ -569,18 +1066,70 @@ impl Handler<GetTotp> for KeepassRxActor {
             Err(err) => return gui.errorReceived(format!("{}", err)),
         };
 
+        let entry_uuid = Uuid::parse_str(&msg.entry_uuid).unwrap_or_default();
         let totp = db.get_totp(&msg.entry_uuid);
+        gui.totpReceived(totp_map(entry_uuid, totp, false));
+    }
+}
 
-        let mut map: HashMap<String, QVariant> = HashMap::new();
-        match totp {
-            Ok(otp) => {
-                let digits = QString::from(otp.code);
-                let valid_for = QString::from(otp.valid_for);
-
-                map.insert("digits".to_string(), digits.into());
-                map.insert("validFor".to_string(), valid_for.into());
-            }
-            Err(err) => {
+impl Handler<AdvanceHotp> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: AdvanceHotp, ctx: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let hotp = modify_db(&mut app_state, &mut gui, |db| {
+            db.advance_hotp_counter(msg.entry_uuid)
+        });
+
+        // Watchers still show the used code, so the next one is
+        // pushed to them.
+        if let Some(watched) = self.watched_totps.get_mut(&msg.entry_uuid) {
+            watched.last_step = None;
+            ctx.run_later(Duration::ZERO, |actor, ctx| actor.tick_totps(ctx));
+        }
+
+        gui.totpReceived(totp_map(msg.entry_uuid, hotp, false));
+    }
+}
+
+impl Handler<RevealOtpQrCode> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: RevealOtpQrCode, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        let qr_code = app_state.curr_db().and_then(|db| {
+            let entry = db
+                .get_entry(msg.entry_uuid)
+                .ok_or(anyhow!("No entry {}", msg.entry_uuid))?;
+
+            let uri = entry.otp_export_uri()?;
+            let image = qr_code(&uri, msg.format)?;
+            let data_url = Zeroizing::new(format!(
+                "data:{};base64,{}",
+                msg.format.mime_type(),
+                BASE64_STANDARD.encode(&image[..])
+            ));
+
+            Ok((uri, data_url))
+        });
+
+        let mut map: HashMap<String, QVariant> = HashMap::new();
+        match qr_code {
+            Ok((uri, data_url)) => {
+                map.insert("uri".to_string(), QString::from(uri.as_str()).into());
+                map.insert("image".to_string(), QString::from(data_url.as_str()).into());
+            }
+            Err(err) => {
                 map.insert(
                     "error".to_string(),
                     QString::from(format!("{}", err)).into(),
@@ -588,7 +1137,414 @@ impl Handler<GetTotp> for KeepassRxActor {
             }
         }
 
-        gui.totpReceived(QVariantMap::from(map));
+        gui.otpQrCodeReceived(
+            QString::from(msg.entry_uuid.to_string()),
+            QVariantMap::from(map),
+        );
+    }
+}
+
+impl Handler<WatchTotp> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: WatchTotp, ctx: &mut Self::Context) -> Self::Result {
+        // Push the code right away, so the new watcher has one too.
+        let watched = self.watched_totps.entry(msg.entry_uuid).or_default();
+        watched.watchers += 1;
+        watched.last_step = None;
+
+        self.tick_totps(ctx);
+    }
+}
+
+impl Handler<UnwatchTotp> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: UnwatchTotp, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(watched) = self.watched_totps.get_mut(&msg.entry_uuid) {
+            watched.watchers = watched.watchers.saturating_sub(1);
+            if watched.watchers == 0 {
+                self.watched_totps.remove(&msg.entry_uuid);
+            }
+        }
+
+        if self.watched_totps.is_empty() {
+            if let Some(ticker) = self.totp_ticker.take() {
+                ctx.cancel_future(ticker);
+            }
+        }
+    }
+}
+
+/// Pushed codes are refreshes for the entries on screen, and must not
+/// be copied to the clipboard like requested ones.
+fn totp_map(entry_uuid: Uuid, totp: Result<RxTotp>, pushed: bool) -> QVariantMap {
+    let mut map: HashMap<String, QVariant> = HashMap::new();
+
+    map.insert(
+        "entryUuid".to_string(),
+        QString::from(entry_uuid.to_string()).into(),
+    );
+
+    let otp = match totp {
+        Ok(otp) => otp,
+        Err(err) => {
+            map.insert(
+                "error".to_string(),
+                QString::from(format!("{}", err)).into(),
+            );
+            return QVariantMap::from(map);
+        }
+    };
+
+    map.insert("digits".to_string(), QString::from(otp.code).into());
+    map.insert("validFor".to_string(), QString::from(otp.valid_for).into());
+    map.insert("period".to_string(), (otp.period as i32).into());
+    map.insert("remaining".to_string(), (otp.remaining as i32).into());
+    map.insert("pushed".to_string(), pushed.into());
+
+    if let Some(counter) = otp.counter {
+        let counter = QString::from(counter.to_string());
+        map.insert("counter".to_string(), counter.into());
+    }
+
+    QVariantMap::from(map)
+}
+
+impl Handler<UpdateEntry> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: UpdateEntry, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let changes = msg
+            .fields
+            .into_iter()
+            .map(|(field_name, value)| {
+                let value = value.map(|val| SecStr::from(val.into_unsecure()));
+                (field_name, value)
+            })
+            .collect();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.update_entry(msg.entry_uuid, changes)?;
+            recheck_pwned_password(db, msg.entry_uuid);
+            Ok(())
+        });
+
+        match result {
+            Ok(_) => gui.entryUpdated(QString::from(msg.entry_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<CreateEntry> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: CreateEntry, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let title = SecStr::from(msg.title.into_unsecure());
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.create_entry(msg.group_uuid, title)
+        });
+
+        match result {
+            Ok(entry_uuid) => gui.entryCreated(QString::from(entry_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<ImportOtpMigration> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: ImportOtpMigration, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = decode_otp_migration(msg.migration_uri.unsecure()).and_then(|otps| {
+            modify_db(&mut app_state, &mut gui, |db| {
+                db.create_otp_entries(msg.group_uuid, &otps)
+            })
+        });
+
+        match result {
+            Ok(entry_uuids) => gui.otpEntriesImported(entry_uuids.len() as i32),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<CreateGroup> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: CreateGroup, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.create_group(msg.parent_uuid, &msg.name)
+        });
+
+        match result {
+            Ok(group_uuid) => gui.groupCreated(QString::from(group_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<DeleteEntry> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: DeleteEntry, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.delete_entry(msg.entry_uuid)
+        });
+
+        match result {
+            Ok(_) => gui.entryDeleted(QString::from(msg.entry_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<DeleteGroup> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: DeleteGroup, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.delete_group(msg.group_uuid)
+        });
+
+        match result {
+            Ok(_) => gui.groupDeleted(QString::from(msg.group_uuid.to_string())),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<MoveEntry> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: MoveEntry, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.move_entry(msg.entry_uuid, msg.new_parent)
+        });
+
+        match result {
+            Ok(_) => gui.entryMoved(
+                QString::from(msg.entry_uuid.to_string()),
+                QString::from(msg.new_parent.to_string()),
+            ),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+impl Handler<MoveGroup> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: MoveGroup, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let result = modify_db(&mut app_state, &mut gui, |db| {
+            db.move_group(msg.group_uuid, msg.new_parent)
+        });
+
+        match result {
+            Ok(_) => gui.groupMoved(
+                QString::from(msg.group_uuid.to_string()),
+                QString::from(msg.new_parent.to_string()),
+            ),
+            Err(err) => gui.errorReceived(format!("{}", err)),
+        }
+    }
+}
+
+/// Decrypt a single attachment and write it to the destination. If
+/// the destination is a directory, the attachment name is used as the
+/// file name. Returns the path of the written file.
+pub(crate) fn export_attachment(
+    db: &RxDatabase,
+    entry_uuid: Uuid,
+    attachment_name: &str,
+    destination: &Path,
+) -> Result<PathBuf> {
+    // Attachment names come from the database, so do not let them
+    // escape the destination directory.
+    let file_name = Path::new(attachment_name)
+        .file_name()
+        .ok_or(anyhow!("Invalid attachment name: {}", attachment_name))?;
+
+    let dest = match destination {
+        dir if dir.is_dir() => dir.join(file_name),
+        file => file.to_path_buf(),
+    };
+
+    if let Some(parent) = dest.parent() {
+        create_dir_all(parent)?;
+    }
+
+    // Held in a SecureVec, which is zeroed out when dropped.
+    let bytes: SecureVec<u8> = db.get_attachment(entry_uuid, attachment_name)?;
+
+    println!("Exporting attachment to {}", dest.display());
+    let bytes_written = write_sensitive_file(&dest, &bytes)?;
+    println!("Exported {} bytes", bytes_written);
+
+    Ok(dest)
+}
+
+impl Handler<ExportAttachment> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: ExportAttachment, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        let entry_uuid = QString::from(msg.entry_uuid.to_string());
+        let attachment_name = QString::from(msg.attachment_name.as_str());
+
+        let result = app_state.curr_db().and_then(|db| {
+            export_attachment(&db, msg.entry_uuid, &msg.attachment_name, &msg.destination)
+        });
+
+        match result {
+            Ok(path) => gui.attachmentExported(
+                entry_uuid,
+                attachment_name,
+                QString::from(path.to_string_lossy().to_string()),
+            ),
+            Err(err) => {
+                gui.attachmentExportFailed(entry_uuid, attachment_name, format!("{}", err))
+            }
+        }
+    }
+}
+
+/// Plain text exports need the master password again, so that an
+/// unlocked phone is not enough to get everything out.
+fn confirm_master_password(app_state: &AppState, master_password: &SecUtf8) -> Result<()> {
+    let stored = app_state.db_password()?;
+
+    // SecStr compares in constant time.
+    match SecStr::new(stored.to_vec()) == SecStr::from(master_password.unsecure()) {
+        true => Ok(()),
+        false => Err(anyhow!("Wrong master password")),
+    }
+}
+
+/// File name for an export of the named group. Anything that could
+/// not be in a file name is replaced.
+fn export_file_name(name: &str, extension: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || " -_".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect();
+
+    match name.is_empty() {
+        true => format!("KeePassRX.{}", extension),
+        false => format!("{}.{}", name, extension),
+    }
+}
+
+/// What QML needs to hand an export off to another app.
+fn export_map(path: &Path, count: usize) -> QVariantMap {
+    let path_str = path.to_string_lossy().to_string();
+    let file_name = path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut map = QVariantMap::default();
+    map.insert("fileName".into(), QString::from(file_name).into());
+    map.insert(
+        "url".into(),
+        QString::from(format!("file://{}", path_str)).into(),
+    );
+    map.insert("path".into(), QString::from(path_str).into());
+    map.insert("count".into(), (count as i32).into());
+    map
+}
+
+impl Handler<ExportCsv> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: ExportCsv, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        let result =
+            confirm_master_password(&app_state, &msg.master_password).and_then(|_| {
+                let db = app_state.curr_db()?;
+                let view = DefaultView::new(&db);
+                let container = view
+                    .root()
+                    .get_container(msg.container_uuid)
+                    .ok_or(anyhow!("Could not find group to export"))?;
+
+                let name = container.get_ref().map(|r| r.name()).unwrap_or_default();
+                let export_dir = exported_databases_path();
+                create_dir_all(&export_dir)?;
+
+                let dest = export_dir.join(export_file_name(&name, "csv"));
+                let count =
+                    write_sensitive_file_with(&dest, |file| export_csv(&db, container, file))?;
+
+                println!("Exported {} entries to {}", count, dest.display());
+                Ok(export_map(&dest, count))
+            });
+
+        match result {
+            Ok(export) => gui.databaseExported(export),
+            Err(err) => gui.databaseExportFailed(format!("{}", err)),
+        }
     }
 }
 
@@ -761,3 +1717,103 @@ impl Handler<DecryptMasterPassword> for KeepassRxActor {
         self.current_operation = Some(handle);
     }
 }
+
+impl Handler<ExportXml> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: ExportXml, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let gui = binding.borrow();
+
+        let app_state = self.app_state.pinned();
+        let app_state = app_state.borrow();
+
+        let result =
+            confirm_master_password(&app_state, &msg.master_password).and_then(|_| {
+                let db = app_state.curr_db()?;
+                let name = db
+                    .metadata()
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| db.root_group().name.clone());
+
+                let export_dir = exported_databases_path();
+                create_dir_all(&export_dir)?;
+
+                let dest = export_dir.join(export_file_name(&name, "xml"));
+                let count = write_sensitive_file_with(&dest, |file| export_xml(&db, file))?;
+
+                println!("Exported {} entries to {}", count, dest.display());
+                Ok(export_map(&dest, count))
+            });
+
+        match result {
+            Ok(export) => gui.databaseExported(export),
+            Err(err) => gui.databaseExportFailed(format!("{}", err)),
+        }
+    }
+}
+
+fn import_errors_list(errors: &[RxImportError]) -> QVariantList {
+    errors
+        .iter()
+        .map(|err| {
+            let mut map = QVariantMap::default();
+            map.insert("position".into(), (err.position as i32).into());
+            map.insert("reason".into(), QString::from(err.reason.as_str()).into());
+            map.to_qvariant()
+        })
+        .collect()
+}
+
+/// The number of imported entries, what could not be imported, and
+/// what was left out of imported entries, for QML.
+fn import_map(count: usize, import: &RxImport) -> QVariantMap {
+    let mut map = QVariantMap::default();
+    map.insert("count".into(), (count as i32).into());
+    map.insert(
+        "errors".into(),
+        import_errors_list(&import.errors).to_qvariant(),
+    );
+    map.insert(
+        "warnings".into(),
+        import_errors_list(&import.warnings).to_qvariant(),
+    );
+    map
+}
+
+impl Handler<ImportEntries> for KeepassRxActor {
+    type Result = ();
+    fn handle(&mut self, msg: ImportEntries, _: &mut Self::Context) -> Self::Result {
+        let binding = self.gui.clone();
+        let binding = binding.pinned();
+        let mut gui = binding.borrow_mut();
+
+        let app_state = self.app_state.pinned();
+        let mut app_state = app_state.borrow_mut();
+
+        let root_name = app_state.curr_db().map(|db| db.root_group().name.clone());
+
+        let result = root_name
+            .and_then(|root_name| msg.format.read(&msg.path, &root_name))
+            .and_then(|import| {
+                let created = modify_db(&mut app_state, &mut gui, |db| {
+                    db.import_entries(msg.group_uuid, &import.entries)
+                })?;
+
+                println!(
+                    "Imported {} entries, skipped {}, with {} warnings",
+                    created.len(),
+                    import.errors.len(),
+                    import.warnings.len()
+                );
+
+                Ok(import_map(created.len(), &import))
+            });
+
+        match result {
+            Ok(import) => gui.entriesImported(import),
+            Err(err) => gui.entryImportFailed(format!("{}", err)),
+        }
+    }
+}
@@ -5,12 +5,19 @@ use gettextrs::pgettext;
 use qmeta_async::with_executor;
 use qmetaobject::*;
 use secstr::SecUtf8;
-use std::fs::{create_dir_all, remove_dir_all};
-use std::path::Path;
+use std::fs::{File, create_dir_all, remove_dir_all};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use unicase::UniCase;
 use uuid::Uuid;
 
+use crate::rx::{
+    RxCharClass, RxCsvColumn, RxCsvPreset, RxFieldName, RxGenerated, RxImportFormat,
+    RxPassphraseProfile, RxPasswordProfile, RxQrFormat, RxWordlist, generate_passphrase,
+    generate_password, read_csv_header,
+};
+
 pub(crate) mod actor;
 pub(crate) mod colors;
 pub(crate) mod instructions;
@@ -140,6 +147,9 @@ pub enum RxViewMode {
     Templates,
     Totp,
     Tags,
+    SavedSearches,
+    PasswordHealth,
+    ReusedPasswords,
 }
 
 fn view_mode_from_string(qval: &QString) -> RxViewMode {
@@ -148,6 +158,9 @@ fn view_mode_from_string(qval: &QString) -> RxViewMode {
         "Templates" => RxViewMode::Templates,
         "Totp" => RxViewMode::Totp,
         "Tags" => RxViewMode::Tags,
+        "SavedSearches" => RxViewMode::SavedSearches,
+        "PasswordHealth" => RxViewMode::PasswordHealth,
+        "ReusedPasswords" => RxViewMode::ReusedPasswords,
         _ => panic!("Invalid view mode: {}", qval),
     }
 }
@@ -158,6 +171,9 @@ fn view_mode_to_string(view_mode: &RxViewMode) -> QString {
         RxViewMode::Templates => "Templates",
         RxViewMode::Totp => "Totp",
         RxViewMode::Tags => "Tags",
+        RxViewMode::SavedSearches => "SavedSearches",
+        RxViewMode::PasswordHealth => "PasswordHealth",
+        RxViewMode::ReusedPasswords => "ReusedPasswords",
     }
     .into()
 }
@@ -179,12 +195,14 @@ pub struct KeepassRx {
     isMasterPasswordEncrypted: qt_property!(bool; NOTIFY masterPasswordStateChanged),
     rootGroupUuid: qt_property!(QString; NOTIFY rootGroupUuidChanged),
     metadata: qt_property!(QVariant; NOTIFY metadataChanged),
+    databaseDirty: qt_property!(bool; NOTIFY databaseDirtyChanged),
 
     // database management
     listImportedDatabases: qt_method!(fn(&self)),
     importDatabase: qt_method!(fn(&self, path: String)),
     getMetadata: qt_method!(fn(&self)),
     closeDatabase: qt_method!(fn(&mut self)),
+    saveDatabase: qt_method!(fn(&self)),
     deleteDatabase: qt_method!(fn(&self, db_name: String)),
 
     // group and entry management
@@ -193,7 +211,41 @@ pub struct KeepassRx {
     getEntries: qt_method!(fn(&self, group_uuid: QString, search_term: QString)),
     getSingleEntry: qt_method!(fn(&self, entry_uuid: QString)),
     getTotp: qt_method!(fn(&self, entry_uuid: QString)),
+    advanceHotp: qt_method!(fn(&self, entry_uuid: QString)),
+    watchTotp: qt_method!(fn(&self, entry_uuid: QString)),
+    unwatchTotp: qt_method!(fn(&self, entry_uuid: QString)),
+    revealOtpQrCode: qt_method!(fn(&self, entry_uuid: QString, format: QString)),
+    getEntryHistory: qt_method!(fn(&self, entry_uuid: QString)),
+    restoreEntryRevision: qt_method!(fn(&self, entry_uuid: QString, index: i32)),
+    getPasswordHealth: qt_method!(fn(&self)),
     getFieldValue: qt_method!(fn(&self, entry_uuid: QString, field_name: QString)),
+    updateEntry: qt_method!(
+        fn(
+            &self,
+            entry_uuid: QString,
+            title: QString,
+            username: QString,
+            password: QString,
+            url: QString,
+            notes: QString,
+        )
+    ),
+    createEntry: qt_method!(fn(&self, group_uuid: QString, title: QString)),
+    importOtpMigration: qt_method!(fn(&self, group_uuid: QString, migration_uri: QString)),
+    createGroup: qt_method!(fn(&self, parent_uuid: QString, name: QString)),
+    deleteEntry: qt_method!(fn(&self, entry_uuid: QString)),
+    deleteGroup: qt_method!(fn(&self, group_uuid: QString)),
+    moveEntry: qt_method!(fn(&self, entry_uuid: QString, new_parent: QString)),
+    moveGroup: qt_method!(fn(&self, group_uuid: QString, new_parent: QString)),
+    exportAttachment: qt_method!(
+        fn(&self, entry_uuid: QString, attachment_name: QString, destination: String)
+    ),
+    exportCsv: qt_method!(fn(&self, container_uuid: QString, master_password: QString)),
+    exportXml: qt_method!(fn(&self, master_password: QString)),
+    cleanupDatabaseExport: qt_method!(fn(&self, path: QString) -> bool),
+    readCsvColumns: qt_method!(fn(&self, path: QString, preset: QString) -> QVariantMap),
+    importCsv: qt_method!(fn(&self, group_uuid: QString, path: QString, columns: QString)),
+    importFile: qt_method!(fn(&self, group_uuid: QString, path: QString, format: QString)),
 
     // easy-open management
     storeMasterPassword: qt_method!(fn(&self, master_password: QString)),
@@ -204,6 +256,21 @@ pub struct KeepassRx {
 
     // misc utility functions
     washOutColor: qt_method!(fn(&self, hex_color: QString) -> QVariantMap),
+    generatePassword: qt_method!(
+        fn(
+            &self,
+            length: i32,
+            lowercase: bool,
+            uppercase: bool,
+            digits: bool,
+            symbols: bool,
+            exclude_look_alikes: bool,
+            min_per_class: i32,
+        ) -> QVariantMap
+    ),
+    generatePassphrase: qt_method!(
+        fn(&self, word_count: i32, separator: QString, capitalize: bool) -> QVariantMap
+    ),
 
     // db management signals
     viewModeChanged: qt_signal!(value: RxViewMode),
@@ -215,6 +282,9 @@ pub struct KeepassRx {
     databaseClosed: qt_signal!(),
     databaseDeleted: qt_signal!(db_name: QString),
     databaseOpenFailed: qt_signal!(message: String),
+    databaseDirtyChanged: qt_signal!(),
+    databaseSaved: qt_signal!(),
+    databaseSaveFailed: qt_signal!(message: String),
     keyFileSet: qt_signal!(),
 
     // data signals
@@ -223,8 +293,25 @@ pub struct KeepassRx {
     entriesReceived: qt_signal!(entries: QStringList),
     errorReceived: qt_signal!(error: String),
     totpReceived: qt_signal!(totp: QVariantMap),
+    otpQrCodeReceived: qt_signal!(entry_uuid: QString, qr_code: QVariantMap),
     singleEntryReceived: qt_signal!(entry: QVariant),
+    entryHistoryReceived: qt_signal!(entry_uuid: QString, history: QVariantList),
+    passwordHealthReceived: qt_signal!(health: QVariantMap),
     fieldValueReceived: qt_signal!(entry_uuid: QString, field_name: QString, field_value: QString, field_extra: QString),
+    entryUpdated: qt_signal!(entry_uuid: QString),
+    entryCreated: qt_signal!(entry_uuid: QString),
+    otpEntriesImported: qt_signal!(count: i32),
+    entryDeleted: qt_signal!(entry_uuid: QString),
+    groupCreated: qt_signal!(group_uuid: QString),
+    groupDeleted: qt_signal!(group_uuid: QString),
+    entryMoved: qt_signal!(entry_uuid: QString, new_parent: QString),
+    groupMoved: qt_signal!(group_uuid: QString, new_parent: QString),
+    attachmentExported: qt_signal!(entry_uuid: QString, attachment_name: QString, path: QString),
+    attachmentExportFailed: qt_signal!(entry_uuid: QString, attachment_name: QString, error: String),
+    databaseExported: qt_signal!(export: QVariantMap),
+    databaseExportFailed: qt_signal!(error: String),
+    entriesImported: qt_signal!(import: QVariantMap),
+    entryImportFailed: qt_signal!(error: String),
 
     // easy-open signals
     masterPasswordStored: qt_signal!(),
@@ -373,6 +460,12 @@ impl KeepassRx {
         actix::spawn(actor.send(CloseDatabase));
     }
 
+    #[with_executor]
+    pub fn saveDatabase(&self) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        actix::spawn(actor.send(SaveDatabase));
+    }
+
     #[with_executor]
     pub fn deleteDatabase(&self, db_name: String) {
         let actor = self.actor.clone().expect("Actor not initialized");
@@ -429,6 +522,46 @@ impl KeepassRx {
         }
     }
 
+    /// Older revisions of an entry, oldest first. Arrives via the
+    /// entryHistoryReceived signal.
+    #[with_executor]
+    pub fn getEntryHistory(&self, entry_uuid: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+
+        match maybe_uuid {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(GetEntryHistory { entry_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Counts of entries by password quality, for the whole
+    /// database. Also checks for breached passwords, if the password
+    /// list is on the device.
+    #[with_executor]
+    pub fn getPasswordHealth(&self) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        actix::spawn(actor.send(GetPasswordHealth));
+    }
+
+    /// Restore a revision, by its historyIndex. The current state of
+    /// the entry goes into the history.
+    #[with_executor]
+    pub fn restoreEntryRevision(&self, entry_uuid: QString, index: i32) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+
+        match (maybe_uuid, usize::try_from(index)) {
+            (Ok(entry_uuid), Ok(index)) => {
+                actix::spawn(actor.send(RestoreEntryRevision { entry_uuid, index }));
+            }
+            (Err(err), _) => self.errorReceived(format!("{}", err)),
+            (_, Err(err)) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
     #[with_executor]
     pub fn getTotp(&self, entry_uuid: QString) {
         let entry_uuid = entry_uuid.to_string();
@@ -436,6 +569,60 @@ impl KeepassRx {
         actix::spawn(actor.send(GetTotp { entry_uuid }));
     }
 
+    /// Push the codes of the entry (as totpReceived) every time they
+    /// roll over, until unwatched.
+    #[with_executor]
+    pub fn watchTotp(&self, entry_uuid: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        match Uuid::from_str(&entry_uuid.to_string()) {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(WatchTotp { entry_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Render the entry's one-time password as a QR code (png or svg).
+    /// Only called after the user explicitly asks to reveal it.
+    #[with_executor]
+    pub fn revealOtpQrCode(&self, entry_uuid: QString, format: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+        let maybe_format = RxQrFormat::try_from(format.to_string().as_str());
+
+        match (maybe_uuid, maybe_format) {
+            (Ok(entry_uuid), Ok(format)) => {
+                actix::spawn(actor.send(RevealOtpQrCode { entry_uuid, format }));
+            }
+            (Err(err), _) => self.errorReceived(format!("{}", err)),
+            (_, Err(err)) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    #[with_executor]
+    pub fn unwatchTotp(&self, entry_uuid: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        match Uuid::from_str(&entry_uuid.to_string()) {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(UnwatchTotp { entry_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    #[with_executor]
+    pub fn advanceHotp(&self, entry_uuid: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+
+        match maybe_uuid {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(AdvanceHotp { entry_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
     #[with_executor]
     pub fn storeMasterPassword(&self, master_password: QString) {
         let actor = self.actor.clone().expect("Actor not initialized");
@@ -486,10 +673,430 @@ impl KeepassRx {
         }
     }
 
+    /// Change the standard fields of an entry. Empty values clear
+    /// the field.
+    #[with_executor]
+    pub fn updateEntry(
+        &self,
+        entry_uuid: QString,
+        title: QString,
+        username: QString,
+        password: QString,
+        url: QString,
+        notes: QString,
+    ) {
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        let to_value = |value: QString| match value.to_string() {
+            value if value.is_empty() => None,
+            value => Some(SecUtf8::from(value)),
+        };
+
+        let fields = vec![
+            (RxFieldName::Title, to_value(title)),
+            (RxFieldName::Username, to_value(username)),
+            (RxFieldName::Password, to_value(password)),
+            (RxFieldName::Url, to_value(url)),
+            (RxFieldName::Notes, to_value(notes)),
+        ];
+
+        match maybe_uuid {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(UpdateEntry { entry_uuid, fields }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    #[with_executor]
+    pub fn createEntry(&self, group_uuid: QString, title: QString) {
+        let maybe_uuid = Uuid::from_str(&group_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(group_uuid) => {
+                actix::spawn(actor.send(CreateEntry {
+                    group_uuid,
+                    title: SecUtf8::from(title.to_string()),
+                }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Create an entry in the group for every one-time password in a
+    /// Google Authenticator export.
+    #[with_executor]
+    pub fn importOtpMigration(&self, group_uuid: QString, migration_uri: QString) {
+        let maybe_uuid = Uuid::from_str(&group_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(group_uuid) => {
+                actix::spawn(actor.send(ImportOtpMigration {
+                    group_uuid,
+                    migration_uri: SecUtf8::from(migration_uri.to_string()),
+                }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    #[with_executor]
+    pub fn createGroup(&self, parent_uuid: QString, name: QString) {
+        let maybe_uuid = Uuid::from_str(&parent_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(parent_uuid) => {
+                actix::spawn(actor.send(CreateGroup {
+                    parent_uuid,
+                    name: name.to_string(),
+                }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Delete an entry. Moves it to the recycle bin, if there is one.
+    #[with_executor]
+    pub fn deleteEntry(&self, entry_uuid: QString) {
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(DeleteEntry { entry_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Delete a group. Moves it to the recycle bin, if there is one.
+    #[with_executor]
+    pub fn deleteGroup(&self, group_uuid: QString) {
+        let maybe_uuid = Uuid::from_str(&group_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(group_uuid) => {
+                actix::spawn(actor.send(DeleteGroup { group_uuid }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    #[with_executor]
+    pub fn moveEntry(&self, entry_uuid: QString, new_parent: QString) {
+        let maybe_uuids = Uuid::from_str(&entry_uuid.to_string())
+            .and_then(|entry| Ok((entry, Uuid::from_str(&new_parent.to_string())?)));
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuids {
+            Ok((entry_uuid, new_parent)) => {
+                actix::spawn(actor.send(MoveEntry {
+                    entry_uuid,
+                    new_parent,
+                }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Move a group into another group. Fails if the new parent is
+    /// inside the group being moved.
+    #[with_executor]
+    pub fn moveGroup(&self, group_uuid: QString, new_parent: QString) {
+        let maybe_uuids = Uuid::from_str(&group_uuid.to_string())
+            .and_then(|group| Ok((group, Uuid::from_str(&new_parent.to_string())?)));
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuids {
+            Ok((group_uuid, new_parent)) => {
+                actix::spawn(actor.send(MoveGroup {
+                    group_uuid,
+                    new_parent,
+                }));
+            }
+            Err(err) => self.errorReceived(format!("{}", err)),
+        }
+    }
+
+    /// Write a decrypted attachment to a user-chosen location. The
+    /// result comes back through attachmentExported or
+    /// attachmentExportFailed.
+    #[with_executor]
+    pub fn exportAttachment(
+        &self,
+        entry_uuid: QString,
+        attachment_name: QString,
+        destination: String,
+    ) {
+        let maybe_uuid = Uuid::from_str(&entry_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(entry_uuid) => {
+                actix::spawn(actor.send(ExportAttachment {
+                    entry_uuid,
+                    attachment_name: attachment_name.to_string(),
+                    destination: PathBuf::from(destination),
+                }));
+            }
+            Err(err) => {
+                self.attachmentExportFailed(entry_uuid, attachment_name, format!("{}", err))
+            }
+        }
+    }
+
+    /// Export a group (and everything below it) as KeePassXC CSV, to
+    /// a temporary file that can be handed off to another app. The
+    /// result comes back through databaseExported or
+    /// databaseExportFailed.
+    #[with_executor]
+    pub fn exportCsv(&self, container_uuid: QString, master_password: QString) {
+        let maybe_uuid = Uuid::from_str(&container_uuid.to_string());
+        let actor = self.actor.clone().expect("Actor not initialized");
+
+        match maybe_uuid {
+            Ok(container_uuid) => {
+                actix::spawn(actor.send(ExportCsv {
+                    container_uuid,
+                    master_password: SecUtf8::from(master_password.to_string()),
+                }));
+            }
+            Err(err) => self.databaseExportFailed(format!("{}", err)),
+        }
+    }
+
+    /// Export the whole database as KeePass 2.x XML, to a temporary
+    /// file that can be handed off to another app. The result comes
+    /// back through databaseExported or databaseExportFailed.
+    #[with_executor]
+    pub fn exportXml(&self, master_password: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        actix::spawn(actor.send(ExportXml {
+            master_password: SecUtf8::from(master_password.to_string()),
+        }));
+    }
+
+    /// Wipe a file written by a database export. Only files inside the
+    /// temporary exports directory can be removed.
+    #[with_executor]
+    pub fn cleanupDatabaseExport(&self, path: QString) -> bool {
+        let path = PathBuf::from(path.to_string());
+        let export_dir = exported_databases_path();
+
+        let is_export = match (path.canonicalize(), export_dir.canonicalize()) {
+            (Ok(path), Ok(export_dir)) => path.starts_with(export_dir),
+            _ => false,
+        };
+
+        if !is_export {
+            println!("Refusing to clean up non-export file: {}", path.display());
+            return false;
+        }
+
+        match wipe_file(&path) {
+            Ok(_) => true,
+            Err(err) => {
+                println!("Failed to wipe {}: {}", path.display(), err);
+                false
+            }
+        }
+    }
+
+    /// The columns of a CSV file, and what each one holds according
+    /// to the preset. With no preset, it is guessed from the columns.
+    #[with_executor]
+    pub fn readCsvColumns(&self, path: QString, preset: QString) -> QVariantMap {
+        let read_header = || -> Result<(Vec<String>, RxCsvPreset)> {
+            // Only the header is read, the rest of the file is not
+            // needed yet.
+            let mut line = String::new();
+            BufReader::new(File::open(path.to_string())?).read_line(&mut line)?;
+            let header = read_csv_header(&line)?;
+
+            let preset = match preset.to_string().as_str() {
+                "" => {
+                    let names: Vec<_> = header.iter().map(String::as_str).collect();
+                    RxCsvPreset::detect(&names)
+                }
+                preset => RxCsvPreset::try_from(preset)?,
+            };
+
+            Ok((header, preset))
+        };
+
+        let mut map = QVariantMap::default();
+        match read_header() {
+            Ok((header, preset)) => {
+                let columns: QVariantList = header
+                    .iter()
+                    .map(|column| QString::from(column.as_str()).to_qvariant())
+                    .collect();
+
+                let mapping: QVariantList = header
+                    .iter()
+                    .map(|column| {
+                        QString::from(preset.column_for(column).as_str()).to_qvariant()
+                    })
+                    .collect();
+
+                map.insert("ok".into(), true.into());
+                map.insert("preset".into(), QString::from(preset.as_str()).into());
+                map.insert("columns".into(), columns.to_qvariant());
+                map.insert("mapping".into(), mapping.to_qvariant());
+            }
+            Err(err) => {
+                map.insert("ok".into(), false.into());
+                map.insert("error".into(), QString::from(format!("{}", err)).into());
+            }
+        }
+
+        map
+    }
+
+    /// Import the entries of a CSV file into the group. The columns
+    /// are a comma-separated list of what each column holds. The
+    /// result comes back through entriesImported or entryImportFailed.
+    #[with_executor]
+    pub fn importCsv(&self, group_uuid: QString, path: QString, columns: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let columns: Result<Vec<_>> = columns
+            .to_string()
+            .split(',')
+            .map(RxCsvColumn::try_from)
+            .collect();
+
+        match (Uuid::from_str(&group_uuid.to_string()), columns) {
+            (Ok(group_uuid), Ok(columns)) => {
+                actix::spawn(actor.send(ImportEntries {
+                    group_uuid,
+                    path: PathBuf::from(path.to_string()),
+                    format: RxImportFormat::Csv(columns),
+                }));
+            }
+            (Err(err), _) => self.entryImportFailed(format!("{}", err)),
+            (_, Err(err)) => self.entryImportFailed(format!("{}", err)),
+        }
+    }
+
+    /// Import the entries of another password manager's export that
+    /// needs no column mapping, like Bitwarden's JSON export. The
+    /// result comes back through entriesImported or entryImportFailed.
+    #[with_executor]
+    pub fn importFile(&self, group_uuid: QString, path: QString, format: QString) {
+        let actor = self.actor.clone().expect("Actor not initialized");
+        let format = RxImportFormat::try_from(format.to_string().as_str());
+
+        match (Uuid::from_str(&group_uuid.to_string()), format) {
+            (Ok(group_uuid), Ok(format)) => {
+                actix::spawn(actor.send(ImportEntries {
+                    group_uuid,
+                    path: PathBuf::from(path.to_string()),
+                    format,
+                }));
+            }
+            (Err(err), _) => self.entryImportFailed(format!("{}", err)),
+            (_, Err(err)) => self.entryImportFailed(format!("{}", err)),
+        }
+    }
+
     #[with_executor]
     pub fn washOutColor(&self, hex_color: QString) -> QVariantMap {
         wash_out_by_blending(&hex_color.to_string(), 0.5)
             .expect("No color generated")
             .into()
     }
+
+    #[with_executor]
+    pub fn generatePassword(
+        &self,
+        length: i32,
+        lowercase: bool,
+        uppercase: bool,
+        digits: bool,
+        symbols: bool,
+        exclude_look_alikes: bool,
+        min_per_class: i32,
+    ) -> QVariantMap {
+        let classes = RxCharClass::ALL
+            .into_iter()
+            .zip([lowercase, uppercase, digits, symbols])
+            .filter_map(|(class, wanted)| wanted.then_some(class))
+            .collect();
+
+        let generate = || -> Result<RxGenerated> {
+            let profile = RxPasswordProfile {
+                length: generated_size(length, "Password length")?,
+                classes,
+                exclude_look_alikes,
+                min_per_class: min_per_class.max(0) as usize,
+            };
+
+            generate_password(&profile)
+        };
+
+        generated_to_map(generate())
+    }
+
+    #[with_executor]
+    pub fn generatePassphrase(
+        &self,
+        word_count: i32,
+        separator: QString,
+        capitalize: bool,
+    ) -> QVariantMap {
+        let generate = || -> Result<RxGenerated> {
+            let profile = RxPassphraseProfile {
+                word_count: generated_size(word_count, "Word count")?,
+                separator: separator.to_string(),
+                capitalize,
+            };
+
+            generate_passphrase(&RxWordlist::eff_large(), &profile)
+        };
+
+        generated_to_map(generate())
+    }
+}
+
+/// A generated password for the UI: the value, its entropy, and its
+/// quality rating. Or the error, if generation failed.
+/// Upper bound for generated password lengths and passphrase word
+/// counts coming from QML.
+const MAX_GENERATED_SIZE: i32 = 1024;
+
+fn generated_size(size: i32, what: &str) -> Result<usize> {
+    match size {
+        1..=MAX_GENERATED_SIZE => Ok(size as usize),
+        _ => Err(anyhow!(
+            "{} must be between 1 and {}",
+            what,
+            MAX_GENERATED_SIZE
+        )),
+    }
+}
+
+fn generated_to_map(generated: Result<RxGenerated>) -> QVariantMap {
+    let mut map = QVariantMap::default();
+
+    match generated {
+        Ok(generated) => {
+            let value = String::from_utf8_lossy(&generated.value);
+            map.insert("value".into(), QString::from(value.as_ref()).into());
+            map.insert("entropy".into(), generated.entropy.into());
+            map.insert(
+                "quality".into(),
+                QString::from(generated.quality().as_str()).into(),
+            );
+        }
+        Err(err) => {
+            map.insert("error".into(), QString::from(format!("{}", err)).into());
+        }
+    }
+
+    map
 }
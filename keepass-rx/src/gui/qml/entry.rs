@@ -1,12 +1,49 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
 use actix::prelude::*;
 use actor_macro::observing_model;
+use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
 use qmeta_async::with_executor;
 use qmetaobject::prelude::*;
+use qmetaobject::{QVariantMap, SimpleListItem, SimpleListModel};
+use uuid::Uuid;
 
 use crate::{
-    actor::ConnectedModelActor, app::AppState, rx::virtual_hierarchy::VirtualHierarchy,
+    actor::ConnectedModelActor,
+    app::AppState,
+    gui::actor::export_attachment,
+    gui::utils::{exported_attachments_path, wipe_file},
+    rx::{RxAttachment, RxAttachmentViewType, virtual_hierarchy::VirtualHierarchy},
 };
 
+/// A single attachment row in the attachments list model. The field
+/// names are the role names used in QML.
+#[derive(Default, Clone, SimpleListItem)]
+#[allow(non_snake_case)]
+pub struct RxUiAttachment {
+    pub attachmentName: QString,
+    pub attachmentSize: i32,
+    pub attachmentMimeType: QString,
+    pub attachmentViewType: QString,
+}
+
+impl From<&RxAttachment> for RxUiAttachment {
+    fn from(value: &RxAttachment) -> Self {
+        RxUiAttachment {
+            attachmentName: QString::from(value.name.as_str()),
+            attachmentSize: i32::try_from(value.size).unwrap_or(i32::MAX),
+            attachmentMimeType: QString::from(value.mime_type.as_str()),
+            attachmentViewType: value
+                .view_type()
+                .map(|vt| QString::from(vt.as_str()))
+                .unwrap_or_default(),
+        }
+    }
+}
+
 /// A QObject that is wired to interact with a database entry via the
 /// app actor.
 #[observing_model]
@@ -16,13 +53,19 @@ pub struct RxUiEntry {
     pub(super) base: qt_base_class!(trait QObject),
 
     pub(super) entryUuid: qt_property!(QString),
-    pub(super) currentTotp: qt_property!(QString; NOTIFY currentTotpChanged),
-    pub(super) currentTotpValidFor: qt_property!(QString; NOTIFY currentTotpValidForChanged),
 
-    pub(super) currentTotpChanged: qt_signal!(),
-    pub(super) currentTotpValidForChanged: qt_signal!(),
+    pub(super) attachments: qt_property!(RefCell<SimpleListModel<RxUiAttachment>>; CONST),
+    pub(super) attachmentCount: qt_property!(i32; NOTIFY attachmentsChanged),
+    pub(super) darkTheme: qt_property!(bool),
+
+    pub(super) attachmentsChanged: qt_signal!(),
 
-    pub(super) updateTotp: qt_method!(fn(&mut self)),
+    pub(super) loadAttachments: qt_method!(fn(&mut self)),
+    pub(super) viewAttachment: qt_method!(fn(&self, name: QString) -> QVariantMap),
+    pub(super) exportAttachment: qt_method!(fn(&self, name: QString) -> QVariantMap),
+    pub(super) exportAttachmentTo:
+        qt_method!(fn(&self, name: QString, path: QString) -> QVariantMap),
+    pub(super) cleanupExportedAttachment: qt_method!(fn(&self, path: QString) -> bool),
 }
 
 #[allow(dead_code, non_snake_case)]
@@ -36,26 +79,170 @@ impl RxUiEntry {
             .map(|reg| reg.actor.clone())
     }
 
+    fn entry_uuid(&self) -> Result<Uuid> {
+        Ok(Uuid::from_str(&self.entryUuid.to_string())?)
+    }
+
     #[with_executor]
-    pub fn updateTotp(&mut self) {
+    pub fn loadAttachments(&mut self) {
         let app_state = self._app.as_pinned().expect("No app state");
         let app_state = app_state.borrow();
 
-        let maybe_db = app_state.curr_db();
+        let attachments: Vec<RxUiAttachment> = self
+            .entry_uuid()
+            .and_then(|uuid| {
+                let db = app_state.curr_db()?;
+                db.get_entry(uuid).ok_or(anyhow!("Could not find entry"))
+            })
+            .map(|entry| entry.attachments().map(RxUiAttachment::from).collect())
+            .unwrap_or_default();
 
-        let totp = maybe_db.and_then(|db| db.get_totp(&self.entryUuid.to_string()));
+        self.attachmentCount = attachments.len() as i32;
+        self.attachments.borrow_mut().reset_data(attachments);
+        self.attachmentsChanged();
+    }
 
-        if let Ok(totp) = totp {
-            let totp_code = QString::from(totp.code);
-            let valid_for = QString::from(totp.valid_for);
+    fn view_attachment(&self, name: &str) -> Result<QVariantMap> {
+        let app_state = self._app.as_pinned().expect("No app state");
+        let app_state = app_state.borrow();
+        let db = app_state.curr_db()?;
+
+        let entry_uuid = self.entry_uuid()?;
+        let entry = db
+            .get_entry(entry_uuid)
+            .ok_or(anyhow!("Could not find entry"))?;
+
+        let attachment = entry
+            .get_attachment(name)
+            .ok_or(anyhow!("No attachment named {}", name))?;
 
-            if totp_code != self.currentTotp {
-                self.currentTotp = totp_code;
-                self.currentTotpChanged();
+        let view_type = attachment.view_type();
+
+        let mut map = QVariantMap::default();
+        map.insert("ok".into(), true.into());
+        map.insert("canView".into(), view_type.is_some().into());
+        map.insert("fileName".into(), QString::from(name).into());
+        map.insert(
+            "mimeType".into(),
+            QString::from(attachment.mime_type.as_str()).into(),
+        );
+        map.insert(
+            "viewType".into(),
+            view_type
+                .map(|vt| QString::from(vt.as_str()))
+                .unwrap_or_default()
+                .into(),
+        );
+
+        // Only decrypt the attachment if we can actually show it.
+        match view_type {
+            Some(RxAttachmentViewType::Text) => {
+                let data = db.get_attachment(entry_uuid, name)?;
+                let text = std::str::from_utf8(&data)?;
+                map.insert("text".into(), QString::from(text).into());
+            }
+            Some(RxAttachmentViewType::Image) => {
+                let data = db.get_attachment(entry_uuid, name)?;
+                let data_url = format!(
+                    "data:{};base64,{}",
+                    attachment.mime_type,
+                    BASE64_STANDARD.encode(&data)
+                );
+                map.insert("dataUrl".into(), QString::from(data_url).into());
             }
-            if valid_for != self.currentTotpValidFor {
-                self.currentTotpValidFor = valid_for;
-                self.currentTotpValidForChanged();
+            None => (),
+        }
+
+        Ok(map)
+    }
+
+    #[with_executor]
+    pub fn viewAttachment(&self, name: QString) -> QVariantMap {
+        self.view_attachment(&name.to_string())
+            .unwrap_or_else(|err| {
+                let mut map = QVariantMap::default();
+                map.insert("ok".into(), false.into());
+                map.insert("canView".into(), false.into());
+                map.insert("error".into(), QString::from(err.to_string()).into());
+                map
+            })
+    }
+
+    fn export_to(&self, name: &str, destination: &Path) -> Result<QVariantMap> {
+        let app_state = self._app.as_pinned().expect("No app state");
+        let app_state = app_state.borrow();
+        let db = app_state.curr_db()?;
+
+        let path = export_attachment(&db, self.entry_uuid()?, name, destination)?;
+        let path_str = path.to_string_lossy().to_string();
+        let file_name = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut map = QVariantMap::default();
+        map.insert("ok".into(), true.into());
+        map.insert("fileName".into(), QString::from(file_name).into());
+        map.insert(
+            "url".into(),
+            QString::from(format!("file://{}", path_str)).into(),
+        );
+        map.insert("path".into(), QString::from(path_str).into());
+
+        Ok(map)
+    }
+
+    fn export_result(result: Result<QVariantMap>) -> QVariantMap {
+        result.unwrap_or_else(|err| {
+            let mut map = QVariantMap::default();
+            map.insert("ok".into(), false.into());
+            map.insert("error".into(), QString::from(err.to_string()).into());
+            map
+        })
+    }
+
+    /// Export to the temporary attachments directory, so that the
+    /// file can be handed off to another app. The caller must clean
+    /// up the file with cleanupExportedAttachment afterwards.
+    #[with_executor]
+    pub fn exportAttachment(&self, name: QString) -> QVariantMap {
+        let export_dir = exported_attachments_path();
+        let result = std::fs::create_dir_all(&export_dir)
+            .map_err(Into::into)
+            .and_then(|_| self.export_to(&name.to_string(), &export_dir));
+
+        Self::export_result(result)
+    }
+
+    /// Export directly to a user-chosen file or directory.
+    #[with_executor]
+    pub fn exportAttachmentTo(&self, name: QString, path: QString) -> QVariantMap {
+        let destination = PathBuf::from(path.to_string());
+        Self::export_result(self.export_to(&name.to_string(), &destination))
+    }
+
+    /// Wipe a file created by exportAttachment. Only files inside the
+    /// temporary attachments directory can be removed.
+    #[with_executor]
+    pub fn cleanupExportedAttachment(&self, path: QString) -> bool {
+        let path = PathBuf::from(path.to_string());
+        let export_dir = exported_attachments_path();
+
+        let is_export = match (path.canonicalize(), export_dir.canonicalize()) {
+            (Ok(path), Ok(export_dir)) => path.starts_with(export_dir),
+            _ => false,
+        };
+
+        if !is_export {
+            println!("Refusing to clean up non-export file: {}", path.display());
+            return false;
+        }
+
+        match wipe_file(&path) {
+            Ok(_) => true,
+            Err(err) => {
+                println!("Failed to wipe {}: {}", path.display(), err);
+                false
             }
         }
     }
@@ -8,7 +8,9 @@ use uuid::Uuid;
 
 use crate::app::AppState;
 use crate::rx::virtual_hierarchy::{RxViewFeature, VirtualHierarchy};
-use crate::rx::{RxContainedRef, RxEntry, RxGroup, RxTag, RxTemplate};
+use crate::rx::{
+    RxCategory, RxContainedRef, RxEntry, RxGroup, RxSavedSearch, RxTag, RxTemplate,
+};
 
 #[derive(QEnum, Clone, Default, Copy, PartialEq, Eq)]
 #[repr(C)]
@@ -18,6 +20,8 @@ pub enum RxItemType {
     Group,
     Template,
     Tag,
+    SavedSearch,
+    Category,
 }
 
 fn entry_type_from_string(qval: &QString) -> RxItemType {
@@ -26,6 +30,8 @@ fn entry_type_from_string(qval: &QString) -> RxItemType {
         "Entry" => RxItemType::Entry,
         "Template" => RxItemType::Template,
         "Tag" => RxItemType::Tag,
+        "SavedSearch" => RxItemType::SavedSearch,
+        "Category" => RxItemType::Category,
         _ => panic!("Invalid entry type: {}", qval),
     }
 }
@@ -36,6 +42,8 @@ fn entry_type_to_string(entry_type: &RxItemType) -> QString {
         RxItemType::Entry => "Entry",
         RxItemType::Template => "Template",
         RxItemType::Tag => "Tag",
+        RxItemType::SavedSearch => "SavedSearch",
+        RxItemType::Category => "Category",
     }
     .into()
 }
@@ -101,6 +109,7 @@ pub struct RxListItem {
     pub(super) hasPassword: qt_property!(bool; NOTIFY hasPasswordChanged),
     pub(super) hasURL: qt_property!(bool; NOTIFY hasURLChanged),
     pub(super) hasTOTP: qt_property!(bool; NOTIFY hasTOTPChanged),
+    pub(super) hasHOTP: qt_property!(bool; NOTIFY hasHOTPChanged),
 
     // Signals boilerplate
     itemTypeChanged: qt_signal!(),
@@ -117,6 +126,7 @@ pub struct RxListItem {
     hasPasswordChanged: qt_signal!(),
     hasURLChanged: qt_signal!(),
     hasTOTPChanged: qt_signal!(),
+    hasHOTPChanged: qt_signal!(),
 }
 
 trait InitFrom<T> {
@@ -169,6 +179,7 @@ impl RxListItem {
         set_value!(self.hasPassword, false);
         set_value!(self.hasURL, false);
         set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
 
         set_value!(self.iconPath, QString::default());
         set_value!(self.iconBuiltin, false);
@@ -184,6 +195,8 @@ impl InitFrom<RxContainedRef> for RxListItem {
             RxContainedRef::Group(group) => self.init_from(group.as_ref()),
             RxContainedRef::Template(template) => self.init_from(template.as_ref()),
             RxContainedRef::Tag(tag) => self.init_from(&tag),
+            RxContainedRef::SavedSearch(search) => self.init_from(&search),
+            RxContainedRef::Category(category) => self.init_from(&category),
             RxContainedRef::VirtualRoot(root_name) => self.init_from_virtual_root(root_name),
         }
     }
@@ -200,6 +213,7 @@ impl InitFrom<&RxTag> for RxListItem {
         set_value!(self.hasPassword, false);
         set_value!(self.hasURL, false);
         set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
 
         set_value!(self.iconPath, QString::default());
         set_value!(self.iconBuiltin, false);
@@ -209,6 +223,48 @@ impl InitFrom<&RxTag> for RxListItem {
     }
 }
 
+impl InitFrom<&RxSavedSearch> for RxListItem {
+    fn init_from(&mut self, value: &RxSavedSearch) {
+        set_value!(self.itemType, RxItemType::SavedSearch);
+        set_value!(self.entryUuid, QString::from(value.uuid.to_string()));
+        set_value!(self.parentUuid, QString::default());
+        set_value!(self.feature, RxViewFeature::None);
+
+        set_value!(self.hasUsername, false);
+        set_value!(self.hasPassword, false);
+        set_value!(self.hasURL, false);
+        set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
+
+        set_value!(self.iconPath, QString::default());
+        set_value!(self.iconBuiltin, false);
+        set_value!(self.title, QString::from(value.name.as_ref()));
+        set_value!(self.subtitle, QString::from(value.query.as_ref()));
+        set_value!(self.description, entry_count(&value.entry_uuids));
+    }
+}
+
+impl InitFrom<&RxCategory> for RxListItem {
+    fn init_from(&mut self, value: &RxCategory) {
+        set_value!(self.itemType, RxItemType::Category);
+        set_value!(self.entryUuid, QString::from(value.uuid.to_string()));
+        set_value!(self.parentUuid, QString::default());
+        set_value!(self.feature, RxViewFeature::None);
+
+        set_value!(self.hasUsername, false);
+        set_value!(self.hasPassword, false);
+        set_value!(self.hasURL, false);
+        set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
+
+        set_value!(self.iconPath, QString::default());
+        set_value!(self.iconBuiltin, false);
+        set_value!(self.title, QString::from(value.name.as_ref()));
+        set_value!(self.subtitle, QString::from(value.description.as_ref()));
+        set_value!(self.description, entry_count(&value.entry_uuids));
+    }
+}
+
 impl InitFrom<&RxTemplate> for RxListItem {
     fn init_from(&mut self, value: &RxTemplate) {
         set_value!(self.itemType, RxItemType::Template);
@@ -220,6 +276,7 @@ impl InitFrom<&RxTemplate> for RxListItem {
         set_value!(self.hasPassword, false);
         set_value!(self.hasURL, false);
         set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
 
         set_value!(
             self.iconPath,
@@ -257,7 +314,8 @@ impl InitFrom<&RxEntry> for RxListItem {
         set_value!(self.hasUsername, value.username().is_some());
         set_value!(self.hasPassword, value.password().is_some());
         set_value!(self.hasURL, value.url().is_some());
-        set_value!(self.hasTOTP, value.raw_otp_value().is_some());
+        set_value!(self.hasTOTP, value.has_otp());
+        set_value!(self.hasHOTP, value.has_hotp());
 
         set_value!(
             self.iconPath,
@@ -349,6 +407,7 @@ impl InitFrom<&RxGroup> for RxListItem {
         set_value!(self.hasPassword, false);
         set_value!(self.hasURL, false);
         set_value!(self.hasTOTP, false);
+        set_value!(self.hasHOTP, false);
     }
 }
 
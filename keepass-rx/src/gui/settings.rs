@@ -82,6 +82,7 @@ impl SettingsBridge {
         match search_type.as_str() {
             "CaseInsensitive" => RxSearchType::CaseInsensitive,
             "Fuzzy" => RxSearchType::Fuzzy,
+            "Advanced" => RxSearchType::Advanced,
             _ => RxSearchType::default(),
         }
     }
@@ -92,6 +93,7 @@ impl SettingsBridge {
                 self.set_string("searchType".into(), "CaseInsensitive".into())
             }
             RxSearchType::Fuzzy => self.set_string("searchType".into(), "Fuzzy".into()),
+            RxSearchType::Advanced => self.set_string("searchType".into(), "Advanced".into()),
         }
 
         self.searchTypeChanged();
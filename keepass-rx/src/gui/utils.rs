@@ -1,7 +1,10 @@
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
 
-use dirs::data_dir;
+use dirs::{cache_dir, data_dir};
 
 use super::RxDbType;
 
@@ -21,6 +24,124 @@ pub fn synced_databases_path() -> PathBuf {
     PathBuf::from(app_data_path()).join("synced")
 }
 
+/// Where the user can put a copy of the Have I Been Pwned password
+/// list (SHA-1, ordered by hash), to check for breached passwords
+/// without network access.
+pub fn pwned_passwords_path() -> PathBuf {
+    PathBuf::from(app_data_path()).join("pwned-passwords.txt")
+}
+
+/// Where attachments are temporarily written so they can be handed
+/// off to other apps. Files here are wiped once the transfer is done.
+pub fn exported_attachments_path() -> PathBuf {
+    let cache_dir = cache_dir().expect("no cache dir?");
+    PathBuf::from(cache_dir).join(APP_ID).join("attachments")
+}
+
+/// Where database exports (CSV and the like) are temporarily written,
+/// so they can be handed off to other apps. Files here are wiped once
+/// the transfer is done.
+pub fn exported_databases_path() -> PathBuf {
+    let cache_dir = cache_dir().expect("no cache dir?");
+    PathBuf::from(cache_dir).join(APP_ID).join("exports")
+}
+
+/// Wipe every file left in the exports directory, e.g. by an export
+/// that was never handed off, or by the app being killed before the
+/// cleanup.
+pub fn wipe_exported_databases() -> Result<()> {
+    let export_dir = exported_databases_path();
+    if !export_dir.is_dir() {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(&export_dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            wipe_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Overwrite a file with zeroes, sync it to disk, and then remove
+/// it. Used for decrypted data that should not linger on disk.
+pub fn wipe_file(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        return Err(anyhow!("{} is not a file", path.display()));
+    }
+
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let zeroes = [0u8; 4096];
+    let mut remaining = len;
+
+    file.seek(SeekFrom::Start(0))?;
+    while remaining > 0 {
+        let chunk = remaining.min(zeroes.len() as u64) as usize;
+        file.write_all(&zeroes[..chunk])?;
+        remaining -= chunk as u64;
+    }
+
+    file.sync_all()?;
+    drop(file);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Write sensitive bytes to the destination. The bytes go to a
+/// temporary file next to the destination first, which is synced
+/// and then moved into place. If anything fails along the way, the
+/// temporary file is wiped.
+pub fn write_sensitive_file(dest: &Path, bytes: &[u8]) -> Result<u64> {
+    write_sensitive_file_with(dest, |file| Ok(file.write_all(bytes)?))?;
+    Ok(bytes.len() as u64)
+}
+
+/// Like write_sensitive_file, but the contents are written by the
+/// given function. For exports that are written bit by bit, rather
+/// than decrypted into memory all at once.
+pub fn write_sensitive_file_with<T>(
+    dest: &Path,
+    write: impl FnOnce(&mut File) -> Result<T>,
+) -> Result<T> {
+    let file_name = dest
+        .file_name()
+        .ok_or(anyhow!("No filename found"))?
+        .to_string_lossy()
+        .into_owned();
+
+    let temp_path = dest.with_file_name(format!(".{}.part", file_name));
+
+    let write_temp = || -> Result<T> {
+        // Only the user can read exported secrets.
+        let mut temp_file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&temp_path)?;
+        let written = write(&mut temp_file)?;
+        temp_file.sync_all()?;
+        Ok(written)
+    };
+
+    let result = write_temp().and_then(|written| {
+        std::fs::rename(&temp_path, dest)?;
+        Ok(written)
+    });
+
+    if result.is_err() && temp_path.exists() {
+        if let Err(wipe_err) = wipe_file(&temp_path) {
+            println!("Failed to wipe {}: {}", temp_path.display(), wipe_err);
+        }
+    }
+
+    result
+}
+
 pub fn db_path_for_type(db_type: RxDbType) -> PathBuf {
     match db_type {
         RxDbType::Imported => imported_databases_path(),
@@ -60,7 +60,9 @@ fn load_gui() -> Result<()> {
     };
 
     use crate::gui::{
-        KeepassRx, RxGuiState, actor::KeepassRxActor, utils::move_old_dirs_and_files,
+        KeepassRx, RxGuiState,
+        actor::KeepassRxActor,
+        utils::{move_old_dirs_and_files, wipe_exported_databases},
     };
 
     use crate::app::KeepassRxApp;
@@ -105,6 +107,11 @@ fn load_gui() -> Result<()> {
         println!("Error during old app data migration: {}", err);
     }
 
+    // Exports from the last run must not stay on disk.
+    if let Err(err) = wipe_exported_databases() {
+        println!("Could not wipe exported databases: {}", err);
+    }
+
     qmeta_async::run(|| {
         // We must return app here because it keeps the value alive
         // for the lifetime of qmeta_async::run. Without this, any
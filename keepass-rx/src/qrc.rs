@@ -88,6 +88,11 @@ qrc!(qml_resources,
          "qml/pages/LicensesPage.qml",
          "qml/pages/LicenseTextPage.qml",
          "qml/pages/AboutPage.qml",
+         "qml/pages/EntryHistoryPage.qml",
+         "qml/pages/GeneratorPage.qml",
+         "qml/pages/OtpExportPage.qml",
+         "qml/pages/ExportPage.qml",
+         "qml/pages/ImportPage.qml",
          "qml/components/EntryItem.qml",
          "qml/components/ConfigurationGroup.qml",
          "qml/components/DetailField.qml",
@@ -64,6 +64,7 @@ pub fn calculate_entropy(password: &SecureVec<u8>) -> Result<f64> {
     Ok(entropy)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PasswordQuality {
     Bad,
     Poor,
@@ -72,6 +73,33 @@ pub enum PasswordQuality {
     Excellent,
 }
 
+impl PasswordQuality {
+    /// All quality ratings, worst first.
+    pub const ALL: [PasswordQuality; 5] = [
+        PasswordQuality::Bad,
+        PasswordQuality::Poor,
+        PasswordQuality::Weak,
+        PasswordQuality::Good,
+        PasswordQuality::Excellent,
+    ];
+
+    /// Bad, Poor, and Weak passwords should be rotated. This is what
+    /// KeePassXC considers weak.
+    pub fn is_weak(&self) -> bool {
+        *self <= PasswordQuality::Weak
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PasswordQuality::Bad => "Bad",
+            PasswordQuality::Poor => "Poor",
+            PasswordQuality::Weak => "Weak",
+            PasswordQuality::Good => "Good",
+            PasswordQuality::Excellent => "Excellent",
+        }
+    }
+}
+
 impl From<f64> for PasswordQuality {
     fn from(entropy: f64) -> Self {
         match entropy {
@@ -87,17 +115,31 @@ impl From<f64> for PasswordQuality {
 #[cfg(test)]
 mod tests {
     use super::{PasswordQuality, calculate_entropy};
+    use libsodium_rs::utils::vec_utils;
 
     // #[test]
     // fn password_entropy_empty_is_zero() {
     //     assert_eq!(calculate_entropy(""), 0.0);
     // }
 
-    // #[test]
-    // fn password_entropy_test_password_matches_kpxc_zxcvbn() {
-    //     let entropy = calculate_entropy("test password");
-    //     assert!((entropy - 16.17).abs() < 0.1);
-    // }
+    #[test]
+    fn password_entropy_test_password_matches_kpxc_zxcvbn() {
+        let password = b"test password";
+        let mut secure = vec_utils::secure_vec::<u8>(password.len()).unwrap();
+        secure.copy_from_slice(password);
+
+        let entropy = calculate_entropy(&secure).expect("could not calculate entropy");
+        assert!((entropy - 16.17).abs() < 0.1);
+    }
+
+    #[test]
+    fn weak_includes_bad_and_poor() {
+        assert!(PasswordQuality::Bad.is_weak());
+        assert!(PasswordQuality::Poor.is_weak());
+        assert!(PasswordQuality::Weak.is_weak());
+        assert!(!PasswordQuality::Good.is_weak());
+        assert!(!PasswordQuality::Excellent.is_weak());
+    }
 
     #[test]
     fn password_quality_thresholds_match_keepassxc() {
@@ -1,5 +1,5 @@
 use deunicode::deunicode_with_tofu_cow;
-use regex::RegexBuilder;
+use regex::{Regex, RegexBuilder};
 use unicase::UniCase;
 use uuid::Uuid;
 use zeroize::Zeroizing;
@@ -10,17 +10,23 @@ use super::{RxDatabase, RxEntry};
 struct QueryToken {
     operator: Option<String>,
     term: QueryTokenTerm,
+
+    /// Excluded with a leading `-`: the entry matches if none of its
+    /// fields match the term, including when it has no such field.
+    negated: bool,
 }
 
 #[derive(Debug, Clone)]
 enum QueryTokenTerm {
     Basic(String),
-    Regex(String),
-    Negated(String),
+
+    /// Compiled once when parsed. An invalid expression matches
+    /// nothing.
+    Regex(Option<Regex>),
 }
 
 fn normalized(value: &str) -> String {
-    UniCase::new(deunicode_with_tofu_cow(value, "u{FFFD}"))
+    UniCase::new(deunicode_with_tofu_cow(value, "\u{FFFD}"))
         .to_folded_case()
         .to_string()
 }
@@ -107,19 +113,25 @@ fn parse_token(token: &str) -> Option<QueryToken> {
         return None;
     }
 
-    let token_term = match term {
-        term if is_regex => QueryTokenTerm::Regex(term),
-        term if is_negated => QueryTokenTerm::Negated(term),
-        term => QueryTokenTerm::Basic(term),
+    let token_term = match is_regex {
+        true => {
+            QueryTokenTerm::Regex(RegexBuilder::new(&term).case_insensitive(true).build().ok())
+        }
+        false => QueryTokenTerm::Basic(term),
     };
 
     Some(QueryToken {
         operator: field,
         term: token_term,
+        negated: is_negated,
     })
 }
 
-fn operator_field(entry: &RxEntry, op: &str, db: &RxDatabase) -> Vec<Zeroizing<String>> {
+fn operator_field(
+    entry: &RxEntry,
+    op: &str,
+    group_name: Option<&str>,
+) -> Vec<Zeroizing<String>> {
     match op.to_lowercase().as_str() {
         "title" | "t" => entry.title().and_then(|v| v.value()).into_iter().collect(),
         "user" | "u" => entry
@@ -129,9 +141,8 @@ fn operator_field(entry: &RxEntry, op: &str, db: &RxDatabase) -> Vec<Zeroizing<S
             .collect(),
         "url" => entry.url().and_then(|v| v.value()).into_iter().collect(),
         "notes" | "n" => entry.notes().and_then(|v| v.value()).into_iter().collect(),
-        "group" | "g" => db
-            .get_group(entry.parent_group)
-            .map(|g| Zeroizing::new(g.name.to_string()))
+        "group" | "g" => group_name
+            .map(|name| Zeroizing::new(name.to_string()))
             .into_iter()
             .collect(),
         "tag" | "tags" => entry
@@ -141,31 +152,29 @@ fn operator_field(entry: &RxEntry, op: &str, db: &RxDatabase) -> Vec<Zeroizing<S
             .collect(),
         "uuid" => vec![Zeroizing::new(entry.uuid.to_string())],
         "is" => {
-            let is_expired = entry.is_expired();
-            let is_weak = entry.is_password_weak();
-
             let mut vals = vec![];
-            if is_expired {
+            if entry.is_expired() {
                 vals.push(Zeroizing::new("expired".to_string()));
             }
-            if is_weak {
+            if entry.is_password_weak() {
                 vals.push(Zeroizing::new("weak".to_string()));
             }
+            if entry.is_pwned() {
+                vals.push(Zeroizing::new("pwned".to_string()));
+            }
             vals
         }
         _ => vec![],
     }
 }
 
-fn entry_default_fields(entry: &RxEntry, db: &RxDatabase) -> Vec<Zeroizing<String>> {
+fn entry_default_fields(entry: &RxEntry, group_name: Option<&str>) -> Vec<Zeroizing<String>> {
     let title = entry.title().and_then(|v| v.value());
     let username = entry.username().and_then(|v| v.value());
     let url = entry.url().and_then(|v| v.value());
     let notes = entry.notes().and_then(|v| v.value());
     let tags = entry.tags().iter().map(|t| Zeroizing::new(t.to_string()));
-    let group_name = db
-        .get_group(entry.parent_group)
-        .map(|group| Zeroizing::new(group.name.clone()));
+    let group_name = group_name.map(|name| Zeroizing::new(name.to_string()));
 
     vec![title, username, url, notes, group_name]
         .into_iter()
@@ -174,15 +183,12 @@ fn entry_default_fields(entry: &RxEntry, db: &RxDatabase) -> Vec<Zeroizing<Strin
         .collect()
 }
 
-fn term_matches(token: &QueryToken, value: &str) -> bool {
-    match token.term {
-        QueryTokenTerm::Regex(ref regex) => RegexBuilder::new(regex)
-            .case_insensitive(true)
-            .build()
-            .map(|regex| regex.is_match(value))
-            .unwrap_or(false),
-        QueryTokenTerm::Negated(ref term) => !normalized(value).contains(&normalized(term)),
-        QueryTokenTerm::Basic(ref term) => normalized(value).contains(&normalized(term)),
+fn term_matches(term: &QueryTokenTerm, value: &str) -> bool {
+    match term {
+        QueryTokenTerm::Regex(regex) => {
+            regex.as_ref().is_some_and(|regex| regex.is_match(value))
+        }
+        QueryTokenTerm::Basic(term) => normalized(value).contains(&normalized(term)),
     }
 }
 
@@ -204,59 +210,82 @@ fn contains_ascii_case_insensitive(haystack: &[u8], needle: &[u8]) -> bool {
         .any(|window| window.eq_ignore_ascii_case(needle))
 }
 
-fn password_term_matches(token: &QueryToken, password: &[u8]) -> bool {
-    match token.term {
-        QueryTokenTerm::Regex(ref regex) => std::str::from_utf8(password)
+fn password_term_matches(term: &QueryTokenTerm, password: &[u8]) -> bool {
+    match term {
+        QueryTokenTerm::Regex(regex) => std::str::from_utf8(password)
             .ok()
-            .and_then(|password| {
-                RegexBuilder::new(regex)
-                    .case_insensitive(true)
-                    .build()
-                    .ok()
-                    .map(|regex| regex.is_match(password))
-            })
-            .unwrap_or(false),
-        QueryTokenTerm::Negated(ref term) => {
-            !contains_ascii_case_insensitive(password, term.as_bytes())
-        }
-        QueryTokenTerm::Basic(ref term) => {
+            .zip(regex.as_ref())
+            .is_some_and(|(password, regex)| regex.is_match(password)),
+        QueryTokenTerm::Basic(term) => {
             contains_ascii_case_insensitive(password, term.as_bytes())
         }
     }
 }
 
-fn entry_matches(db: &RxDatabase, entry: &RxEntry, tokens: &[QueryToken]) -> bool {
+fn token_matches(entry: &RxEntry, group_name: Option<&str>, token: &QueryToken) -> bool {
+    let found = match token.operator.as_deref() {
+        Some(op) if is_password_operator(op) => entry
+            .password()
+            .and_then(|value| value.value_secure())
+            .as_deref()
+            .is_some_and(|password| password_term_matches(&token.term, password)),
+        Some(op) => operator_field(entry, op, group_name)
+            .iter()
+            .any(|field| term_matches(&token.term, field)),
+        None => entry_default_fields(entry, group_name)
+            .iter()
+            .any(|field| term_matches(&token.term, field)),
+    };
+
+    found != token.negated
+}
+
+fn entry_matches(entry: &RxEntry, group_name: Option<&str>, tokens: &[QueryToken]) -> bool {
     tokens
         .iter()
-        .all(|token| match token.operator.as_deref() {
-            Some(op) if is_password_operator(op) => entry
-                .password()
-                .and_then(|value| value.value_secure())
-                .as_deref()
-                .is_some_and(|password| password_term_matches(token, password)),
-            Some(op) => operator_field(entry, op, db)
-                .iter()
-                .any(|field| term_matches(token, field)),
-            None => entry_default_fields(entry, db)
-                .iter()
-                .any(|field| term_matches(token, field)),
-        })
+        .all(|token| token_matches(entry, group_name, token))
 }
 
-pub fn evaluate_saved_search(db: &RxDatabase, query: &str) -> Vec<Uuid> {
-    let tokens: Vec<_> = tokenize(query)
-        .into_iter()
-        .flat_map(|token| parse_token(&token))
-        .collect();
+/// A parsed query in the KeePassXC search language, e.g. `user:alice
+/// -url:test *title:^Git`.
+#[derive(Debug, Clone)]
+pub struct RxQuery(Vec<QueryToken>);
 
-    if tokens.is_empty() {
-        return vec![];
+impl RxQuery {
+    /// Parse a query. Returns None if there is nothing to search for.
+    pub fn parse(query: &str) -> Option<Self> {
+        let tokens: Vec<_> = tokenize(query)
+            .into_iter()
+            .flat_map(|token| parse_token(&token))
+            .collect();
+
+        match tokens.is_empty() {
+            true => None,
+            false => Some(Self(tokens)),
+        }
+    }
+
+    /// Whether the entry matches every term of the query. The name
+    /// of the entry's group is needed for the group: operator.
+    pub fn matches(&self, entry: &RxEntry, group_name: Option<&str>) -> bool {
+        entry_matches(entry, group_name, &self.0)
     }
+}
+
+pub fn evaluate_saved_search(db: &RxDatabase, query: &str) -> Vec<Uuid> {
+    let Some(query) = RxQuery::parse(query) else {
+        return vec![];
+    };
 
     db.all_entries_iter()
-        .filter_map(|entry| match entry_matches(db, entry.as_ref(), &tokens) {
-            true => Some(entry.uuid),
-            false => None,
+        .filter_map(|entry| {
+            let group = db.get_group(entry.parent_group);
+            let group_name = group.as_ref().map(|group| group.name.as_str());
+
+            match query.matches(entry.as_ref(), group_name) {
+                true => Some(entry.uuid),
+                false => None,
+            }
         })
         .collect()
 }
@@ -264,17 +293,12 @@ pub fn evaluate_saved_search(db: &RxDatabase, query: &str) -> Vec<Uuid> {
 #[cfg(test)]
 mod tests {
     use keepass::db::Node;
-    use keyring::set_default_credential_builder;
-    use zeroize::Zeroizing;
 
-    use crate::rx::ZeroableDatabase;
+    use crate::rx::test_support::{keepass_db, load_db};
 
     use super::*;
 
     fn test_db() -> RxDatabase {
-        set_default_credential_builder(keyring::mock::default_credential_builder());
-
-        let mut db = keepass::db::Database::new(Default::default());
         let mut root = keepass::db::Group::new("Root");
         let mut child = keepass::db::Group::new("Email");
 
@@ -310,12 +334,18 @@ mod tests {
             keepass::db::Value::Unprotected("BobSecret1".into()),
         );
 
+        let mut entry3 = keepass::db::Entry::new();
+        entry3.fields.insert(
+            "Title".into(),
+            keepass::db::Value::Unprotected("Door code".into()),
+        );
+
         child.add_child(Node::Entry(entry1));
         root.add_child(Node::Group(child));
         root.add_child(Node::Entry(entry2));
-        db.root = root;
+        root.add_child(Node::Entry(entry3));
 
-        RxDatabase::new(Zeroizing::new(ZeroableDatabase(db)))
+        load_db(keepass_db(root))
     }
 
     #[test]
@@ -332,6 +362,26 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn negated_term_excludes_entries_with_any_matching_field() {
+        let db = test_db();
+
+        // Gmail has other fields without "gmail", but is still left out.
+        let results = evaluate_saved_search(&db, "-gmail");
+        assert_eq!(results.len(), 2);
+
+        let results = evaluate_saved_search(&db, "-*title:^git");
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn negated_term_matches_entries_without_the_field() {
+        let db = test_db();
+        assert_eq!(evaluate_saved_search(&db, "-user:bob").len(), 2);
+        assert_eq!(evaluate_saved_search(&db, "-url:gmail").len(), 2);
+        assert_eq!(evaluate_saved_search(&db, "-pw:secret").len(), 2);
+    }
+
     #[test]
     fn password_field_query_matches_expected_entry() {
         let db = test_db();
@@ -339,6 +389,13 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn group_query_matches_expected_entry() {
+        let db = test_db();
+        let results = evaluate_saved_search(&db, "group:email");
+        assert_eq!(results.len(), 1);
+    }
+
     #[test]
     fn password_regex_query_matches_expected_entry() {
         let db = test_db();
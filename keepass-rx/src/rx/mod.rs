@@ -1,18 +1,57 @@
+mod entropy;
 pub(crate) mod icons;
+mod kpxc_search;
+mod otp_migration;
+mod rx_1pux;
+mod rx_attachment;
+mod rx_bitwarden;
 mod rx_container;
+mod rx_csv;
 mod rx_db;
 mod rx_entry;
+mod rx_generator;
 mod rx_group;
+mod rx_health;
+mod rx_import;
 mod rx_loader;
+mod rx_otp;
+mod rx_pwned;
+mod rx_saver;
+mod rx_times;
+mod rx_xml;
 mod search;
 pub(crate) mod virtual_hierarchy;
 mod zeroable_db;
 
+#[cfg(test)]
+mod test_support;
+
 #[cfg(feature = "gui")]
 mod rx_gui_traits;
+pub use entropy::PasswordQuality;
+pub use kpxc_search::{RxQuery, evaluate_saved_search};
+pub use otp_migration::{RxMigratedOtp, decode_otp_migration};
+pub use rx_1pux::read_1pux;
+pub use rx_attachment::*;
+pub use rx_bitwarden::read_bitwarden_json;
 pub use rx_container::*;
+pub use rx_csv::{
+    KEEPASSXC_CSV_COLUMNS, RxCsvColumn, RxCsvPreset, export_csv, read_csv, read_csv_header,
+};
 pub use rx_db::*;
 pub use rx_entry::*;
+pub use rx_generator::*;
 pub use rx_group::*;
+pub use rx_health::{RxPasswordHealth, RxPasswordReuse};
+pub use rx_import::{
+    RxImport, RxImportError, RxImportFormat, RxImportTemplate, RxImportedEntry,
+};
+pub use rx_otp::{
+    RxHotp, RxOtpError, RxQrFormat, RxTotpParams, current_time_step, qr_code, until_rollover,
+};
+pub use rx_pwned::RxPwnedChecker;
+pub use rx_saver::*;
+pub use rx_times::*;
+pub use rx_xml::export_xml;
 pub use search::RxSearchType;
 pub use zeroable_db::ZeroableDatabase;
@@ -0,0 +1,261 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use totp_rs::Secret;
+use zeroize::Zeroizing;
+
+use super::rx_otp::{encode_component, encode_label};
+
+const MIGRATION_URI_PREFIX: &str = "otpauth-migration://offline?";
+
+/// A one-time password taken out of a Google Authenticator export.
+pub struct RxMigratedOtp {
+    /// Normalized otpauth URI, ready for an otp field.
+    pub uri: Zeroizing<String>,
+    pub issuer: String,
+    pub account: String,
+}
+
+/// Just enough of the protobuf wire format to read the export
+/// payload. Fields are read in order, unknown ones are skipped.
+struct ProtoReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Skipped,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(anyhow!("Migration payload is truncated"))?;
+
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(anyhow!("Migration payload has a malformed number"))
+    }
+
+    fn next_field(&mut self) -> Result<Option<(u64, ProtoValue<'a>)>> {
+        if self.pos >= self.data.len() {
+            return Ok(None);
+        }
+
+        let key = self.varint()?;
+        let value = match key & 0x7 {
+            0 => ProtoValue::Varint(self.varint()?),
+            1 => self.take(8).map(|_| ProtoValue::Skipped)?,
+            2 => {
+                let len = usize::try_from(self.varint()?)?;
+                ProtoValue::Bytes(self.take(len)?)
+            }
+            5 => self.take(4).map(|_| ProtoValue::Skipped)?,
+            wire_type => {
+                return Err(anyhow!("Unsupported protobuf wire type {}", wire_type));
+            }
+        };
+
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn percent_decode(input: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes = input.as_bytes();
+    let mut decoded = Zeroizing::new(Vec::with_capacity(bytes.len()));
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'%' => {
+                let hex = bytes
+                    .get(pos + 1..pos + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or(anyhow!("Migration URI has a malformed escape"))?;
+
+                decoded.push(hex);
+                pos += 3;
+            }
+            // Some QR scanners turn the + of the base64 into a space.
+            b' ' => {
+                decoded.push(b'+');
+                pos += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                pos += 1;
+            }
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn migrated_otp(params: &[u8]) -> Result<RxMigratedOtp> {
+    let mut secret = Zeroizing::new(Vec::new());
+    let mut account = String::new();
+    let mut issuer = String::new();
+    let mut algorithm = 0;
+    let mut digits = 0;
+    let mut otp_type = 0;
+    let mut counter = 0;
+
+    let mut reader = ProtoReader::new(params);
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            (1, ProtoValue::Bytes(bytes)) => secret.extend_from_slice(bytes),
+            (2, ProtoValue::Bytes(bytes)) => account = String::from_utf8(bytes.to_vec())?,
+            (3, ProtoValue::Bytes(bytes)) => issuer = String::from_utf8(bytes.to_vec())?,
+            (4, ProtoValue::Varint(value)) => algorithm = value,
+            (5, ProtoValue::Varint(value)) => digits = value,
+            (6, ProtoValue::Varint(value)) => otp_type = value,
+            (7, ProtoValue::Varint(value)) => counter = value,
+            _ => (),
+        }
+    }
+
+    if secret.is_empty() {
+        return Err(anyhow!("One-time password {} has no secret", account));
+    }
+
+    let algorithm = match algorithm {
+        0 | 1 => "SHA1",
+        2 => "SHA256",
+        3 => "SHA512",
+        _ => return Err(anyhow!("One-time password {} uses MD5", account)),
+    };
+
+    let digits = match digits {
+        2 => 8,
+        _ => 6,
+    };
+
+    // The account name often has the issuer in front already.
+    let label = match issuer.is_empty() || account.starts_with(&format!("{}:", issuer)) {
+        true => account.clone(),
+        false => format!("{}:{}", issuer, account),
+    };
+
+    let secret = Zeroizing::new(Secret::Raw(secret.to_vec()).to_encoded().to_string());
+    let kind = match otp_type {
+        1 => format!("hotp/{}?counter={}&", encode_label(&label), counter),
+        _ => format!("totp/{}?period=30&", encode_label(&label)),
+    };
+
+    let uri = Zeroizing::new(format!(
+        "otpauth://{}secret={}&issuer={}&algorithm={}&digits={}",
+        kind,
+        *secret,
+        encode_component(&issuer),
+        algorithm,
+        digits
+    ));
+
+    Ok(RxMigratedOtp {
+        uri,
+        issuer,
+        account,
+    })
+}
+
+/// Decode an otpauth-migration URI, as exported by Google
+/// Authenticator. The data parameter is a base64 protobuf that holds
+/// any number of one-time passwords.
+pub fn decode_otp_migration(migration_uri: &str) -> Result<Vec<RxMigratedOtp>> {
+    let query = migration_uri
+        .trim()
+        .strip_prefix(MIGRATION_URI_PREFIX)
+        .ok_or(anyhow!("Not a Google Authenticator export"))?;
+
+    let data = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("data="))
+        .ok_or(anyhow!("Google Authenticator export has no data"))?;
+
+    let data = percent_decode(data)?;
+    let payload = Zeroizing::new(BASE64_STANDARD.decode(&data[..])?);
+
+    let mut otps = vec![];
+    let mut reader = ProtoReader::new(&payload);
+    while let Some((field, value)) = reader.next_field()? {
+        if let (1, ProtoValue::Bytes(params)) = (field, value) {
+            otps.push(migrated_otp(params)?);
+        }
+    }
+
+    Ok(otps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proto_field(field: u8, bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![(field << 3) | 2, bytes.len() as u8];
+        encoded.extend_from_slice(bytes);
+        encoded
+    }
+
+    #[test]
+    fn decodes_migration_payload() {
+        let mut totp = proto_field(1, b"Hello!\xde\xad\xbe\xef");
+        totp.extend(proto_field(2, b"alice@example.com"));
+        totp.extend(proto_field(3, b"Example"));
+        totp.extend([4 << 3, 1, 5 << 3, 2, 6 << 3, 2]);
+
+        let mut hotp = proto_field(1, b"12345678901234567890");
+        hotp.extend(proto_field(2, b"VPN:bob"));
+        hotp.extend(proto_field(3, b"VPN"));
+        hotp.extend([6 << 3, 1, 7 << 3, 5]);
+
+        let mut payload = proto_field(1, &totp);
+        payload.extend(proto_field(1, &hotp));
+        payload.extend([2 << 3, 1]);
+
+        let data = BASE64_STANDARD
+            .encode(&payload)
+            .replace('+', "%2B")
+            .replace('/', "%2F")
+            .replace('=', "%3D");
+
+        let otps = decode_otp_migration(&format!("{}data={}", MIGRATION_URI_PREFIX, data))
+            .expect("Could not decode migration");
+
+        assert_eq!(otps.len(), 2);
+        assert_eq!(otps[0].issuer, "Example");
+        assert_eq!(otps[0].account, "alice@example.com");
+        assert_eq!(
+            otps[0].uri.as_str(),
+            "otpauth://totp/Example:alice%40example.com?period=30&secret=JBSWY3DPEHPK3PXP&issuer=Example&algorithm=SHA1&digits=8"
+        );
+
+        assert_eq!(
+            otps[1].uri.as_str(),
+            "otpauth://hotp/VPN:bob?counter=5&secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=VPN&algorithm=SHA1&digits=6"
+        );
+
+        assert!(decode_otp_migration("otpauth://totp/Example?secret=ABC").is_err());
+    }
+}
@@ -0,0 +1,603 @@
+use anyhow::{Result, anyhow};
+use chrono::DateTime;
+use serde::Deserialize;
+use std::io::{Read, Seek};
+use zeroize::Zeroizing;
+use zip::ZipArchive;
+
+use super::rx_import::{
+    NOTES_FIELD, OTP_FIELD, PASSWORD_FIELD, RxImport, RxImportedEntry, Secret, TITLE_FIELD,
+    USERNAME_FIELD, text,
+};
+use super::rx_otp::imported_otp_uri;
+
+/// The JSON file inside the archive that describes every item.
+const EXPORT_DATA: &str = "export.data";
+
+/// Largest export.data that is read, as the size in the archive
+/// header cannot be trusted.
+const MAX_EXPORT_DATA_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Largest attached file that is read.
+const MAX_ATTACHMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Login fields of this type hold passwords.
+const PASSWORD_LOGIN_FIELD: &str = "P";
+
+/// Items in the archive of a vault, rather than deleted or active.
+const ARCHIVED_STATE: &str = "archived";
+
+#[derive(Deserialize)]
+struct OnePuxExport {
+    #[serde(default)]
+    accounts: Vec<OnePuxAccount>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxAccount {
+    #[serde(default)]
+    vaults: Vec<OnePuxVault>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxVault {
+    attrs: OnePuxVaultAttrs,
+    #[serde(default)]
+    items: Vec<OnePuxItem>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxVaultAttrs {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxItem {
+    state: Option<String>,
+    overview: OnePuxOverview,
+    details: OnePuxDetails,
+}
+
+#[derive(Deserialize)]
+struct OnePuxOverview {
+    title: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    urls: Vec<OnePuxUrl>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxUrl {
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxDetails {
+    #[serde(default)]
+    login_fields: Vec<OnePuxLoginField>,
+    notes_plain: Secret,
+    #[serde(default)]
+    sections: Vec<OnePuxSection>,
+    document_attributes: Option<OnePuxFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxLoginField {
+    value: Secret,
+    name: Option<String>,
+    field_type: Option<String>,
+    designation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxSection {
+    #[serde(default)]
+    fields: Vec<OnePuxField>,
+}
+
+#[derive(Deserialize)]
+struct OnePuxField {
+    title: Option<String>,
+    id: Option<String>,
+    value: OnePuxValue,
+}
+
+/// A field value is an object with a single key that says what kind
+/// of value it is. Kinds that are not known here are left out.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxValue {
+    concealed: Secret,
+    totp: Secret,
+    string: Secret,
+    url: Secret,
+    phone: Secret,
+    menu: Secret,
+    gender: Secret,
+    credit_card_type: Secret,
+    credit_card_number: Secret,
+    email: Option<OnePuxEmail>,
+    date: Option<i64>,
+    month_year: Option<u32>,
+    address: Option<OnePuxAddress>,
+    file: Option<OnePuxFile>,
+    ssh_key: Option<OnePuxSshKey>,
+}
+
+/// Older exports have the email address as plain text.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OnePuxEmail {
+    Text(Zeroizing<String>),
+    Address { email_address: Secret },
+}
+
+#[derive(Deserialize)]
+struct OnePuxAddress {
+    street: Secret,
+    city: Secret,
+    state: Secret,
+    zip: Secret,
+    country: Secret,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxFile {
+    file_name: String,
+    document_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnePuxSshKey {
+    private_key: Secret,
+}
+
+/// Read a file from the archive, stopping at the limit instead of
+/// trusting the size in the archive.
+fn read_capped(file: impl Read, name: &str, limit: u64) -> Result<Zeroizing<Vec<u8>>> {
+    let mut data = Zeroizing::new(vec![]);
+    file.take(limit + 1).read_to_end(&mut data)?;
+
+    match data.len() as u64 > limit {
+        true => Err(anyhow!("The file {} is too large to import", name)),
+        false => Ok(data),
+    }
+}
+
+/// Attached files are stored as files/<document id>__<file name>.
+fn read_attachment<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    file: &OnePuxFile,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let prefix = format!("files/{}__", file.document_id);
+    let path = archive
+        .file_names()
+        .find(|name| name.starts_with(&prefix))
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("The file {} is missing from the archive", file.file_name))?;
+
+    let zip_file = archive.by_name(&path)?;
+    read_capped(zip_file, &file.file_name, MAX_ATTACHMENT_SIZE)
+}
+
+/// The text of a field value, and whether it is protected.
+fn field_text(value: &OnePuxValue) -> Option<(Zeroizing<String>, bool)> {
+    if let Some(concealed) = value.concealed.clone() {
+        return Some((concealed, true));
+    }
+
+    if let Some(number) = value.credit_card_number.clone() {
+        return Some((number, true));
+    }
+
+    if let Some(private_key) = value
+        .ssh_key
+        .as_ref()
+        .and_then(|key| key.private_key.clone())
+    {
+        return Some((private_key, true));
+    }
+
+    let plain = [
+        &value.string,
+        &value.url,
+        &value.phone,
+        &value.menu,
+        &value.gender,
+        &value.credit_card_type,
+    ];
+
+    if let Some(text) = plain.into_iter().flatten().next().cloned() {
+        return Some((text, false));
+    }
+
+    if let Some(email) = &value.email {
+        let email = match email {
+            OnePuxEmail::Text(email) => email.clone(),
+            OnePuxEmail::Address { email_address } => {
+                Zeroizing::new(text(email_address).into())
+            }
+        };
+        return Some((email, false));
+    }
+
+    if let Some(address) = &value.address {
+        let parts = [
+            &address.street,
+            &address.city,
+            &address.state,
+            &address.zip,
+            &address.country,
+        ];
+
+        let address = parts
+            .into_iter()
+            .map(text)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Some((Zeroizing::new(address), false));
+    }
+
+    if let Some(date) = value
+        .date
+        .and_then(|date| DateTime::from_timestamp(date, 0))
+    {
+        let date = date.format("%Y-%m-%d").to_string();
+        return Some((Zeroizing::new(date), false));
+    }
+
+    // Written as YYYYMM, like 203003.
+    if let Some(month_year) = value.month_year {
+        let date = format!("{:02}/{}", month_year % 100, month_year / 100);
+        return Some((Zeroizing::new(date), false));
+    }
+
+    None
+}
+
+fn import_item<R: Read + Seek>(
+    item: &OnePuxItem,
+    archive: &mut ZipArchive<R>,
+    warnings: &mut Vec<String>,
+) -> Result<RxImportedEntry> {
+    let mut entry = RxImportedEntry::default();
+    let details = &item.details;
+
+    for field in &details.login_fields {
+        match field.designation.as_deref() {
+            Some("username") => entry.set_field(USERNAME_FIELD, text(&field.value), false),
+            Some("password") => entry.set_field(PASSWORD_FIELD, text(&field.value), true),
+            _ => {
+                let name = field.name.as_deref().unwrap_or_default();
+                let protected = field.field_type.as_deref() == Some(PASSWORD_LOGIN_FIELD);
+                entry.add_custom_field(name, text(&field.value), protected);
+            }
+        }
+    }
+
+    let mut files: Vec<&OnePuxFile> = details.document_attributes.iter().collect();
+
+    for field in details.sections.iter().flat_map(|section| &section.fields) {
+        let name = field
+            .title
+            .as_deref()
+            .filter(|title| !title.trim().is_empty())
+            .or(field.id.as_deref())
+            .unwrap_or_default();
+
+        // The first one-time password goes where KeePass clients look
+        // for it. Any others, and ones that cannot be read, are kept
+        // as protected custom fields.
+        if let Some(totp) = field
+            .value
+            .totp
+            .as_ref()
+            .filter(|totp| !totp.trim().is_empty())
+        {
+            match imported_otp_uri(totp) {
+                Some(otp) if entry.field(OTP_FIELD).is_none() => {
+                    entry.set_field(OTP_FIELD, &otp, true)
+                }
+                Some(otp) => entry.add_custom_field(name, &otp, true),
+                None => {
+                    entry.add_custom_field(name, totp, true);
+                    warnings.push(format!(
+                        "The TOTP value of {} is not a valid secret or URI",
+                        name
+                    ));
+                }
+            }
+        } else if let Some(file) = &field.value.file {
+            files.push(file);
+        } else if let Some((value, protected)) = field_text(&field.value) {
+            entry.add_custom_field(name, &value, protected);
+        }
+    }
+
+    for file in files {
+        match read_attachment(archive, file) {
+            Ok(data) => entry.add_attachment(&file.file_name, data),
+            Err(err) => warnings.push(err.to_string()),
+        }
+    }
+
+    if entry.is_empty() && text(&details.notes_plain).is_empty() {
+        return Err(anyhow!("There is nothing to import"));
+    }
+
+    let overview = &item.overview;
+    entry.set_field(
+        TITLE_FIELD,
+        overview.title.as_deref().unwrap_or_default(),
+        false,
+    );
+    entry.set_field(NOTES_FIELD, text(&details.notes_plain), false);
+
+    // The main URL is usually also the first of the list.
+    let mut urls: Vec<&str> = overview.url.as_deref().into_iter().collect();
+    for url in overview.urls.iter().filter_map(|url| url.url.as_deref()) {
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+
+    entry.add_urls(urls);
+    entry.tags = overview.tags.clone();
+    entry.ensure_title();
+
+    Ok(entry)
+}
+
+/// Read the entries of a 1Password 1PUX archive. Vaults become groups,
+/// and attached files become attachments. Items that cannot be
+/// imported are reported by their position in the export, counting
+/// from 1, as are archived items, which are left out. Files and
+/// one-time passwords that cannot be read are reported as warnings
+/// for the item they belong to.
+pub fn read_1pux<R: Read + Seek>(reader: R) -> Result<RxImport> {
+    let mut archive =
+        ZipArchive::new(reader).map_err(|err| anyhow!("Not a 1PUX archive: {}", err))?;
+
+    let export: OnePuxExport = {
+        let data_file = archive
+            .by_name(EXPORT_DATA)
+            .map_err(|_| anyhow!("Not a 1PUX archive: {} is missing", EXPORT_DATA))?;
+
+        let json = read_capped(data_file, EXPORT_DATA, MAX_EXPORT_DATA_SIZE)?;
+        serde_json::from_slice(&json).map_err(|err| anyhow!("Not a 1PUX archive: {}", err))?
+    };
+
+    let mut import = RxImport::default();
+    let vaults = export.accounts.iter().flat_map(|account| &account.vaults);
+    let items = vaults.flat_map(|vault| vault.items.iter().map(move |item| (vault, item)));
+
+    for (index, (vault, item)) in items.enumerate() {
+        if item.state.as_deref() == Some(ARCHIVED_STATE) {
+            import.skip(index + 1, "The item is archived");
+            continue;
+        }
+
+        let mut warnings = vec![];
+
+        match import_item(item, &mut archive, &mut warnings) {
+            Ok(mut entry) => {
+                let vault_name = vault.attrs.name.as_deref().unwrap_or_default().trim();
+                if !vault_name.is_empty() {
+                    entry.group_path = vec![vault_name.to_string()];
+                }
+
+                import.entries.push(entry);
+
+                // The entry is imported without these parts.
+                for reason in warnings {
+                    import.warn(index + 1, reason);
+                }
+            }
+            Err(err) => import.skip(index + 1, err.to_string()),
+        }
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rx::rx_import::URL_FIELD;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    const EXPORT: &str = r#"{
+        "accounts": [{
+            "attrs": { "accountName": "Alice", "email": "alice@example.com" },
+            "vaults": [
+                {
+                    "attrs": { "uuid": "v1", "name": "Private" },
+                    "items": [
+                        {
+                            "uuid": "i1",
+                            "categoryUuid": "001",
+                            "state": "active",
+                            "overview": {
+                                "title": "Mail",
+                                "url": "https://mail.example.com",
+                                "urls": [
+                                    { "label": "", "url": "https://mail.example.com" },
+                                    { "label": "", "url": "https://webmail.example.com" }
+                                ],
+                                "tags": ["work"]
+                            },
+                            "details": {
+                                "loginFields": [
+                                    { "value": "alice", "name": "email", "fieldType": "E", "designation": "username" },
+                                    { "value": "hunter2", "name": "password", "fieldType": "P", "designation": "password" },
+                                    { "value": "1234", "name": "pin", "fieldType": "P" }
+                                ],
+                                "notesPlain": "",
+                                "sections": [{
+                                    "title": "Security",
+                                    "fields": [
+                                        { "title": "one-time password", "id": "TOTP_1", "value": { "totp": "JBSWY3DPEHPK3PXP" } },
+                                        { "title": "Recovery", "id": "r", "value": { "concealed": "abcd" } },
+                                        { "title": "Backup email", "id": "e", "value": { "email": { "email_address": "bob@example.com", "provider": null } } },
+                                        { "title": "Since", "id": "d", "value": { "date": 1700000000 } },
+                                        { "title": "Scan", "id": "f", "value": { "file": { "fileName": "scan.txt", "documentId": "doc1", "decryptedSize": 5 } } },
+                                        { "title": "Lost", "id": "g", "value": { "file": { "fileName": "lost.txt", "documentId": "doc2", "decryptedSize": 5 } } },
+                                        { "title": "Linked", "id": "l", "value": { "reference": "i2" } }
+                                    ]
+                                }]
+                            }
+                        },
+                        {
+                            "uuid": "i2",
+                            "categoryUuid": "003",
+                            "overview": { "title": "Empty" },
+                            "details": { "loginFields": [], "sections": [] }
+                        }
+                    ]
+                },
+                {
+                    "attrs": { "uuid": "v2", "name": "Shared" },
+                    "items": [
+                        {
+                            "uuid": "i3",
+                            "categoryUuid": "002",
+                            "overview": { "title": "Visa" },
+                            "details": {
+                                "sections": [{
+                                    "title": "",
+                                    "fields": [
+                                        { "title": "number", "id": "ccnum", "value": { "creditCardNumber": "4111111111111111" } },
+                                        { "title": "expiry date", "id": "expiry", "value": { "monthYear": 203003 } }
+                                    ]
+                                }]
+                            }
+                        },
+                        {
+                            "uuid": "i4",
+                            "overview": { "title": "Bad" },
+                            "details": {
+                                "sections": [{
+                                    "fields": [{ "title": "otp", "id": "otp", "value": { "totp": "not a secret!" } }]
+                                }]
+                            }
+                        },
+                        {
+                            "uuid": "i5",
+                            "state": "archived",
+                            "overview": { "title": "Old" },
+                            "details": {
+                                "loginFields": [
+                                    { "value": "hunter1", "name": "password", "fieldType": "P", "designation": "password" }
+                                ]
+                            }
+                        }
+                    ]
+                }
+            ]
+        }]
+    }"#;
+
+    fn archive() -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        let options =
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        writer.start_file(EXPORT_DATA, options).unwrap();
+        writer.write_all(EXPORT.as_bytes()).unwrap();
+        writer.start_file("files/doc1__scan.txt", options).unwrap();
+        writer.write_all(b"hello").unwrap();
+
+        let mut archive = writer.finish().unwrap();
+        archive.set_position(0);
+        archive
+    }
+
+    #[test]
+    fn reads_1pux_archive() {
+        let import = read_1pux(archive()).expect("Could not read archive");
+        assert_eq!(import.entries.len(), 3);
+
+        let positions: Vec<_> = import.errors.iter().map(|err| err.position).collect();
+        assert_eq!(positions, vec![2, 5]);
+
+        // The missing file and the bad one-time password are reported,
+        // but their items are imported.
+        let positions: Vec<_> = import.warnings.iter().map(|err| err.position).collect();
+        assert_eq!(positions, vec![1, 4]);
+
+        let mail = &import.entries[0];
+        assert_eq!(mail.group_path, vec!["Private"]);
+        assert_eq!(mail.tags, vec!["work"]);
+        assert_eq!(mail.field(USERNAME_FIELD), Some("alice"));
+        assert_eq!(mail.field(URL_FIELD), Some("https://mail.example.com"));
+        assert_eq!(
+            mail.field("KP2A_URL_1"),
+            Some("https://webmail.example.com")
+        );
+        assert_eq!(mail.field("KP2A_URL_2"), None);
+        assert!(mail.field(OTP_FIELD).is_some());
+        assert_eq!(mail.field("Backup email"), Some("bob@example.com"));
+        assert_eq!(mail.field("Since"), Some("2023-11-14"));
+        assert_eq!(mail.field("Linked"), None);
+
+        let kp_mail = mail.to_keepass();
+        assert!(matches!(
+            kp_mail.fields.get("pin"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+        assert!(matches!(
+            kp_mail.fields.get("Recovery"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+        assert!(matches!(
+            kp_mail.fields.get("scan.txt"),
+            Some(keepass::db::Value::Bytes(data)) if data == b"hello"
+        ));
+
+        let visa = &import.entries[1];
+        assert_eq!(visa.group_path, vec!["Shared"]);
+        assert_eq!(visa.field("expiry date"), Some("03/2030"));
+        assert!(matches!(
+            visa.to_keepass().fields.get("number"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+
+        let bad = &import.entries[2];
+        assert_eq!(bad.field(OTP_FIELD), None);
+        assert!(matches!(
+            bad.to_keepass().fields.get("otp"),
+            Some(keepass::db::Value::Protected(value)) if value.unsecure() == b"not a secret!"
+        ));
+    }
+
+    #[test]
+    fn caps_file_reads() {
+        assert_eq!(
+            read_capped(&b"hello"[..], "a", 5).unwrap().as_slice(),
+            b"hello"
+        );
+        assert!(read_capped(&b"hello"[..], "a", 4).is_err());
+    }
+
+    #[test]
+    fn rejects_other_archives() {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        writer
+            .start_file("readme.txt", SimpleFileOptions::default())
+            .unwrap();
+
+        let mut archive = writer.finish().unwrap();
+        archive.set_position(0);
+        assert!(read_1pux(archive).is_err());
+        assert!(read_1pux(Cursor::new(b"not a zip".to_vec())).is_err());
+    }
+}
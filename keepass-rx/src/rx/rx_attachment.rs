@@ -0,0 +1,152 @@
+use crate::crypto::{EncryptedValue, MasterKey};
+
+use super::rx_entry::next_value_id;
+use anyhow::Result;
+use infer;
+use libsodium_rs::utils::{SecureVec, vec_utils};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+const FALLBACK_BINARY_MIME_TYPE: &str = "application/octet-stream";
+const FALLBACK_TEXT_MIME_TYPE: &str = "text/plain";
+
+/// How the UI can display an attachment, if at all.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RxAttachmentViewType {
+    Text,
+    Image,
+}
+
+impl RxAttachmentViewType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RxAttachmentViewType::Text => "text",
+            RxAttachmentViewType::Image => "image",
+        }
+    }
+}
+
+/// A binary field attached to an entry. The contents are encrypted
+/// in memory, and only decrypted when the user wants to view or
+/// export the attachment.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct RxAttachment {
+    pub name: String,
+
+    #[zeroize(skip)]
+    pub size: usize,
+
+    #[zeroize(skip)]
+    pub mime_type: String,
+
+    #[zeroize(skip)]
+    is_text: bool,
+
+    data: EncryptedValue,
+}
+
+impl RxAttachment {
+    pub fn new(master_key: &MasterKey, name: String, mut bytes: Vec<u8>) -> Result<Self> {
+        // Sniff the content type while we still have the plaintext.
+        // Anything infer does not know about is either text (if it
+        // is valid UTF-8) or an opaque binary blob.
+        let is_text = std::str::from_utf8(&bytes).is_ok();
+        let mime_type = infer::get(&bytes)
+            .map(|kind| kind.mime_type().to_string())
+            .unwrap_or_else(|| match is_text {
+                true => FALLBACK_TEXT_MIME_TYPE.to_string(),
+                false => FALLBACK_BINARY_MIME_TYPE.to_string(),
+            });
+
+        let size = bytes.len();
+        let mut secure_vec = vec_utils::secure_vec::<u8>(size)?;
+        secure_vec.copy_from_slice(&bytes);
+        bytes.zeroize();
+
+        let data = EncryptedValue::new(master_key, next_value_id(), secure_vec)?;
+
+        Ok(Self {
+            name,
+            size,
+            mime_type,
+            is_text,
+            data,
+        })
+    }
+
+    pub fn view_type(&self) -> Option<RxAttachmentViewType> {
+        if self.mime_type.starts_with("image/") {
+            Some(RxAttachmentViewType::Image)
+        } else if self.is_text {
+            Some(RxAttachmentViewType::Text)
+        } else {
+            None
+        }
+    }
+
+    /// Decrypt the attachment contents.
+    pub fn data(&self, master_key: &MasterKey) -> Result<SecureVec<u8>> {
+        self.data.expose(master_key)
+    }
+}
+
+impl std::fmt::Debug for RxAttachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RxAttachment")
+            .field("name", &self.name)
+            .field("size", &self.size)
+            .field("mime_type", &self.mime_type)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyring::set_default_credential_builder;
+
+    #[test]
+    fn detects_text_attachment() {
+        set_default_credential_builder(keyring::mock::default_credential_builder());
+        let master_key = MasterKey::new().expect("could not create master key");
+
+        let attachment =
+            RxAttachment::new(&master_key, "notes.txt".to_string(), b"hello".to_vec())
+                .expect("could not create attachment");
+
+        assert_eq!(attachment.size, 5);
+        assert_eq!(attachment.mime_type, "text/plain");
+        assert_eq!(attachment.view_type(), Some(RxAttachmentViewType::Text));
+
+        let data = attachment.data(&master_key).expect("could not decrypt");
+        assert_eq!(&*data, b"hello");
+    }
+
+    #[test]
+    fn detects_image_attachment() {
+        set_default_credential_builder(keyring::mock::default_credential_builder());
+        let master_key = MasterKey::new().expect("could not create master key");
+
+        let png_header = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00];
+        let attachment = RxAttachment::new(&master_key, "image.png".to_string(), png_header)
+            .expect("could not create attachment");
+
+        assert_eq!(attachment.mime_type, "image/png");
+        assert_eq!(attachment.view_type(), Some(RxAttachmentViewType::Image));
+    }
+
+    #[test]
+    fn unknown_binary_is_not_viewable() {
+        set_default_credential_builder(keyring::mock::default_credential_builder());
+        let master_key = MasterKey::new().expect("could not create master key");
+
+        let attachment = RxAttachment::new(
+            &master_key,
+            "blob".to_string(),
+            vec![0xFF, 0xFE, 0x00, 0x81],
+        )
+        .expect("could not create attachment");
+
+        assert_eq!(attachment.mime_type, "application/octet-stream");
+        assert_eq!(attachment.view_type(), None);
+    }
+}
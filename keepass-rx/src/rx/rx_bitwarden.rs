@@ -0,0 +1,409 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::rx_import::{
+    CARD_TEMPLATE, IDENTITY_TEMPLATE, NOTES_FIELD, OTP_FIELD, PASSWORD_FIELD, RxImport,
+    RxImportedEntry, Secret, TITLE_FIELD, USERNAME_FIELD, text,
+};
+use super::rx_otp::imported_otp_uri;
+
+const LOGIN_ITEM: u8 = 1;
+const SECURE_NOTE_ITEM: u8 = 2;
+const CARD_ITEM: u8 = 3;
+const IDENTITY_ITEM: u8 = 4;
+const SSH_KEY_ITEM: u8 = 5;
+
+const HIDDEN_FIELD: u8 = 1;
+const LINKED_FIELD: u8 = 3;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenExport {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    folders: Vec<BitwardenFolder>,
+    /// Organization exports have collections instead of folders.
+    #[serde(default)]
+    collections: Vec<BitwardenFolder>,
+    #[serde(default)]
+    items: Vec<BitwardenItem>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenItem {
+    #[serde(rename = "type")]
+    kind: u8,
+    name: Option<String>,
+    notes: Secret,
+    folder_id: Option<String>,
+    collection_ids: Option<Vec<String>>,
+    fields: Option<Vec<BitwardenField>>,
+    login: Option<BitwardenLogin>,
+    card: Option<BitwardenCard>,
+    identity: Option<BitwardenIdentity>,
+    ssh_key: Option<BitwardenSshKey>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenField {
+    name: Option<String>,
+    value: Secret,
+    #[serde(rename = "type")]
+    kind: u8,
+}
+
+#[derive(Deserialize)]
+struct BitwardenLogin {
+    uris: Option<Vec<BitwardenUri>>,
+    username: Secret,
+    password: Secret,
+    totp: Secret,
+}
+
+#[derive(Deserialize)]
+struct BitwardenUri {
+    uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenCard {
+    cardholder_name: Secret,
+    brand: Secret,
+    number: Secret,
+    exp_month: Secret,
+    exp_year: Secret,
+    code: Secret,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenIdentity {
+    title: Secret,
+    first_name: Secret,
+    middle_name: Secret,
+    last_name: Secret,
+    address1: Secret,
+    address2: Secret,
+    address3: Secret,
+    city: Secret,
+    state: Secret,
+    postal_code: Secret,
+    country: Secret,
+    company: Secret,
+    email: Secret,
+    phone: Secret,
+    ssn: Secret,
+    username: Secret,
+    passport_number: Secret,
+    license_number: Secret,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenSshKey {
+    private_key: Secret,
+    public_key: Secret,
+    key_fingerprint: Secret,
+}
+
+fn import_login(entry: &mut RxImportedEntry, login: &BitwardenLogin) -> Result<()> {
+    entry.set_field(USERNAME_FIELD, text(&login.username), false);
+    entry.set_field(PASSWORD_FIELD, text(&login.password), true);
+
+    let uris = login.uris.iter().flatten();
+    entry.add_urls(uris.filter_map(|uri| uri.uri.as_deref()));
+
+    if let Some(totp) = login.totp.as_ref().filter(|totp| !totp.trim().is_empty()) {
+        let otp = imported_otp_uri(totp)
+            .ok_or(anyhow!("The TOTP value is not a valid secret or URI"))?;
+        entry.set_field(OTP_FIELD, &otp, true);
+    }
+
+    Ok(())
+}
+
+fn import_card(entry: &mut RxImportedEntry, card: &BitwardenCard) {
+    entry.template = Some(&CARD_TEMPLATE);
+
+    let expiration = match (text(&card.exp_month), text(&card.exp_year)) {
+        ("", year) => year.to_string(),
+        (month, "") => month.to_string(),
+        (month, year) => format!("{:0>2}/{}", month, year),
+    };
+
+    entry.set_field("Cardholder", text(&card.cardholder_name), false);
+    entry.set_field("Brand", text(&card.brand), false);
+    entry.set_field("Number", text(&card.number), true);
+    entry.set_field("Expiration", &expiration, false);
+    entry.set_field("CVV", text(&card.code), true);
+}
+
+fn import_identity(entry: &mut RxImportedEntry, identity: &BitwardenIdentity) {
+    entry.template = Some(&IDENTITY_TEMPLATE);
+    entry.set_field(USERNAME_FIELD, text(&identity.username), false);
+
+    let fields = [
+        ("Honorific", &identity.title),
+        ("First Name", &identity.first_name),
+        ("Middle Name", &identity.middle_name),
+        ("Last Name", &identity.last_name),
+        ("Company", &identity.company),
+        ("Email", &identity.email),
+        ("Phone", &identity.phone),
+        ("Address 1", &identity.address1),
+        ("Address 2", &identity.address2),
+        ("Address 3", &identity.address3),
+        ("City", &identity.city),
+        ("State", &identity.state),
+        ("Postal Code", &identity.postal_code),
+        ("Country", &identity.country),
+    ];
+
+    for (name, value) in fields {
+        entry.set_field(name, text(value), false);
+    }
+
+    entry.set_field("Social Security Number", text(&identity.ssn), true);
+    entry.set_field("Passport Number", text(&identity.passport_number), true);
+    entry.set_field("License Number", text(&identity.license_number), true);
+}
+
+fn import_ssh_key(entry: &mut RxImportedEntry, ssh_key: &BitwardenSshKey) {
+    entry.add_custom_field("Private Key", text(&ssh_key.private_key), true);
+    entry.add_custom_field("Public Key", text(&ssh_key.public_key), false);
+    entry.add_custom_field("Fingerprint", text(&ssh_key.key_fingerprint), false);
+}
+
+fn import_item(
+    item: &BitwardenItem,
+    group_names: &HashMap<&str, &str>,
+) -> Result<RxImportedEntry> {
+    let mut entry = RxImportedEntry::default();
+
+    match item.kind {
+        LOGIN_ITEM => {
+            if let Some(login) = &item.login {
+                import_login(&mut entry, login)?;
+            }
+        }
+        SECURE_NOTE_ITEM => (),
+        CARD_ITEM => {
+            if let Some(card) = &item.card {
+                import_card(&mut entry, card);
+            }
+        }
+        IDENTITY_ITEM => {
+            if let Some(identity) = &item.identity {
+                import_identity(&mut entry, identity);
+            }
+        }
+        SSH_KEY_ITEM => {
+            if let Some(ssh_key) = &item.ssh_key {
+                import_ssh_key(&mut entry, ssh_key);
+            }
+        }
+        kind => return Err(anyhow!("Items of type {} are not supported", kind)),
+    }
+
+    // Hidden custom fields stay hidden. Linked fields only point at
+    // another field of the item, so there is nothing to import.
+    for field in item.fields.iter().flatten() {
+        if field.kind != LINKED_FIELD {
+            let name = field.name.as_deref().unwrap_or_default();
+            entry.add_custom_field(name, text(&field.value), field.kind == HIDDEN_FIELD);
+        }
+    }
+
+    // Cards and identities without any details are still worth
+    // keeping for their name, unlike empty logins.
+    if entry.is_empty() && text(&item.notes).is_empty() && entry.template.is_none() {
+        return Err(anyhow!("There is nothing to import"));
+    }
+
+    entry.set_field(TITLE_FIELD, item.name.as_deref().unwrap_or_default(), false);
+    entry.set_field(NOTES_FIELD, text(&item.notes), false);
+    entry.ensure_title();
+
+    // Bitwarden separates nested folders with slashes.
+    let group = item
+        .folder_id
+        .as_deref()
+        .or(item
+            .collection_ids
+            .iter()
+            .flatten()
+            .next()
+            .map(String::as_str))
+        .and_then(|id| group_names.get(id));
+
+    if let Some(group) = group {
+        entry.group_path = group
+            .split('/')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+
+    Ok(entry)
+}
+
+/// Read the entries of an unencrypted Bitwarden JSON export. Folders
+/// (or collections) become groups. Items that cannot be imported are
+/// reported by their position in the export, counting from 1.
+pub fn read_bitwarden_json(json: &[u8]) -> Result<RxImport> {
+    let export: BitwardenExport = serde_json::from_slice(json)
+        .map_err(|err| anyhow!("Not a Bitwarden JSON export: {}", err))?;
+
+    if export.encrypted {
+        return Err(anyhow!(
+            "Encrypted Bitwarden exports cannot be imported. Export again without encryption."
+        ));
+    }
+
+    let group_names: HashMap<&str, &str> = export
+        .folders
+        .iter()
+        .chain(export.collections.iter())
+        .map(|folder| (folder.id.as_str(), folder.name.as_str()))
+        .collect();
+
+    let mut import = RxImport::default();
+    for (index, item) in export.items.iter().enumerate() {
+        match import_item(item, &group_names) {
+            Ok(entry) => import.entries.push(entry),
+            Err(err) => import.skip(index + 1, err.to_string()),
+        }
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rx::rx_import::URL_FIELD;
+
+    const EXPORT: &str = r#"{
+        "encrypted": false,
+        "folders": [{ "id": "f1", "name": "Work/Mail" }],
+        "items": [
+            {
+                "type": 1,
+                "name": "Mail",
+                "folderId": "f1",
+                "notes": null,
+                "fields": [
+                    { "name": "PIN", "value": "1234", "type": 1 },
+                    { "name": "Region", "value": "eu", "type": 0 },
+                    { "name": "Link", "value": null, "type": 3, "linkedId": 100 }
+                ],
+                "login": {
+                    "uris": [
+                        { "match": null, "uri": "https://mail.example.com" },
+                        { "match": null, "uri": "https://webmail.example.com" }
+                    ],
+                    "username": "alice",
+                    "password": "hunter2",
+                    "totp": "JBSWY3DPEHPK3PXP"
+                }
+            },
+            {
+                "type": 2,
+                "name": "Recovery codes",
+                "folderId": null,
+                "notes": "1111 2222",
+                "secureNote": { "type": 0 }
+            },
+            {
+                "type": 3,
+                "name": "Visa",
+                "card": {
+                    "cardholderName": "Alice Example",
+                    "brand": "Visa",
+                    "number": "4111111111111111",
+                    "expMonth": "3",
+                    "expYear": "2030",
+                    "code": "123"
+                }
+            },
+            {
+                "type": 4,
+                "name": "Me",
+                "identity": {
+                    "firstName": "Alice",
+                    "lastName": "Example",
+                    "ssn": "123-45-6789",
+                    "username": "alice"
+                }
+            },
+            { "type": 1, "name": "Empty", "login": { "username": null, "password": null } },
+            { "type": 1, "name": "Bad", "login": { "totp": "not a secret!" } },
+            { "type": 9, "name": "From the future" }
+        ]
+    }"#;
+
+    #[test]
+    fn reads_bitwarden_export() {
+        let import = read_bitwarden_json(EXPORT.as_bytes()).expect("Could not read export");
+        assert_eq!(import.entries.len(), 4);
+
+        let positions: Vec<_> = import.errors.iter().map(|err| err.position).collect();
+        assert_eq!(positions, vec![5, 6, 7]);
+
+        let mail = &import.entries[0];
+        assert_eq!(mail.group_path, vec!["Work", "Mail"]);
+        assert_eq!(mail.field(URL_FIELD), Some("https://mail.example.com"));
+        assert_eq!(
+            mail.field("KP2A_URL_1"),
+            Some("https://webmail.example.com")
+        );
+        assert_eq!(mail.field("Link"), None);
+        assert!(mail.field(OTP_FIELD).is_some());
+
+        let kp_mail = mail.to_keepass();
+        assert!(matches!(
+            kp_mail.fields.get("PIN"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+        assert!(matches!(
+            kp_mail.fields.get("Region"),
+            Some(keepass::db::Value::Unprotected(_))
+        ));
+
+        let note = &import.entries[1];
+        assert!(note.group_path.is_empty());
+        assert_eq!(note.field(NOTES_FIELD), Some("1111 2222"));
+
+        let card = &import.entries[2];
+        assert!(card.template.is_some_and(|t| t.name == CARD_TEMPLATE.name));
+        assert_eq!(card.field("Expiration"), Some("03/2030"));
+        assert!(matches!(
+            card.to_keepass().fields.get("Number"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+
+        let identity = &import.entries[3];
+        assert!(
+            identity
+                .template
+                .is_some_and(|t| t.name == IDENTITY_TEMPLATE.name)
+        );
+        assert_eq!(identity.field(USERNAME_FIELD), Some("alice"));
+        assert_eq!(identity.field("Last Name"), Some("Example"));
+
+        let encrypted = br#"{ "encrypted": true, "encKeyValidation_DO_NOT_EDIT": "x" }"#;
+        assert!(read_bitwarden_json(encrypted).is_err());
+        assert!(read_bitwarden_json(b"name,url").is_err());
+    }
+}
@@ -7,7 +7,7 @@ use unicase::UniCase;
 use uuid::Uuid;
 
 use super::{
-    RxDatabase, RxEntry, RxGroup, RxSearchType, RxTag, RxTemplate,
+    RxCategory, RxDatabase, RxEntry, RxGroup, RxSavedSearch, RxSearchType, RxTag, RxTemplate,
     search::{CaseInsensitiveSearch, Search, search_contained_ref},
 };
 
@@ -66,6 +66,8 @@ pub enum RxContainedType {
     Group,
     Template,
     Tag,
+    SavedSearch,
+    Category,
     VirtualRoot,
 }
 
@@ -103,7 +105,11 @@ impl RxContainer {
 
     pub fn get_ref(&self) -> Option<RxContainedRef> {
         match self.contained_type {
-            RxContainedType::Group | RxContainedType::Template | RxContainedType::Tag => {
+            RxContainedType::Group
+            | RxContainedType::Template
+            | RxContainedType::Tag
+            | RxContainedType::SavedSearch
+            | RxContainedType::Category => {
                 self.item().grouping().and_then(|g| g.contained_ref())
             }
             RxContainedType::Entry => self.item().entry().map(|e| RxContainedRef::Entry(e)),
@@ -297,6 +303,44 @@ impl IntoContainer for RxTag {
     }
 }
 
+impl IntoContainer for RxSavedSearch {
+    fn into_container(&self, db: &RxDatabase) -> RxContainer {
+        let entries: Vec<_> = self
+            .entry_uuids
+            .iter()
+            .flat_map(|id| db.get_entry(*id).map(|entry| RxContainer::from(entry, db)))
+            .collect();
+
+        RxContainer {
+            is_root: false,
+            contained_type: RxContainedType::SavedSearch,
+            item: RxContainerItem::Grouping(RxContainerGrouping {
+                children: entries,
+                grouping: RxGrouping::SavedSearch(self.clone()),
+            }),
+        }
+    }
+}
+
+impl IntoContainer for RxCategory {
+    fn into_container(&self, db: &RxDatabase) -> RxContainer {
+        let entries: Vec<_> = self
+            .entry_uuids
+            .iter()
+            .flat_map(|id| db.get_entry(*id).map(|entry| RxContainer::from(entry, db)))
+            .collect();
+
+        RxContainer {
+            is_root: false,
+            contained_type: RxContainedType::Category,
+            item: RxContainerItem::Grouping(RxContainerGrouping {
+                children: entries,
+                grouping: RxGrouping::Category(self.clone()),
+            }),
+        }
+    }
+}
+
 impl IntoContainer for Rc<RxGroup> {
     fn into_container(&self, db: &RxDatabase) -> RxContainer {
         let mut subgroups: Vec<_> = self
@@ -361,6 +405,8 @@ impl IntoContainer for Rc<RxEntry> {
 pub enum RxGrouping {
     Template(Rc<RxTemplate>),
     Tag(RxTag),
+    SavedSearch(RxSavedSearch),
+    Category(RxCategory),
     Group(Rc<RxGroup>),
     VirtualRoot,
 }
@@ -371,6 +417,10 @@ impl RxGrouping {
             RxGrouping::Group(group) => Some(RxContainedRef::Group(group.clone())),
             RxGrouping::Template(template) => Some(RxContainedRef::Template(template.clone())),
             RxGrouping::Tag(tag) => Some(RxContainedRef::Tag(tag.clone())),
+            RxGrouping::SavedSearch(search) => {
+                Some(RxContainedRef::SavedSearch(search.clone()))
+            }
+            RxGrouping::Category(category) => Some(RxContainedRef::Category(category.clone())),
             RxGrouping::VirtualRoot => None,
         }
     }
@@ -389,6 +439,8 @@ impl RxContainerGrouping {
             RxGrouping::Group(group) => group.uuid,
             RxGrouping::Template(template) => template.uuid,
             RxGrouping::Tag(tag) => tag.uuid,
+            RxGrouping::SavedSearch(search) => search.uuid,
+            RxGrouping::Category(category) => category.uuid,
             RxGrouping::VirtualRoot => Uuid::default(),
         }
     }
@@ -405,6 +457,8 @@ pub enum RxContainedRef {
     Group(Rc<RxGroup>),
     Template(Rc<RxTemplate>),
     Tag(RxTag),
+    SavedSearch(RxSavedSearch),
+    Category(RxCategory),
     Entry(Rc<RxEntry>),
 }
 
@@ -436,7 +490,9 @@ impl RxContainedRef {
             RxContainedRef::Group(_) => 1,
             RxContainedRef::Template(_) => 2,
             RxContainedRef::Tag(_) => 3,
-            RxContainedRef::Entry(_) => 4,
+            RxContainedRef::SavedSearch(_) => 4,
+            RxContainedRef::Category(_) => 5,
+            RxContainedRef::Entry(_) => 6,
         }
     }
 
@@ -446,6 +502,8 @@ impl RxContainedRef {
             RxContainedRef::Group(group) => group.uuid,
             RxContainedRef::Template(template) => template.uuid,
             RxContainedRef::Tag(tag) => tag.uuid,
+            RxContainedRef::SavedSearch(search) => search.uuid,
+            RxContainedRef::Category(category) => category.uuid,
             RxContainedRef::VirtualRoot(_) => Uuid::default(),
         }
     }
@@ -459,6 +517,8 @@ impl RxContainedRef {
             RxContainedRef::Group(group) => group.name.clone(),
             RxContainedRef::Template(template) => template.name.clone(),
             RxContainedRef::Tag(tag) => tag.name.clone(),
+            RxContainedRef::SavedSearch(search) => search.name.clone(),
+            RxContainedRef::Category(category) => category.name.clone(),
             RxContainedRef::VirtualRoot(name) => name.clone(),
         }
     }
@@ -467,7 +527,10 @@ impl RxContainedRef {
         match self {
             RxContainedRef::Entry(entry) => Some(entry.parent_group),
             RxContainedRef::Group(group) => group.parent,
-            RxContainedRef::Template(_) | RxContainedRef::Tag(_) => Some(Uuid::default()), //virtual root
+            RxContainedRef::Template(_)
+            | RxContainedRef::Tag(_)
+            | RxContainedRef::SavedSearch(_)
+            | RxContainedRef::Category(_) => Some(Uuid::default()), //virtual root
             RxContainedRef::VirtualRoot(_) => None,
         }
     }
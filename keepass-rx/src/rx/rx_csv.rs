@@ -0,0 +1,707 @@
+use anyhow::{Result, anyhow};
+use indexmap::IndexSet;
+use std::collections::HashMap;
+use std::io::Write;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use super::icons::RxIcon;
+use super::rx_import::{
+    NOTES_FIELD, OTP_FIELD, PASSWORD_FIELD, RxImport, RxImportedEntry, TITLE_FIELD, URL_FIELD,
+    USERNAME_FIELD,
+};
+use super::rx_otp::imported_otp_uri;
+use super::{RxContainedRef, RxContainer, RxDatabase, RxEntry, RxTimes, RxValueKeyRef};
+
+/// Columns of a KeePassXC CSV export, in the order KeePassXC writes
+/// (and expects to read) them.
+pub const KEEPASSXC_CSV_COLUMNS: [&str; 10] = [
+    "Group",
+    "Title",
+    "Username",
+    "Password",
+    "URL",
+    "Notes",
+    "TOTP",
+    "Icon",
+    "Last Modified",
+    "Created",
+];
+
+/// A string for a field value, sized up front so the secret is not
+/// left behind in a smaller buffer when the string grows.
+fn field_buffer(capacity: usize) -> Zeroizing<String> {
+    Zeroizing::new(String::with_capacity(capacity))
+}
+
+/// Quote a CSV field. Like KeePassXC, every field is quoted, so
+/// commas and line breaks in notes survive.
+fn quote_field(value: &str) -> Zeroizing<String> {
+    let mut quoted = field_buffer(value.len() + value.matches('"').count() + 2);
+
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            quoted.push('"');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+fn write_row<W: Write>(out: &mut W, fields: &[Zeroizing<String>]) -> Result<()> {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.write_all(b",")?;
+        }
+
+        out.write_all(quote_field(field).as_bytes())?;
+    }
+
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Slash-separated path of a group, starting at the root group, like
+/// KeePassXC's Group column.
+fn group_path(db: &RxDatabase, group_uuid: Uuid) -> String {
+    let mut names = vec![];
+    let mut next = Some(group_uuid);
+
+    while let Some(group) = next.and_then(|uuid| db.get_group(uuid)) {
+        names.push(group.name.clone());
+        next = group.parent;
+    }
+
+    names.reverse();
+    names.join("/")
+}
+
+/// One row of the export. Each secret is decrypted on its own, and
+/// wiped as soon as the row is written.
+fn entry_row(entry: &RxEntry, group: &str) -> Vec<Zeroizing<String>> {
+    let value = |field: Option<RxValueKeyRef<'_>>| {
+        field.and_then(|field| field.value()).unwrap_or_default()
+    };
+
+    let icon = match entry.icon {
+        RxIcon::Builtin(icon_id) => icon_id,
+        _ => 0,
+    };
+
+    vec![
+        Zeroizing::new(group.to_string()),
+        value(entry.title()),
+        value(entry.username()),
+        value(entry.password()),
+        value(entry.url()),
+        value(entry.notes()),
+        entry.otp_export_uri().unwrap_or_default(),
+        Zeroizing::new(icon.to_string()),
+        Zeroizing::new(RxTimes::format(&entry.times.last_modified).unwrap_or_default()),
+        Zeroizing::new(RxTimes::format(&entry.times.created).unwrap_or_default()),
+    ]
+}
+
+/// Write every entry in the container, and in all containers below
+/// it, as KeePassXC CSV. Returns the number of entries written.
+pub fn export_csv<W: Write>(
+    db: &RxDatabase,
+    container: &RxContainer,
+    out: &mut W,
+) -> Result<usize> {
+    let header: Vec<_> = KEEPASSXC_CSV_COLUMNS
+        .iter()
+        .map(|column| Zeroizing::new(column.to_string()))
+        .collect();
+    write_row(out, &header)?;
+
+    let containers =
+        std::iter::once(container).chain(container.child_containers_recursive().into_values());
+
+    let mut group_paths: HashMap<Uuid, String> = HashMap::new();
+    let mut written = IndexSet::new();
+
+    for child in containers {
+        let Some(RxContainedRef::Entry(entry)) = child.get_ref() else {
+            continue;
+        };
+
+        if !written.insert(entry.uuid) {
+            continue;
+        }
+
+        let group = group_paths
+            .entry(entry.parent_group)
+            .or_insert_with(|| group_path(db, entry.parent_group));
+
+        write_row(out, &entry_row(&entry, group))?;
+    }
+
+    out.flush()?;
+    Ok(written.len())
+}
+
+/// Split CSV text into rows of fields, as RFC 4180 describes it:
+/// fields may be quoted, quotes inside are doubled, and quoted fields
+/// may hold commas and line breaks.
+fn parse_csv(text: &str) -> Result<Vec<Vec<Zeroizing<String>>>> {
+    let bytes = text.as_bytes();
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut pos = 0;
+
+    let field_end = |from: usize| {
+        text[from..]
+            .find([',', '\r', '\n'])
+            .map_or(text.len(), |i| from + i)
+    };
+
+    loop {
+        let field = if bytes.get(pos) == Some(&b'"') {
+            let start = pos + 1;
+            let mut end = start;
+
+            loop {
+                let quote = text[end..].find('"').ok_or(anyhow!(
+                    "Row {} has a quote that is never closed",
+                    rows.len() + 1
+                ))?;
+
+                end += quote;
+                match bytes.get(end + 1) {
+                    Some(b'"') => end += 2,
+                    _ => break,
+                }
+            }
+
+            let quoted = &text[start..end];
+            let mut field = field_buffer(quoted.len());
+            for (index, part) in quoted.split("\"\"").enumerate() {
+                if index > 0 {
+                    field.push('"');
+                }
+                field.push_str(part);
+            }
+
+            // Anything between the closing quote and the next comma is
+            // not valid CSV, and dropped.
+            pos = field_end(end + 1);
+            field
+        } else {
+            let end = field_end(pos);
+            let field = Zeroizing::new(text[pos..end].to_string());
+            pos = end;
+            field
+        };
+
+        row.push(field);
+
+        match bytes.get(pos) {
+            Some(b',') => pos += 1,
+            Some(b'\r') | Some(b'\n') => {
+                if bytes[pos] == b'\r' && bytes.get(pos + 1) == Some(&b'\n') {
+                    pos += 1;
+                }
+
+                pos += 1;
+                rows.push(std::mem::take(&mut row));
+                if pos >= bytes.len() {
+                    break;
+                }
+            }
+            _ => {
+                rows.push(row);
+                break;
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+/// What a column of an imported CSV file holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RxCsvColumn {
+    Ignore,
+    Group,
+    Title,
+    Username,
+    Password,
+    Url,
+    Notes,
+    Totp,
+    /// Bitwarden's fields column: one "name: value" custom field per
+    /// line.
+    Fields,
+    /// A custom field, named after the column.
+    CustomField,
+}
+
+impl RxCsvColumn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RxCsvColumn::Ignore => "ignore",
+            RxCsvColumn::Group => "group",
+            RxCsvColumn::Title => "title",
+            RxCsvColumn::Username => "username",
+            RxCsvColumn::Password => "password",
+            RxCsvColumn::Url => "url",
+            RxCsvColumn::Notes => "notes",
+            RxCsvColumn::Totp => "totp",
+            RxCsvColumn::Fields => "fields",
+            RxCsvColumn::CustomField => "custom",
+        }
+    }
+}
+
+impl TryFrom<&str> for RxCsvColumn {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "ignore" => Ok(RxCsvColumn::Ignore),
+            "group" => Ok(RxCsvColumn::Group),
+            "title" => Ok(RxCsvColumn::Title),
+            "username" => Ok(RxCsvColumn::Username),
+            "password" => Ok(RxCsvColumn::Password),
+            "url" => Ok(RxCsvColumn::Url),
+            "notes" => Ok(RxCsvColumn::Notes),
+            "totp" => Ok(RxCsvColumn::Totp),
+            "fields" => Ok(RxCsvColumn::Fields),
+            "custom" => Ok(RxCsvColumn::CustomField),
+            other => Err(anyhow!("Unknown CSV column {}", other)),
+        }
+    }
+}
+
+/// Column mappings for the CSV exports of other password managers.
+/// Custom guesses the mapping from the column names, which the user
+/// can then change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RxCsvPreset {
+    KeePassXC,
+    Bitwarden,
+    Chrome,
+    Firefox,
+    Custom,
+}
+
+impl RxCsvPreset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RxCsvPreset::KeePassXC => "keepassxc",
+            RxCsvPreset::Bitwarden => "bitwarden",
+            RxCsvPreset::Chrome => "chrome",
+            RxCsvPreset::Firefox => "firefox",
+            RxCsvPreset::Custom => "custom",
+        }
+    }
+
+    /// Guess which password manager wrote the file from its header.
+    pub fn detect(header: &[&str]) -> RxCsvPreset {
+        let has = |name: &str| {
+            header
+                .iter()
+                .any(|column| column.trim().eq_ignore_ascii_case(name))
+        };
+
+        if has("login_password") {
+            RxCsvPreset::Bitwarden
+        } else if has("httpRealm") || has("formActionOrigin") {
+            RxCsvPreset::Firefox
+        } else if has("Group") && has("Title") {
+            RxCsvPreset::KeePassXC
+        } else if has("name") && has("url") && has("username") && has("password") {
+            RxCsvPreset::Chrome
+        } else {
+            RxCsvPreset::Custom
+        }
+    }
+
+    /// What the column with the given name holds.
+    pub fn column_for(&self, header: &str) -> RxCsvColumn {
+        let header = header.trim().to_lowercase();
+
+        match self {
+            RxCsvPreset::KeePassXC => match header.as_str() {
+                "group" => RxCsvColumn::Group,
+                "title" => RxCsvColumn::Title,
+                "username" => RxCsvColumn::Username,
+                "password" => RxCsvColumn::Password,
+                "url" => RxCsvColumn::Url,
+                "notes" => RxCsvColumn::Notes,
+                "totp" => RxCsvColumn::Totp,
+                _ => RxCsvColumn::Ignore,
+            },
+            RxCsvPreset::Bitwarden => match header.as_str() {
+                "folder" => RxCsvColumn::Group,
+                "name" => RxCsvColumn::Title,
+                "login_username" => RxCsvColumn::Username,
+                "login_password" => RxCsvColumn::Password,
+                "login_uri" => RxCsvColumn::Url,
+                "notes" => RxCsvColumn::Notes,
+                "login_totp" => RxCsvColumn::Totp,
+                "fields" => RxCsvColumn::Fields,
+                _ => RxCsvColumn::Ignore,
+            },
+            RxCsvPreset::Chrome => match header.as_str() {
+                "name" => RxCsvColumn::Title,
+                "url" => RxCsvColumn::Url,
+                "username" => RxCsvColumn::Username,
+                "password" => RxCsvColumn::Password,
+                "note" => RxCsvColumn::Notes,
+                _ => RxCsvColumn::Ignore,
+            },
+            RxCsvPreset::Firefox => match header.as_str() {
+                "url" => RxCsvColumn::Url,
+                "username" => RxCsvColumn::Username,
+                "password" => RxCsvColumn::Password,
+                _ => RxCsvColumn::Ignore,
+            },
+            RxCsvPreset::Custom => match header.as_str() {
+                "group" | "folder" | "path" => RxCsvColumn::Group,
+                "title" | "name" => RxCsvColumn::Title,
+                "username" | "user" | "login" | "email" | "login_username" => {
+                    RxCsvColumn::Username
+                }
+                "password" | "login_password" => RxCsvColumn::Password,
+                "url" | "uri" | "website" | "login_uri" => RxCsvColumn::Url,
+                "notes" | "note" | "comments" => RxCsvColumn::Notes,
+                "totp" | "otp" | "2fa" | "login_totp" => RxCsvColumn::Totp,
+                "" => RxCsvColumn::Ignore,
+                _ => RxCsvColumn::CustomField,
+            },
+        }
+    }
+}
+
+impl TryFrom<&str> for RxCsvPreset {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "keepassxc" => Ok(RxCsvPreset::KeePassXC),
+            "bitwarden" => Ok(RxCsvPreset::Bitwarden),
+            "chrome" => Ok(RxCsvPreset::Chrome),
+            "firefox" => Ok(RxCsvPreset::Firefox),
+            "custom" => Ok(RxCsvPreset::Custom),
+            other => Err(anyhow!("Unknown CSV preset {}", other)),
+        }
+    }
+}
+
+/// The column names of a CSV file, from its first row.
+pub fn read_csv_header(text: &str) -> Result<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let header = parse_csv(text.lines().next().unwrap_or_default())?;
+
+    Ok(header
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|column| column.trim().to_string())
+        .collect())
+}
+
+/// Group names from a slash-separated path. KeePassXC starts the path
+/// with the root group, which is left out, as entries are imported
+/// into a group of the user's choosing.
+fn import_group_path(path: &str, root_name: &str) -> Vec<String> {
+    let mut names: Vec<_> = path
+        .split('/')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if names.first().is_some_and(|name| name == root_name) {
+        names.remove(0);
+    }
+
+    names
+}
+
+/// Bitwarden puts several URIs in one column, separated by commas.
+/// The value is only split if every part is a URL, since commas are
+/// allowed in URLs too.
+fn split_urls(value: &str) -> Vec<&str> {
+    let urls: Vec<_> = value.split(',').map(str::trim).collect();
+    match urls.len() > 1 && urls.iter().all(|url| url.contains("://")) {
+        true => urls,
+        false => vec![value],
+    }
+}
+
+fn import_row(
+    header: &[Zeroizing<String>],
+    row: &[Zeroizing<String>],
+    columns: &[RxCsvColumn],
+    root_name: &str,
+) -> Result<RxImportedEntry> {
+    let mut entry = RxImportedEntry::default();
+
+    for ((name, value), column) in header.iter().zip(row).zip(columns) {
+        let value = value.as_str();
+        match column {
+            RxCsvColumn::Ignore => (),
+            RxCsvColumn::Group => entry.group_path = import_group_path(value, root_name),
+            RxCsvColumn::Title => entry.set_field(TITLE_FIELD, value, false),
+            RxCsvColumn::Username => entry.set_field(USERNAME_FIELD, value, false),
+            RxCsvColumn::Password => entry.set_field(PASSWORD_FIELD, value, true),
+            RxCsvColumn::Url => entry.add_urls(split_urls(value)),
+            RxCsvColumn::Notes => entry.set_field(NOTES_FIELD, value, false),
+            RxCsvColumn::Totp if value.trim().is_empty() => (),
+            RxCsvColumn::Totp => {
+                let otp = imported_otp_uri(value)
+                    .ok_or(anyhow!("The TOTP value is not a valid secret or URI"))?;
+                entry.set_field(OTP_FIELD, &otp, true);
+            }
+            RxCsvColumn::Fields => {
+                for line in value.lines().filter(|line| !line.trim().is_empty()) {
+                    let (field_name, field_value) =
+                        line.split_once(": ").unwrap_or((line, ""));
+                    entry.add_custom_field(field_name, field_value, false);
+                }
+            }
+            RxCsvColumn::CustomField => entry.add_custom_field(name, value, false),
+        }
+    }
+
+    if entry.is_empty() {
+        return Err(anyhow!("There is nothing to import"));
+    }
+
+    entry.ensure_title();
+    Ok(entry)
+}
+
+/// Read the entries of a CSV file, with the given meaning for each
+/// column. The first row is the header. Rows that cannot be imported
+/// are reported by their row number, with the header as row 1.
+pub fn read_csv(text: &str, columns: &[RxCsvColumn], root_name: &str) -> Result<RxImport> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = parse_csv(text)?.into_iter();
+    let header = rows.next().unwrap_or_default();
+
+    if columns.len() != header.len() {
+        return Err(anyhow!(
+            "The file has {} columns, but {} were mapped",
+            header.len(),
+            columns.len()
+        ));
+    }
+
+    let mut import = RxImport::default();
+    for (index, row) in rows.enumerate() {
+        let row_number = index + 2;
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        if row.len() != header.len() {
+            import.skip(
+                row_number,
+                format!("Expected {} columns, found {}", header.len(), row.len()),
+            );
+            continue;
+        }
+
+        match import_row(&header, &row, columns, root_name) {
+            Ok(entry) => import.entries.push(entry),
+            Err(err) => import.skip(row_number, err.to_string()),
+        }
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rx::test_support::{keepass_db, load_db};
+    use crate::rx::virtual_hierarchy::{DefaultView, VirtualHierarchy};
+    use chrono::NaiveDate;
+    use secstr::SecStr;
+
+    #[test]
+    fn exports_keepassxc_csv() {
+        let mut root = keepass::db::Group::new("Root");
+        let mut work = keepass::db::Group::new("Work");
+        let work_id = work.uuid;
+
+        let time = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(3, 4, 5)
+            .unwrap();
+
+        let mut entry = keepass::db::Entry::new();
+        entry.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("Mail \"work\"".to_string()),
+        );
+        entry.fields.insert(
+            "UserName".to_string(),
+            keepass::db::Value::Unprotected("alice".to_string()),
+        );
+        entry.fields.insert(
+            "Password".to_string(),
+            keepass::db::Value::Protected(SecStr::from("hunter2")),
+        );
+        entry.fields.insert(
+            "Notes".to_string(),
+            keepass::db::Value::Unprotected("line one\nline, two".to_string()),
+        );
+        entry.times.set_creation(time);
+        entry.times.set_last_modification(time);
+        work.add_child(keepass::db::Node::Entry(entry));
+
+        let mut other = keepass::db::Entry::new();
+        other.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("Elsewhere".to_string()),
+        );
+
+        root.add_child(keepass::db::Node::Entry(other));
+        root.add_child(keepass::db::Node::Group(work));
+
+        let rx_db = load_db(keepass_db(root));
+        let view = DefaultView::new(&rx_db);
+        let container = view.root().get_container(work_id).expect("No container");
+
+        let mut csv = Zeroizing::new(Vec::new());
+        let count = export_csv(&rx_db, container, &mut *csv).expect("Could not export");
+        assert_eq!(count, 1);
+
+        assert_eq!(
+            std::str::from_utf8(&csv).unwrap(),
+            concat!(
+                "\"Group\",\"Title\",\"Username\",\"Password\",\"URL\",\"Notes\",\"TOTP\",",
+                "\"Icon\",\"Last Modified\",\"Created\"\n",
+                "\"Root/Work\",\"Mail \"\"work\"\"\",\"alice\",\"hunter2\",\"\",",
+                "\"line one\nline, two\",\"\",\"0\",\"2024-01-02T03:04:05Z\",",
+                "\"2024-01-02T03:04:05Z\"\n",
+            )
+        );
+
+        let root = view.root().get_container(view.root().uuid()).unwrap();
+        let mut everything = Vec::new();
+        let count = export_csv(&rx_db, root, &mut everything).expect("Could not export");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn parses_quoted_csv() {
+        let rows = parse_csv("a,\"b,\"\"c\"\"\",\r\n\"multi\nline\",x,\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][1].as_str(), "b,\"c\"");
+        assert_eq!(rows[0][2].as_str(), "");
+        assert_eq!(rows[1][0].as_str(), "multi\nline");
+
+        let err = parse_csv("a,b\n\"never closed,c\n").unwrap_err();
+        assert_eq!(err.to_string(), "Row 2 has a quote that is never closed");
+    }
+
+    #[test]
+    fn detects_csv_presets() {
+        let detect = |header: &str| {
+            let header = read_csv_header(header).unwrap();
+            RxCsvPreset::detect(&header.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            detect(
+                "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp"
+            ),
+            RxCsvPreset::Bitwarden
+        );
+        assert_eq!(
+            detect("url,username,password,httpRealm,formActionOrigin,guid"),
+            RxCsvPreset::Firefox
+        );
+        assert_eq!(
+            detect("\u{feff}\"Group\",\"Title\",\"Username\""),
+            RxCsvPreset::KeePassXC
+        );
+        assert_eq!(
+            detect("name,url,username,password,note"),
+            RxCsvPreset::Chrome
+        );
+        assert_eq!(detect("site,login,secret"), RxCsvPreset::Custom);
+        assert_eq!(
+            RxCsvPreset::Custom.column_for("secret"),
+            RxCsvColumn::CustomField
+        );
+    }
+
+    #[test]
+    fn reads_csv_entries() {
+        let csv = concat!(
+            "\"Group\",\"Title\",\"Username\",\"Password\",\"URL\",\"Notes\",\"TOTP\",\"Icon\"\n",
+            "\"Root/Work/Mail\",\"Mail\",\"alice\",\"hunter2\",\"https://mail.example.com\",\"a\nb\",\"JBSWY3DPEHPK3PXP\",\"0\"\n",
+            "\n",
+            "\"Root\",\"\",\"bob\",\"pw\",\"https://www.example.org/login\",\"\",\"\",\"0\"\n",
+            "\"Root\",\"Broken\",\"carol\"\n",
+            "\"Root\",\"Bad OTP\",\"dave\",\"pw\",\"\",\"\",\"not a secret!\",\"0\"\n",
+            "\"Root\",\"\",\"\",\"\",\"\",\"\",\"\",\"0\"\n",
+        );
+
+        let header = read_csv_header(csv).unwrap();
+        let columns: Vec<_> = header
+            .iter()
+            .map(|column| RxCsvPreset::KeePassXC.column_for(column))
+            .collect();
+
+        let import = read_csv(csv, &columns, "Root").unwrap();
+        assert_eq!(import.entries.len(), 2);
+
+        let mail = &import.entries[0];
+        assert_eq!(mail.group_path, vec!["Work", "Mail"]);
+        assert_eq!(mail.field(NOTES_FIELD), Some("a\nb"));
+        assert!(
+            mail.field(OTP_FIELD)
+                .is_some_and(|otp| otp.starts_with("otpauth://totp/"))
+        );
+
+        let bob = &import.entries[1];
+        assert!(bob.group_path.is_empty());
+        assert_eq!(bob.field(TITLE_FIELD), Some("example.org"));
+
+        let positions: Vec<_> = import.errors.iter().map(|err| err.position).collect();
+        assert_eq!(positions, vec![5, 6, 7]);
+
+        assert!(read_csv(csv, &columns[1..], "Root").is_err());
+    }
+
+    #[test]
+    fn reads_bitwarden_csv() {
+        let csv = concat!(
+            "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp\n",
+            "Social/Chat,,login,Chat,,\"PIN: 1234\nRecovery: abc\",0,\"https://a.example,https://b.example\",alice,pw,steam://JBSWY3DPEHPK3PXP\n",
+        );
+
+        let header = read_csv_header(csv).unwrap();
+        let columns: Vec<_> = header
+            .iter()
+            .map(|column| RxCsvPreset::Bitwarden.column_for(column))
+            .collect();
+
+        let import = read_csv(csv, &columns, "Root").unwrap();
+        assert!(import.errors.is_empty());
+
+        let entry = &import.entries[0];
+        assert_eq!(entry.group_path, vec!["Social", "Chat"]);
+        assert_eq!(entry.field("PIN"), Some("1234"));
+        assert_eq!(entry.field("Recovery"), Some("abc"));
+        assert_eq!(entry.field(URL_FIELD), Some("https://a.example"));
+        assert_eq!(entry.field("KP2A_URL_1"), Some("https://b.example"));
+        assert!(
+            entry
+                .field(OTP_FIELD)
+                .is_some_and(|otp| otp.starts_with("otpauth://totp/Steam:"))
+        );
+    }
+}
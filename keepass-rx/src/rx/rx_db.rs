@@ -1,13 +1,22 @@
 use crate::crypto::MasterKey;
 
 use super::rx_loader::RxLoader;
-use super::{RxEntry, RxGroup, RxTemplate, RxTotp, ZeroableDatabase};
+use super::{
+    RxEntry, RxFieldName, RxFieldProtection, RxGroup, RxImportTemplate, RxImportedEntry,
+    RxMigratedOtp, RxPwnedChecker, RxTemplate, RxTimes, RxTotp, RxValue, TEMPLATE_FIELD_NAME,
+    ZeroableDatabase,
+};
 use anyhow::{Result, anyhow};
 use indexmap::IndexMap;
-use keepass::config::DatabaseConfig;
-use keepass::db::Meta;
+use keepass::Database;
+use keepass::config::{DatabaseConfig, DatabaseVersion};
+use keepass::db::{
+    DeletedObject, DeletedObjects, Entry, Group, HeaderAttachment, Meta, Node, Times, Value,
+};
+use libsodium_rs::utils::SecureVec;
 use paste::paste;
 use regex::Regex;
+use secstr::SecStr;
 use std::rc::Rc;
 use std::sync::LazyLock;
 use std::{collections::HashMap, str::FromStr};
@@ -42,6 +51,10 @@ macro_rules! get_kpxc_field {
     }};
 }
 
+/// Prefix of the custom data keys that KeePassXC stores saved
+/// searches under. The rest of the key is the name of the search.
+const KPXC_SAVED_SEARCH_PREFIX: &str = "KPXC_SavedSearch_";
+
 #[derive(Default, Clone)]
 pub struct RxMetadata {
     pub color: Option<String>,
@@ -52,8 +65,8 @@ pub struct RxMetadata {
 }
 
 impl RxMetadata {
-    pub fn new(mut config: DatabaseConfig, meta: Meta) -> RxMetadata {
-        let mut custom_db_data = config.public_custom_data.take().map(|pcd| pcd.data);
+    pub fn new(config: &DatabaseConfig, meta: &Meta) -> RxMetadata {
+        let mut custom_db_data = config.public_custom_data.clone().map(|pcd| pcd.data);
 
         RxMetadata {
             name: get_kpxc_field!(String, custom_db_data, "KPXC_PUBLIC_NAME"),
@@ -64,6 +77,23 @@ impl RxMetadata {
     }
 }
 
+/// Look up the entry password in the breached password list, and
+/// flag the entry if it is there. Returns whether it is.
+fn flag_pwned(entry: &mut Rc<RxEntry>, checker: &RxPwnedChecker) -> Result<bool> {
+    let pwned = match entry.password().and_then(|p| p.value_secure()) {
+        Some(password) if !password.is_empty() => checker.lookup(&password[..])?.is_some(),
+        _ => false,
+    };
+
+    // Avoid copying entries that are shared with views when nothing
+    // changed.
+    if entry.pwned != pwned {
+        Rc::make_mut(entry).pwned = pwned;
+    }
+
+    Ok(pwned)
+}
+
 #[derive(Clone)]
 pub struct RxDatabase {
     // Not to be confused with encryption key for master DB password.
@@ -74,6 +104,16 @@ pub struct RxDatabase {
     templates: HashMap<Uuid, Rc<RxTemplate>>,
     all_groups: IndexMap<Uuid, Rc<RxGroup>>,
     all_entries: IndexMap<Uuid, Rc<RxEntry>>,
+
+    // Original database settings and metadata, needed to write the
+    // database back out.
+    config: DatabaseConfig,
+    meta: Meta,
+    header_attachments: Vec<HeaderAttachment>,
+    deleted_objects: DeletedObjects,
+
+    /// Whether there are changes that have not yet been saved.
+    dirty: bool,
 }
 
 impl Zeroize for RxDatabase {
@@ -143,6 +183,11 @@ impl RxDatabase {
             all_groups: loaded.state.all_groups,
             all_entries: loaded.state.all_entries,
             metadata: loaded.metadata,
+            config: loaded.config,
+            meta: loaded.meta,
+            header_attachments: loaded.header_attachments,
+            deleted_objects: loaded.deleted_objects,
+            dirty: false,
         };
 
         // Map templates. Easier to do when we have access to DB logic.
@@ -211,10 +256,703 @@ impl RxDatabase {
         self.templates.values()
     }
 
+    /// Searches saved by KeePassXC, as (name, query) pairs sorted by
+    /// name. They are stored in the database's custom data.
+    pub fn saved_searches(&self) -> Vec<(String, String)> {
+        let mut searches: Vec<_> = self
+            .meta
+            .custom_data
+            .items
+            .iter()
+            .filter_map(|(key, item)| {
+                let name = key.strip_prefix(KPXC_SAVED_SEARCH_PREFIX)?;
+                let query = match item.value.as_ref()? {
+                    Value::Unprotected(query) => query.clone(),
+                    Value::Protected(query) => {
+                        String::from_utf8(query.unsecure().to_vec()).ok()?
+                    }
+                    Value::Bytes(_) => return None,
+                };
+
+                Some((name.to_string(), query))
+            })
+            .collect();
+
+        searches.sort_by(|(a, _), (b, _)| a.cmp(b));
+        searches
+    }
+
     pub fn get_template(&self, template_uuid: Uuid) -> Option<Rc<RxTemplate>> {
         self.templates.get(&template_uuid).cloned()
     }
 
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Mutable access to an entry. If the entry is shared (e.g. by a
+    /// view), it is copied, so views must be rebuilt after changes.
+    fn get_entry_mut(&mut self, entry_uuid: Uuid) -> Result<&mut RxEntry> {
+        self.all_entries
+            .get_mut(&entry_uuid)
+            .map(Rc::make_mut)
+            .ok_or(anyhow!("Could not find entry {}", entry_uuid))
+    }
+
+    /// Update the standard fields (title, username, password, URL,
+    /// notes) of an entry. Values are encrypted under the master key.
+    /// A value of None clears the field.
+    pub fn update_entry(
+        &mut self,
+        entry_uuid: Uuid,
+        changes: Vec<(RxFieldName, Option<SecStr>)>,
+    ) -> Result<()> {
+        // Encrypt everything before touching the entry, so a failure
+        // does not leave it half-updated.
+        let encrypted_changes = changes
+            .into_iter()
+            .map(|(field_name, value)| {
+                let encrypted = value
+                    .map(|val| RxValue::encrypted(&self.master_key, val))
+                    .transpose()?;
+                Ok((field_name, encrypted))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (max_items, max_size) = self.history_limits();
+        let entry = self.get_entry_mut(entry_uuid)?;
+        entry.push_history(max_items, max_size);
+
+        for (field_name, value) in encrypted_changes {
+            entry.set_field(&field_name, value)?;
+        }
+
+        entry.touch_modified();
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Maximum number of history items per entry, and maximum size
+    /// of an entry's history in bytes. KeePass stores -1 for no
+    /// limit.
+    fn history_limits(&self) -> (Option<usize>, Option<usize>) {
+        let max_items = self
+            .meta
+            .history_max_items
+            .and_then(|max| usize::try_from(max).ok());
+
+        let max_size = self
+            .meta
+            .history_max_size
+            .and_then(|max| usize::try_from(max).ok());
+
+        (max_items, max_size)
+    }
+
+    /// Restore an older revision of an entry. Like KeePassXC, the
+    /// current state is kept in the history first, so the restore
+    /// can itself be undone.
+    pub fn restore_entry_revision(&mut self, entry_uuid: Uuid, index: usize) -> Result<()> {
+        let (max_items, max_size) = self.history_limits();
+        let entry = self.get_entry_mut(entry_uuid)?;
+
+        let revision = entry.get_history(index).cloned().ok_or(anyhow!(
+            "Entry {} has no revision {}",
+            entry_uuid,
+            index
+        ))?;
+
+        entry.push_history(max_items, max_size);
+        entry.restore_revision(&revision);
+        entry.touch_modified();
+
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn get_group_mut(&mut self, group_uuid: Uuid) -> Result<&mut RxGroup> {
+        self.all_groups
+            .get_mut(&group_uuid)
+            .map(Rc::make_mut)
+            .ok_or(anyhow!("Could not find group {}", group_uuid))
+    }
+
+    /// Check every entry password against the local breached password
+    /// list, and flag the ones that are in it. Returns the number of
+    /// flagged entries.
+    pub fn check_pwned(&mut self, checker: &RxPwnedChecker) -> Result<usize> {
+        let mut pwned_count = 0;
+
+        for entry in self.all_entries.values_mut() {
+            if flag_pwned(entry, checker)? {
+                pwned_count += 1;
+            }
+        }
+
+        Ok(pwned_count)
+    }
+
+    /// Check a single entry password against the local breached
+    /// password list, e.g. after it was edited. Returns whether it is
+    /// in the list.
+    pub fn check_pwned_entry(
+        &mut self,
+        entry_uuid: Uuid,
+        checker: &RxPwnedChecker,
+    ) -> Result<bool> {
+        let entry = self
+            .all_entries
+            .get_mut(&entry_uuid)
+            .ok_or(anyhow!("Could not find entry {}", entry_uuid))?;
+
+        flag_pwned(entry, checker)
+    }
+
+    /// The recycle bin group, if it is turned on and exists. KeePass
+    /// turns it on unless the database says otherwise.
+    pub fn recycle_bin(&self) -> Option<Rc<RxGroup>> {
+        if self.meta.recyclebin_enabled == Some(false) {
+            return None;
+        }
+
+        self.metadata
+            .recycle_bin_uuid
+            .and_then(|bin_uuid| self.get_group(bin_uuid))
+    }
+
+    /// Whether the group is the ancestor group, or somewhere inside
+    /// it.
+    pub fn is_within_group(&self, group_uuid: Uuid, ancestor_uuid: Uuid) -> bool {
+        let mut current = Some(group_uuid);
+
+        // No path up to the root is longer than the number of groups,
+        // so this also stops if the parents ever form a cycle.
+        for _ in 0..=self.all_groups.len() {
+            let Some(uuid) = current else {
+                break;
+            };
+
+            if uuid == ancestor_uuid {
+                return true;
+            }
+
+            current = self.all_groups.get(&uuid).and_then(|group| group.parent);
+        }
+
+        false
+    }
+
+    /// Whether the group is the recycle bin, or somewhere inside it.
+    pub fn is_in_recycle_bin(&self, group_uuid: Uuid) -> bool {
+        self.recycle_bin()
+            .is_some_and(|bin| self.is_within_group(group_uuid, bin.uuid))
+    }
+
+    /// Create a new, empty entry in the given group. Returns the UUID
+    /// of the new entry.
+    pub fn create_entry(&mut self, group_uuid: Uuid, title: SecStr) -> Result<Uuid> {
+        if !self.all_groups.contains_key(&group_uuid) {
+            return Err(anyhow!("Could not find group {}", group_uuid));
+        }
+
+        let mut entry = Entry::new();
+        entry
+            .fields
+            .insert("Title".to_string(), Value::Protected(title));
+
+        self.add_entry(group_uuid, entry)
+    }
+
+    /// Add a fully built KeePass entry to the given group. Returns the
+    /// UUID of the entry.
+    fn add_entry(&mut self, group_uuid: Uuid, entry: Entry) -> Result<Uuid> {
+        let rx_entry = RxEntry::new(&self.master_key, entry, group_uuid, None);
+        let entry_uuid = rx_entry.uuid;
+
+        self.get_group_mut(group_uuid)?.entries.push(entry_uuid);
+        self.all_entries.insert(entry_uuid, Rc::new(rx_entry));
+
+        self.dirty = true;
+        Ok(entry_uuid)
+    }
+
+    /// Create an entry for each one-time password, with an otp field
+    /// like KeePassXC uses. The issuer becomes the title. Returns the
+    /// UUIDs of the new entries.
+    pub fn create_otp_entries(
+        &mut self,
+        group_uuid: Uuid,
+        otps: &[RxMigratedOtp],
+    ) -> Result<Vec<Uuid>> {
+        if !self.all_groups.contains_key(&group_uuid) {
+            return Err(anyhow!("Could not find group {}", group_uuid));
+        }
+
+        otps.iter()
+            .map(|otp| {
+                let title = match otp.issuer.is_empty() {
+                    true => &otp.account,
+                    false => &otp.issuer,
+                };
+
+                let mut entry = Entry::new();
+                entry
+                    .fields
+                    .insert("Title".to_string(), Value::Unprotected(title.clone()));
+                entry.fields.insert(
+                    "UserName".to_string(),
+                    Value::Unprotected(otp.account.clone()),
+                );
+                entry.fields.insert(
+                    "otp".to_string(),
+                    Value::Protected(SecStr::from(otp.uri.as_str())),
+                );
+
+                self.add_entry(group_uuid, entry)
+            })
+            .collect()
+    }
+
+    /// Find the group at the end of a path of group names below the
+    /// given group, creating the groups that do not exist yet.
+    fn find_or_create_group_path(
+        &mut self,
+        parent_uuid: Uuid,
+        path: &[String],
+    ) -> Result<Uuid> {
+        let mut group_uuid = parent_uuid;
+
+        for name in path {
+            let existing = self
+                .get_group(group_uuid)
+                .ok_or(anyhow!("Could not find group {}", group_uuid))?
+                .subgroups
+                .iter()
+                .copied()
+                .find(|uuid| {
+                    self.get_group(*uuid)
+                        .is_some_and(|group| group.name == *name)
+                });
+
+            group_uuid = match existing {
+                Some(uuid) => uuid,
+                None => self.create_group(group_uuid, name)?,
+            };
+        }
+
+        Ok(group_uuid)
+    }
+
+    /// Add entries read from another password manager's export to the
+    /// given group. The groups of the entries are created below it, or
+    /// reused if a group of the same name is already there. Returns
+    /// the UUIDs of the new entries.
+    pub fn import_entries(
+        &mut self,
+        group_uuid: Uuid,
+        entries: &[RxImportedEntry],
+    ) -> Result<Vec<Uuid>> {
+        if !self.all_groups.contains_key(&group_uuid) {
+            return Err(anyhow!("Could not find group {}", group_uuid));
+        }
+
+        entries
+            .iter()
+            .map(|entry| {
+                let parent = self.find_or_create_group_path(group_uuid, &entry.group_path)?;
+                let mut kp_entry = entry.to_keepass();
+
+                let Some(template) = entry.template else {
+                    return self.add_entry(parent, kp_entry);
+                };
+
+                let template_uuid = self.find_or_create_template(template)?;
+                kp_entry.fields.insert(
+                    TEMPLATE_FIELD_NAME.to_string(),
+                    Value::Unprotected(template_uuid.to_string()),
+                );
+
+                let entry_uuid = self.add_entry(parent, kp_entry)?;
+                let rx_template = self
+                    .templates
+                    .get_mut(&template_uuid)
+                    .ok_or(anyhow!("No template {}", template_uuid))?;
+                Rc::make_mut(rx_template).entry_uuids.push(entry_uuid);
+
+                Ok(entry_uuid)
+            })
+            .collect()
+    }
+
+    /// The group that holds template entries. Created (like KeePassDX
+    /// does) if the database has none yet.
+    fn find_or_create_templates_group(&mut self) -> Result<Uuid> {
+        let existing = self
+            .meta
+            .entry_templates_group
+            .filter(|uuid| self.all_groups.contains_key(uuid));
+
+        match existing {
+            Some(group_uuid) => Ok(group_uuid),
+            None => {
+                let group_uuid = self.create_group(self.root, "Templates")?;
+                self.meta.entry_templates_group = Some(group_uuid);
+                Ok(group_uuid)
+            }
+        }
+    }
+
+    /// The template entry with the template's name, creating it in
+    /// the templates group if the database does not have one. The
+    /// fields are described the way KeePassDX does it, so KeePassDX
+    /// can show entries made from the template too.
+    fn find_or_create_template(&mut self, template: &RxImportTemplate) -> Result<Uuid> {
+        let templates_group = self.find_or_create_templates_group()?;
+        let is_template = |entry: &RxEntry| {
+            entry
+                .title()
+                .and_then(|title| title.value())
+                .is_some_and(|title| title.as_str() == template.name)
+        };
+
+        let existing = self
+            .templates
+            .keys()
+            .chain(
+                self.get_group(templates_group)
+                    .iter()
+                    .flat_map(|group| &group.entries),
+            )
+            .copied()
+            .find(|uuid| {
+                self.get_entry(*uuid)
+                    .is_some_and(|entry| is_template(&entry))
+            });
+
+        let template_uuid = match existing {
+            Some(template_uuid) => template_uuid,
+            None => {
+                let mut entry = Entry::new();
+                entry.fields.insert(
+                    "Title".to_string(),
+                    Value::Unprotected(template.name.to_string()),
+                );
+                entry.fields.insert(
+                    "_etm_template".to_string(),
+                    Value::Unprotected("1".to_string()),
+                );
+
+                for (position, (name, protected)) in template.fields.iter().enumerate() {
+                    let field_type = match protected {
+                        true => "Protected Inline",
+                        false => "Inline",
+                    };
+
+                    let mut describe = |prefix: &str, value: String| {
+                        entry
+                            .fields
+                            .insert(format!("{}{}", prefix, name), Value::Unprotected(value));
+                    };
+
+                    describe("_etm_title_", name.to_string());
+                    describe("_etm_type_", field_type.to_string());
+                    describe("_etm_position_", position.to_string());
+                }
+
+                self.add_entry(templates_group, entry)?
+            }
+        };
+
+        let template_entry = self
+            .get_entry(template_uuid)
+            .ok_or(anyhow!("No template entry {}", template_uuid))?;
+        self.templates.entry(template_uuid).or_insert_with(|| {
+            Rc::new(RxTemplate {
+                uuid: template_uuid,
+                name: template.name.to_string(),
+                icon: template_entry.icon.clone(),
+                entry_uuids: vec![],
+            })
+        });
+
+        Ok(template_uuid)
+    }
+
+    /// Create a new, empty group inside the given group. Returns the
+    /// UUID of the new group.
+    pub fn create_group(&mut self, parent_uuid: Uuid, name: &str) -> Result<Uuid> {
+        if !self.all_groups.contains_key(&parent_uuid) {
+            return Err(anyhow!("Could not find group {}", parent_uuid));
+        }
+
+        let mut group = Group::new(name);
+        let rx_group = RxGroup::new(&mut group, vec![], vec![], Some(parent_uuid));
+        let group_uuid = rx_group.uuid;
+
+        self.get_group_mut(parent_uuid)?.subgroups.push(group_uuid);
+        self.all_groups.insert(group_uuid, Rc::new(rx_group));
+
+        self.dirty = true;
+        Ok(group_uuid)
+    }
+
+    /// Put an entry into another group, keeping the indexes of both
+    /// groups consistent.
+    fn relocate_entry(&mut self, entry_uuid: Uuid, new_parent: Uuid) -> Result<()> {
+        let old_parent = self.get_entry_mut(entry_uuid)?.parent_group;
+        self.get_group_mut(old_parent)?
+            .entries
+            .retain(|uuid| *uuid != entry_uuid);
+        self.get_group_mut(new_parent)?.entries.push(entry_uuid);
+
+        let entry = self.get_entry_mut(entry_uuid)?;
+        entry.parent_group = new_parent;
+        entry.times.location_changed = Some(RxTimes::now());
+        Ok(())
+    }
+
+    /// Put a group into another group, keeping the indexes of both
+    /// groups consistent.
+    fn relocate_group(&mut self, group_uuid: Uuid, new_parent: Uuid) -> Result<()> {
+        let old_parent = self
+            .get_group_mut(group_uuid)?
+            .parent
+            .ok_or(anyhow!("Cannot move the root group"))?;
+
+        self.get_group_mut(old_parent)?
+            .subgroups
+            .retain(|uuid| *uuid != group_uuid);
+        self.get_group_mut(new_parent)?.subgroups.push(group_uuid);
+
+        let group = self.get_group_mut(group_uuid)?;
+        group.parent = Some(new_parent);
+        group.times.location_changed = Some(RxTimes::now());
+        Ok(())
+    }
+
+    /// Move an entry into another group.
+    pub fn move_entry(&mut self, entry_uuid: Uuid, new_parent: Uuid) -> Result<()> {
+        let old_parent = self
+            .get_entry(entry_uuid)
+            .map(|entry| entry.parent_group)
+            .ok_or(anyhow!("Could not find entry {}", entry_uuid))?;
+
+        if !self.all_groups.contains_key(&new_parent) {
+            return Err(anyhow!("Could not find group {}", new_parent));
+        }
+
+        if old_parent != new_parent {
+            self.relocate_entry(entry_uuid, new_parent)?;
+            self.dirty = true;
+        }
+
+        Ok(())
+    }
+
+    /// Move a group (and everything in it) into another group. A
+    /// group cannot be moved into itself or one of its own subgroups.
+    pub fn move_group(&mut self, group_uuid: Uuid, new_parent: Uuid) -> Result<()> {
+        let old_parent = self
+            .get_group(group_uuid)
+            .ok_or(anyhow!("Could not find group {}", group_uuid))?
+            .parent
+            .ok_or(anyhow!("Cannot move the root group"))?;
+
+        if !self.all_groups.contains_key(&new_parent) {
+            return Err(anyhow!("Could not find group {}", new_parent));
+        }
+
+        if self.is_within_group(new_parent, group_uuid) {
+            return Err(anyhow!("Cannot move a group into itself or its subgroups"));
+        }
+
+        if old_parent != new_parent {
+            self.relocate_group(group_uuid, new_parent)?;
+            self.dirty = true;
+        }
+
+        Ok(())
+    }
+
+    fn record_deletion(&mut self, uuid: Uuid) {
+        self.deleted_objects.objects.push(DeletedObject {
+            uuid: uuid,
+            deletion_time: Times::now(),
+        });
+    }
+
+    fn remove_entry(&mut self, entry_uuid: Uuid) -> Result<()> {
+        let entry = self
+            .all_entries
+            .shift_remove(&entry_uuid)
+            .ok_or(anyhow!("Could not find entry {}", entry_uuid))?;
+
+        if let Some(parent) = self.all_groups.get_mut(&entry.parent_group) {
+            Rc::make_mut(parent)
+                .entries
+                .retain(|uuid| *uuid != entry_uuid);
+        }
+
+        if let Some(template) = entry
+            .template_uuid
+            .and_then(|template_uuid| self.templates.get_mut(&template_uuid))
+        {
+            Rc::make_mut(template)
+                .entry_uuids
+                .retain(|uuid| *uuid != entry_uuid);
+        }
+
+        self.record_deletion(entry_uuid);
+        Ok(())
+    }
+
+    fn remove_group(&mut self, group_uuid: Uuid) -> Result<()> {
+        let group = self
+            .get_group(group_uuid)
+            .ok_or(anyhow!("Could not find group {}", group_uuid))?;
+
+        for entry_uuid in group.entries.iter() {
+            self.remove_entry(*entry_uuid)?;
+        }
+
+        for subgroup_uuid in group.subgroups.iter() {
+            self.remove_group(*subgroup_uuid)?;
+        }
+
+        if let Some(parent) = group.parent.and_then(|p| self.all_groups.get_mut(&p)) {
+            Rc::make_mut(parent)
+                .subgroups
+                .retain(|uuid| *uuid != group_uuid);
+        }
+
+        self.all_groups.shift_remove(&group_uuid);
+        self.record_deletion(group_uuid);
+        Ok(())
+    }
+
+    /// Delete an entry. If there is a recycle bin, the entry is moved
+    /// there. Entries already in the recycle bin are deleted
+    /// permanently.
+    pub fn delete_entry(&mut self, entry_uuid: Uuid) -> Result<()> {
+        let parent = self
+            .get_entry(entry_uuid)
+            .map(|entry| entry.parent_group)
+            .ok_or(anyhow!("Could not find entry {}", entry_uuid))?;
+
+        match self.recycle_bin() {
+            Some(bin) if !self.is_in_recycle_bin(parent) => {
+                self.relocate_entry(entry_uuid, bin.uuid)?
+            }
+            _ => self.remove_entry(entry_uuid)?,
+        }
+
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Delete a group and everything in it. If there is a recycle
+    /// bin, the group is moved there. Groups already in the recycle
+    /// bin, and groups that hold the recycle bin (or are the recycle
+    /// bin), are deleted permanently, like KeePassXC does.
+    pub fn delete_group(&mut self, group_uuid: Uuid) -> Result<()> {
+        if group_uuid == self.root {
+            return Err(anyhow!("Cannot delete the root group"));
+        }
+
+        match self.recycle_bin() {
+            Some(bin)
+                if !self.is_in_recycle_bin(group_uuid)
+                    && !self.is_within_group(bin.uuid, group_uuid) =>
+            {
+                self.relocate_group(group_uuid, bin.uuid)?
+            }
+            _ => {
+                self.remove_group(group_uuid)?;
+
+                // The recycle bin was deleted along with the group.
+                let bin_removed = self
+                    .metadata
+                    .recycle_bin_uuid
+                    .is_some_and(|bin_uuid| !self.all_groups.contains_key(&bin_uuid));
+
+                if bin_removed {
+                    self.metadata.recycle_bin_uuid = None;
+                    self.meta.recyclebin_uuid = None;
+                }
+            }
+        }
+
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn field_protection(&self) -> RxFieldProtection {
+        self.meta
+            .memory_protection
+            .as_ref()
+            .map(|mp| RxFieldProtection {
+                title: mp.protect_title,
+                username: mp.protect_username,
+                password: mp.protect_password,
+                url: mp.protect_url,
+                notes: mp.protect_notes,
+            })
+            .unwrap_or_default()
+    }
+
+    fn group_to_keepass(
+        &self,
+        group: &RxGroup,
+        protection: &RxFieldProtection,
+    ) -> Result<Group> {
+        let mut kp_group = group.to_keepass();
+
+        for entry_uuid in group.entries.iter() {
+            let entry = self
+                .all_entries
+                .get(entry_uuid)
+                .ok_or(anyhow!("Group refers to missing entry {}", entry_uuid))?;
+            kp_group.add_child(Node::Entry(entry.to_keepass(protection)?));
+        }
+
+        for subgroup_uuid in group.subgroups.iter() {
+            let subgroup = self.all_groups.get(subgroup_uuid).ok_or(anyhow!(
+                "Group refers to missing subgroup {}",
+                subgroup_uuid
+            ))?;
+            kp_group.add_child(Node::Group(self.group_to_keepass(subgroup, protection)?));
+        }
+
+        Ok(kp_group)
+    }
+
+    /// Convert back into a KeePass database, so that it can be saved.
+    /// Only KDBX4 can be written, so older KDBX databases are
+    /// upgraded. KDB (KeePass 1) databases cannot be saved.
+    pub fn to_keepass(&self) -> Result<Database> {
+        let mut config = self.config.clone();
+        config.version = match config.version {
+            DatabaseVersion::KDB4(minor) => DatabaseVersion::KDB4(minor),
+            DatabaseVersion::KDB(_) => {
+                return Err(anyhow!("KeePass 1 (KDB) databases cannot be saved"));
+            }
+            _ => DatabaseVersion::KDB4(0),
+        };
+
+        let protection = self.field_protection();
+        let mut db = Database::new(config);
+        db.meta = self.meta.clone();
+        db.header_attachments = self.header_attachments.clone();
+        db.deleted_objects = self.deleted_objects.clone();
+        db.root = self.group_to_keepass(self.root_group(), &protection)?;
+
+        Ok(db)
+    }
+
     pub fn get_totp(&self, entry_uuid: &str) -> Result<RxTotp> {
         let entry_uuid = Uuid::from_str(entry_uuid)?;
         let entry = self
@@ -224,14 +962,35 @@ impl RxDatabase {
 
         Ok(entry.totp()?)
     }
+
+    /// Move an HOTP entry on to its next code, once the current one
+    /// has been used. Returns the new code.
+    pub fn advance_hotp_counter(&mut self, entry_uuid: Uuid) -> Result<RxTotp> {
+        let entry = self.get_entry_mut(entry_uuid)?;
+
+        entry.advance_hotp_counter()?;
+        entry.touch_modified();
+        self.dirty = true;
+
+        Ok(entry.totp()?)
+    }
+
+    /// Decrypt a single attachment of an entry on demand.
+    pub fn get_attachment(&self, entry_uuid: Uuid, name: &str) -> Result<SecureVec<u8>> {
+        let entry = self
+            .all_entries
+            .get(&entry_uuid)
+            .ok_or(anyhow!("Could not find entry for attachment"))?;
+
+        entry.attachment_data(name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use keyring::set_default_credential_builder;
-
     use super::*;
-    use crate::rx::{RxCustomFields, RxValue, TEMPLATE_FIELD_NAME};
+    use crate::rx::test_support::{keepass_db, load_db};
+    use crate::rx::{RxCustomFields, RxOtpError, RxValue, TEMPLATE_FIELD_NAME};
 
     #[test]
     fn test_extract_string() {
@@ -243,8 +1002,6 @@ mod tests {
 
     #[test]
     fn loads_recursively() {
-        set_default_credential_builder(keyring::mock::default_credential_builder());
-        let mut db = keepass::db::Database::new(Default::default());
         let mut group = keepass::db::Group::new("groupname");
         let mut subgroup = keepass::db::Group::new("subgroupname");
 
@@ -271,9 +1028,7 @@ mod tests {
         group.add_child(keepass::db::Node::Entry(entry));
         group.add_child(keepass::db::Node::Group(subgroup));
 
-        db.root = group;
-
-        let rx_db = RxDatabase::new(Zeroizing::new(ZeroableDatabase(db)));
+        let rx_db = load_db(keepass_db(group));
         let rx_root = rx_db.root_group();
 
         assert_eq!(rx_db.all_groups_iter().count(), 2);
@@ -293,6 +1048,842 @@ mod tests {
         assert_eq!(rx_subgroup.entries, vec![sub_entry_id]);
     }
 
+    #[test]
+    fn loads_attachments() {
+        let mut group = keepass::db::Group::new("groupname");
+
+        let mut entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+
+        entry.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("entry with attachment".to_string()),
+        );
+
+        entry.fields.insert(
+            "readme.txt".to_string(),
+            keepass::db::Value::Bytes(b"attached text".to_vec()),
+        );
+
+        group.add_child(keepass::db::Node::Entry(entry));
+
+        let rx_db = load_db(keepass_db(group));
+        let rx_entry = rx_db.get_entry(entry_id).expect("Could not find entry");
+
+        assert_eq!(rx_entry.attachments().count(), 1);
+        assert_eq!(rx_entry.custom_fields.iter().count(), 0);
+
+        let attachment = rx_entry
+            .get_attachment("readme.txt")
+            .expect("Could not find attachment");
+
+        assert_eq!(attachment.size, 13);
+        assert_eq!(attachment.mime_type, "text/plain");
+
+        let data = rx_db
+            .get_attachment(entry_id, "readme.txt")
+            .expect("Could not decrypt attachment");
+
+        assert_eq!(&*data, b"attached text");
+        assert!(rx_db.get_attachment(entry_id, "missing.txt").is_err());
+    }
+
+    #[test]
+    fn updates_entry_fields() {
+        let mut group = keepass::db::Group::new("groupname");
+
+        let mut entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+
+        entry.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("old title".to_string()),
+        );
+
+        entry.fields.insert(
+            "URL".to_string(),
+            keepass::db::Value::Unprotected("https://example.com".to_string()),
+        );
+
+        group.add_child(keepass::db::Node::Entry(entry));
+
+        let mut rx_db = load_db(keepass_db(group));
+        assert!(!rx_db.is_dirty());
+
+        rx_db
+            .update_entry(
+                entry_id,
+                vec![
+                    (RxFieldName::Title, Some(SecStr::from("new title"))),
+                    (RxFieldName::Password, Some(SecStr::from("hunter2"))),
+                    (RxFieldName::Url, None),
+                ],
+            )
+            .expect("Could not update entry");
+
+        assert!(rx_db.is_dirty());
+
+        let rx_entry = rx_db.get_entry(entry_id).expect("Could not find entry");
+        let title = rx_entry.title().and_then(|t| t.value());
+        let password = rx_entry.password().and_then(|p| p.value());
+
+        assert_eq!(title.as_deref().map(|t| t.as_str()), Some("new title"));
+        assert_eq!(password.as_deref().map(|p| p.as_str()), Some("hunter2"));
+        assert!(rx_entry.url().is_none());
+    }
+
+    #[test]
+    fn converts_back_to_keepass() {
+        let mut group = keepass::db::Group::new("groupname");
+        let mut subgroup = keepass::db::Group::new("subgroupname");
+
+        let mut entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+
+        entry.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("title".to_string()),
+        );
+
+        entry.fields.insert(
+            "KPEX_PASSKEY_USERNAME".to_string(),
+            keepass::db::Value::Unprotected("hidden but kept".to_string()),
+        );
+
+        entry.custom_data.items.insert(
+            "KPXC_DECRYPTION_TIME".to_string(),
+            keepass::db::CustomDataItem {
+                value: Some(keepass::db::Value::Protected(SecStr::from("plugin secret"))),
+                last_modification_time: None,
+            },
+        );
+
+        subgroup.notes = Some("group notes".to_string());
+        subgroup.custom_data.items.insert(
+            "GroupSetting".to_string(),
+            keepass::db::CustomDataItem {
+                value: Some(keepass::db::Value::Unprotected("on".to_string())),
+                last_modification_time: None,
+            },
+        );
+
+        entry.tags.push("tag".to_string());
+        subgroup.add_child(keepass::db::Node::Entry(entry));
+        group.add_child(keepass::db::Node::Group(subgroup));
+
+        let mut rx_db = load_db(keepass_db(group));
+        rx_db
+            .update_entry(
+                entry_id,
+                vec![(RxFieldName::Password, Some(SecStr::from("secret")))],
+            )
+            .expect("Could not update entry");
+
+        // Nothing secret is kept in plain memory.
+        let rx_entry = rx_db.get_entry(entry_id).unwrap();
+        assert!(rx_entry.remainder.custom_data.items.is_empty());
+        let rx_subgroup = rx_db.get_group(rx_entry.parent_group).unwrap();
+        assert!(rx_subgroup.remainder.notes.is_none());
+        assert!(rx_subgroup.remainder.custom_data.items.is_empty());
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        assert_eq!(kp_db.root.name, "groupname");
+
+        let kp_subgroup = kp_db.root.groups().into_iter().next().expect("No subgroup");
+        assert_eq!(kp_subgroup.name, "subgroupname");
+        assert_eq!(kp_subgroup.notes.as_deref(), Some("group notes"));
+        assert!(kp_subgroup.custom_data.items.contains_key("GroupSetting"));
+
+        let kp_entry = kp_subgroup.entries().into_iter().next().expect("No entry");
+
+        assert_eq!(kp_entry.uuid, entry_id);
+        assert_eq!(kp_entry.get_title(), Some("title"));
+        assert_eq!(kp_entry.get_password(), Some("secret"));
+        assert_eq!(
+            kp_entry.get("KPEX_PASSKEY_USERNAME"),
+            Some("hidden but kept")
+        );
+        assert_eq!(kp_entry.tags, vec!["tag".to_string()]);
+        assert!(matches!(
+            kp_entry.custom_data.items["KPXC_DECRYPTION_TIME"].value,
+            Some(keepass::db::Value::Protected(_))
+        ));
+        assert!(!kp_entry.fields.contains_key("KPXC_DECRYPTION_TIME"));
+        assert!(matches!(
+            kp_entry.fields.get("Password"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+    }
+
+    #[test]
+    fn deletes_via_recycle_bin() {
+        let mut root = keepass::db::Group::new("root");
+        let bin = keepass::db::Group::new("Recycle Bin");
+        let bin_id = bin.uuid;
+
+        root.add_child(keepass::db::Node::Group(bin));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_uuid = Some(bin_id);
+
+        let mut rx_db = load_db(db);
+        let root_id = rx_db.root_group().uuid;
+
+        let group_id = rx_db
+            .create_group(root_id, "new group")
+            .expect("Could not create group");
+        let entry_id = rx_db
+            .create_entry(group_id, SecStr::from("new entry"))
+            .expect("Could not create entry");
+
+        assert_eq!(rx_db.get_group(group_id).unwrap().parent, Some(root_id));
+        assert_eq!(rx_db.get_group(group_id).unwrap().entries, vec![entry_id]);
+
+        // First delete goes to the recycle bin.
+        rx_db
+            .delete_entry(entry_id)
+            .expect("Could not delete entry");
+        assert_eq!(rx_db.get_entry(entry_id).unwrap().parent_group, bin_id);
+        assert!(rx_db.get_group(group_id).unwrap().entries.is_empty());
+        assert_eq!(rx_db.get_group(bin_id).unwrap().entries, vec![entry_id]);
+
+        // Second delete is permanent.
+        rx_db
+            .delete_entry(entry_id)
+            .expect("Could not delete entry");
+        assert!(rx_db.get_entry(entry_id).is_none());
+        assert!(rx_db.get_group(bin_id).unwrap().entries.is_empty());
+
+        rx_db
+            .delete_group(group_id)
+            .expect("Could not delete group");
+        assert_eq!(rx_db.get_group(group_id).unwrap().parent, Some(bin_id));
+        assert!(rx_db.is_in_recycle_bin(group_id));
+
+        rx_db
+            .delete_group(group_id)
+            .expect("Could not delete group");
+        assert!(rx_db.get_group(group_id).is_none());
+        assert!(rx_db.delete_group(root_id).is_err());
+        assert!(rx_db.is_dirty());
+    }
+
+    #[test]
+    fn deletes_groups_holding_the_recycle_bin() {
+        let mut root = keepass::db::Group::new("root");
+        let mut outer = keepass::db::Group::new("outer");
+        let bin = keepass::db::Group::new("Recycle Bin");
+        let outer_id = outer.uuid;
+        let bin_id = bin.uuid;
+
+        outer.add_child(keepass::db::Node::Group(bin));
+        root.add_child(keepass::db::Node::Group(outer));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_uuid = Some(bin_id);
+
+        let mut rx_db = load_db(db);
+        let root_id = rx_db.root_group().uuid;
+
+        // The group cannot go into the recycle bin inside it.
+        rx_db
+            .delete_group(outer_id)
+            .expect("Could not delete group");
+        assert!(rx_db.get_group(outer_id).is_none());
+        assert!(rx_db.get_group(bin_id).is_none());
+        assert!(rx_db.recycle_bin().is_none());
+        assert!(rx_db.meta.recyclebin_uuid.is_none());
+        assert!(rx_db.root_group().subgroups.is_empty());
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        assert!(kp_db.root.groups().is_empty());
+
+        // Parents that loop around do not hang the ancestor check.
+        let a_id = rx_db.create_group(root_id, "a").unwrap();
+        let b_id = rx_db.create_group(a_id, "b").unwrap();
+        rx_db.get_group_mut(a_id).unwrap().parent = Some(b_id);
+        assert!(!rx_db.is_within_group(a_id, root_id));
+    }
+
+    #[test]
+    fn deletes_permanently_without_recycle_bin() {
+        let mut root = keepass::db::Group::new("root");
+        let bin = keepass::db::Group::new("Recycle Bin");
+        let bin_id = bin.uuid;
+
+        root.add_child(keepass::db::Node::Group(bin));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_uuid = Some(bin_id);
+        db.meta.recyclebin_enabled = Some(false);
+
+        let mut rx_db = load_db(db);
+        let root_id = rx_db.root_group().uuid;
+        assert!(rx_db.recycle_bin().is_none());
+
+        let entry_id = rx_db.create_entry(root_id, SecStr::from("entry")).unwrap();
+        let group_id = rx_db.create_group(root_id, "group").unwrap();
+
+        rx_db
+            .delete_entry(entry_id)
+            .expect("Could not delete entry");
+        rx_db
+            .delete_group(group_id)
+            .expect("Could not delete group");
+        assert!(rx_db.get_entry(entry_id).is_none());
+        assert!(rx_db.get_group(group_id).is_none());
+        assert!(rx_db.get_group(bin_id).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn moves_entries_and_groups() {
+        let mut rx_db = load_db(keepass_db(keepass::db::Group::new("root")));
+        let root_id = rx_db.root_group().uuid;
+
+        let outer_id = rx_db.create_group(root_id, "outer").unwrap();
+        let inner_id = rx_db.create_group(outer_id, "inner").unwrap();
+        let entry_id = rx_db.create_entry(root_id, SecStr::from("entry")).unwrap();
+
+        rx_db
+            .move_entry(entry_id, inner_id)
+            .expect("Could not move entry");
+        assert_eq!(rx_db.get_entry(entry_id).unwrap().parent_group, inner_id);
+        assert_eq!(rx_db.get_group(inner_id).unwrap().entries, vec![entry_id]);
+        assert!(!rx_db.root_group().entries.contains(&entry_id));
+        assert!(
+            rx_db
+                .get_entry(entry_id)
+                .unwrap()
+                .times
+                .location_changed
+                .is_some()
+        );
+
+        // Cannot move a group into itself or below itself.
+        assert!(rx_db.move_group(outer_id, outer_id).is_err());
+        assert!(rx_db.move_group(outer_id, inner_id).is_err());
+        assert!(rx_db.move_group(root_id, inner_id).is_err());
+
+        rx_db
+            .move_group(inner_id, root_id)
+            .expect("Could not move group");
+        assert_eq!(rx_db.get_group(inner_id).unwrap().parent, Some(root_id));
+        assert!(rx_db.get_group(outer_id).unwrap().subgroups.is_empty());
+        assert!(rx_db.root_group().subgroups.contains(&inner_id));
+    }
+
+    #[test]
+    fn keeps_and_restores_history() {
+        let mut root = keepass::db::Group::new("root");
+
+        let mut old_entry = keepass::db::Entry::new();
+        old_entry.fields.insert(
+            "Password".to_string(),
+            keepass::db::Value::Protected(SecStr::from("oldest")),
+        );
+
+        let mut entry = old_entry.clone();
+        entry.fields.insert(
+            "Password".to_string(),
+            keepass::db::Value::Protected(SecStr::from("current")),
+        );
+
+        let mut history = keepass::db::History::default();
+        history.add_entry(old_entry);
+        entry.history = Some(history);
+
+        let entry_id = entry.uuid;
+        root.add_child(keepass::db::Node::Entry(entry));
+
+        let mut rx_db = load_db(keepass_db(root));
+        let password = |rx_db: &RxDatabase, index: Option<usize>| {
+            let entry = rx_db.get_entry(entry_id).unwrap();
+            let entry = match index {
+                Some(index) => entry.get_history(index).unwrap().clone(),
+                None => (*entry).clone(),
+            };
+            entry
+                .password()
+                .and_then(|p| p.value())
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(rx_db.get_entry(entry_id).unwrap().history().count(), 1);
+        assert_eq!(password(&rx_db, Some(0)), "oldest");
+
+        rx_db
+            .update_entry(
+                entry_id,
+                vec![(RxFieldName::Password, Some(SecStr::from("newest")))],
+            )
+            .expect("Could not update entry");
+
+        assert_eq!(rx_db.get_entry(entry_id).unwrap().history().count(), 2);
+        assert_eq!(password(&rx_db, Some(1)), "current");
+        assert_eq!(password(&rx_db, None), "newest");
+
+        rx_db
+            .restore_entry_revision(entry_id, 0)
+            .expect("Could not restore revision");
+
+        assert_eq!(password(&rx_db, None), "oldest");
+        assert_eq!(password(&rx_db, Some(2)), "newest");
+        assert!(rx_db.restore_entry_revision(entry_id, 10).is_err());
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        let kp_entry = kp_db.root.entries().into_iter().next().expect("No entry");
+        let kp_history = kp_entry.history.as_ref().expect("History was lost");
+        assert_eq!(kp_history.get_entries().len(), 3);
+    }
+
+    #[test]
+    fn limits_history() {
+        let mut root = keepass::db::Group::new("root");
+        let entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+        root.add_child(keepass::db::Node::Entry(entry));
+
+        let mut db = keepass_db(root);
+        db.meta.history_max_items = Some(2);
+        db.meta.history_max_size = Some(-1);
+
+        let mut rx_db = load_db(db);
+        let update = |rx_db: &mut RxDatabase, password: &str| {
+            rx_db
+                .update_entry(
+                    entry_id,
+                    vec![(RxFieldName::Password, Some(SecStr::from(password)))],
+                )
+                .expect("Could not update entry");
+        };
+
+        for password in ["one", "two", "three", "four"] {
+            update(&mut rx_db, password);
+        }
+
+        // Only the item limit applies; -1 means no size limit.
+        assert_eq!(rx_db.get_entry(entry_id).unwrap().history().count(), 2);
+
+        // Each revision has a password of at least three bytes, so
+        // only the newest one fits.
+        rx_db.meta.history_max_items = Some(-1);
+        rx_db.meta.history_max_size = Some(5);
+        update(&mut rx_db, "five");
+
+        let entry = rx_db.get_entry(entry_id).unwrap();
+        let history: Vec<_> = entry
+            .history()
+            .map(|old| old.password().and_then(|p| p.value()).unwrap().to_string())
+            .collect();
+        assert_eq!(history, vec!["four"]);
+    }
+
+    #[test]
+    fn reads_saved_searches() {
+        let mut db = keepass_db(keepass::db::Group::new("root"));
+
+        for (key, query) in [
+            ("KPXC_SavedSearch_Work", "group:work"),
+            ("KPXC_SavedSearch_Expired", "is:expired"),
+            ("SomethingElse", "not a search"),
+        ] {
+            db.meta.custom_data.items.insert(
+                key.to_string(),
+                keepass::db::CustomDataItem {
+                    value: Some(keepass::db::Value::Unprotected(query.to_string())),
+                    last_modification_time: None,
+                },
+            );
+        }
+
+        let rx_db = load_db(db);
+
+        assert_eq!(
+            rx_db.saved_searches(),
+            vec![
+                ("Expired".to_string(), "is:expired".to_string()),
+                ("Work".to_string(), "group:work".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rechecks_pwned_password_after_edit() {
+        let dir = std::env::temp_dir().join("keepassrx-tests").join(format!(
+            "rechecks_pwned_password_after_edit-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("Could not create test dir");
+
+        // SHA-1 of "password".
+        let path = dir.join("pwned.txt");
+        std::fs::write(
+            &path,
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n",
+        )
+        .expect("Could not write test file");
+        let checker = RxPwnedChecker::open(&path).expect("Could not open checker");
+
+        let mut rx_db = load_db(keepass_db(keepass::db::Group::new("root")));
+        let root_id = rx_db.root_group().uuid;
+        let entry_id = rx_db.create_entry(root_id, SecStr::from("entry")).unwrap();
+
+        let set_password = |rx_db: &mut RxDatabase, password: &str| {
+            rx_db
+                .update_entry(
+                    entry_id,
+                    vec![(RxFieldName::Password, Some(SecStr::from(password)))],
+                )
+                .expect("Could not update entry");
+        };
+
+        set_password(&mut rx_db, "password");
+        assert_eq!(rx_db.check_pwned(&checker).unwrap(), 1);
+        assert!(rx_db.get_entry(entry_id).unwrap().is_pwned());
+
+        // Editing clears the flag until the entry is checked again.
+        set_password(&mut rx_db, "something else");
+        assert!(!rx_db.get_entry(entry_id).unwrap().is_pwned());
+
+        set_password(&mut rx_db, "password");
+        assert!(rx_db.check_pwned_entry(entry_id, &checker).unwrap());
+        assert!(rx_db.get_entry(entry_id).unwrap().is_pwned());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reads_legacy_totp_fields() {
+        let mut root = keepass::db::Group::new("root");
+
+        let mut entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+        entry.fields.insert(
+            "TOTP Seed".to_string(),
+            keepass::db::Value::Protected(SecStr::from("jbsw y3dp ehpk 3pxp")),
+        );
+        entry.fields.insert(
+            "TOTP Settings".to_string(),
+            keepass::db::Value::Unprotected("30;8".to_string()),
+        );
+
+        let mut steam_entry = keepass::db::Entry::new();
+        let steam_entry_id = steam_entry.uuid;
+        steam_entry.fields.insert(
+            "TOTP Seed".to_string(),
+            keepass::db::Value::Protected(SecStr::from("JBSWY3DPEHPK3PXP")),
+        );
+        steam_entry.fields.insert(
+            "TOTP Settings".to_string(),
+            keepass::db::Value::Unprotected("30;S".to_string()),
+        );
+
+        root.add_child(keepass::db::Node::Entry(entry));
+        root.add_child(keepass::db::Node::Entry(steam_entry));
+
+        let rx_db = load_db(keepass_db(root));
+
+        let entry = rx_db.get_entry(entry_id).expect("No entry");
+        assert!(entry.has_otp());
+        assert!(!entry.has_steam_otp());
+        assert_eq!(entry.custom_fields.iter().count(), 0);
+        assert_eq!(
+            entry.otp_export_uri().expect("No export URI").as_str(),
+            "otpauth://totp/KeePassRX?secret=JBSWY3DPEHPK3PXP&period=30&digits=8"
+        );
+        let totp = rx_db.get_totp(&entry_id.to_string()).expect("No TOTP");
+        assert_eq!(totp.code.len(), 8);
+        assert_eq!(totp.period, 30);
+        assert!(totp.remaining <= 30);
+
+        let steam_entry = rx_db.get_entry(steam_entry_id).expect("No entry");
+        assert!(steam_entry.has_steam_otp());
+        assert_eq!(
+            steam_entry
+                .otp_export_uri()
+                .expect("No export URI")
+                .as_str(),
+            "otpauth://totp/Steam:Steam?secret=JBSWY3DPEHPK3PXP&period=30&digits=5\
+             &issuer=Steam&encoder=steam"
+        );
+        assert_eq!(
+            rx_db
+                .get_totp(&steam_entry_id.to_string())
+                .expect("No TOTP")
+                .code
+                .len(),
+            5
+        );
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        let kp_entry = kp_db
+            .root
+            .entries()
+            .into_iter()
+            .find(|entry| entry.uuid == entry_id)
+            .expect("No entry");
+
+        assert_eq!(kp_entry.get("TOTP Seed"), Some("jbsw y3dp ehpk 3pxp"));
+        assert_eq!(kp_entry.get("TOTP Settings"), Some("30;8"));
+        assert!(kp_entry.get("otp").is_none());
+    }
+
+    #[test]
+    fn reports_unsupported_otps() {
+        let mut root = keepass::db::Group::new("root");
+
+        let mut entry = keepass::db::Entry::new();
+        let entry_id = entry.uuid;
+        entry.fields.insert(
+            "otp".to_string(),
+            keepass::db::Value::Protected(SecStr::from(
+                "otpauth://totp/Bank?secret=JBSWY3DPEHPK3PXP&algorithm=SHA256&digits=8&period=60",
+            )),
+        );
+
+        let mut yandex_entry = keepass::db::Entry::new();
+        let yandex_entry_id = yandex_entry.uuid;
+        yandex_entry.fields.insert(
+            "otp".to_string(),
+            keepass::db::Value::Protected(SecStr::from(
+                "otpauth://yaotp/Yandex?secret=JBSWY3DPEHPK3PXP",
+            )),
+        );
+
+        let mut motp_entry = keepass::db::Entry::new();
+        let motp_entry_id = motp_entry.uuid;
+        motp_entry.fields.insert(
+            "otp".to_string(),
+            keepass::db::Value::Protected(SecStr::from(
+                "motp://Example?secret=1234567890abcdef",
+            )),
+        );
+
+        root.add_child(keepass::db::Node::Entry(entry));
+        root.add_child(keepass::db::Node::Entry(yandex_entry));
+        root.add_child(keepass::db::Node::Entry(motp_entry));
+
+        let rx_db = load_db(keepass_db(root));
+
+        let entry = rx_db.get_entry(entry_id).expect("No entry");
+        let params = entry.totp_params().expect("No TOTP settings");
+        assert_eq!(params.algorithm_name(), "SHA256");
+        assert_eq!(params.digits, 8);
+        assert_eq!(params.period, 60);
+
+        let totp = rx_db.get_totp(&entry_id.to_string()).expect("No TOTP");
+        assert_eq!(totp.code.len(), 8);
+        assert_eq!(totp.period, 60);
+
+        for (entry_id, kind) in [(yandex_entry_id, "Yandex"), (motp_entry_id, "mOTP")] {
+            let entry = rx_db.get_entry(entry_id).expect("No entry");
+            assert!(entry.has_otp());
+
+            let err = entry.totp().err().expect("OTP should not be supported");
+            assert_eq!(
+                err.downcast_ref::<RxOtpError>(),
+                Some(&RxOtpError::UnsupportedType(kind.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn advances_hotp_counter() {
+        let mut root = keepass::db::Group::new("root");
+
+        let mut fields_entry = keepass::db::Entry::new();
+        let fields_entry_id = fields_entry.uuid;
+        fields_entry.fields.insert(
+            "HmacOtp-Secret".to_string(),
+            keepass::db::Value::Protected(SecStr::from("12345678901234567890")),
+        );
+        fields_entry.fields.insert(
+            "HmacOtp-Counter".to_string(),
+            keepass::db::Value::Unprotected("1".to_string()),
+        );
+
+        let mut uri_entry = keepass::db::Entry::new();
+        let uri_entry_id = uri_entry.uuid;
+        uri_entry.fields.insert(
+            "otp".to_string(),
+            keepass::db::Value::Protected(SecStr::from(
+                "otpauth://hotp/VPN?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=2",
+            )),
+        );
+
+        root.add_child(keepass::db::Node::Entry(fields_entry));
+        root.add_child(keepass::db::Node::Entry(uri_entry));
+
+        let mut rx_db = load_db(keepass_db(root));
+        assert!(rx_db.get_entry(fields_entry_id).unwrap().has_hotp());
+        assert!(rx_db.get_entry(uri_entry_id).unwrap().has_otp());
+
+        // Looking at the code does not use it up.
+        let fields_id = fields_entry_id.to_string();
+        assert_eq!(rx_db.get_totp(&fields_id).unwrap().code, "287082");
+        assert_eq!(rx_db.get_totp(&fields_id).unwrap().code, "287082");
+        assert!(!rx_db.is_dirty());
+
+        let next = rx_db
+            .advance_hotp_counter(fields_entry_id)
+            .expect("Could not advance HOTP");
+        assert_eq!(next.code, "359152");
+        assert_eq!(next.counter, Some(2));
+        assert_eq!(rx_db.get_totp(&fields_id).unwrap().code, "359152");
+
+        let next = rx_db
+            .advance_hotp_counter(uri_entry_id)
+            .expect("Could not advance HOTP");
+        assert_eq!(next.code, "969429");
+        assert!(rx_db.is_dirty());
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        let kp_entry = |uuid| {
+            kp_db
+                .root
+                .entries()
+                .into_iter()
+                .find(|entry| entry.uuid == uuid)
+                .expect("No entry")
+        };
+
+        assert_eq!(kp_entry(fields_entry_id).get("HmacOtp-Counter"), Some("2"));
+        assert!(
+            kp_entry(uri_entry_id)
+                .get("otp")
+                .is_some_and(|otp| otp.ends_with("&counter=3"))
+        );
+    }
+
+    #[test]
+    fn creates_otp_entries_from_migration() {
+        let mut rx_db = load_db(keepass_db(keepass::db::Group::new("root")));
+        let root_id = rx_db.root_group().uuid;
+
+        let otps = vec![RxMigratedOtp {
+            uri: Zeroizing::new(
+                "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example"
+                    .to_string(),
+            ),
+            issuer: "Example".to_string(),
+            account: "alice".to_string(),
+        }];
+
+        let created = rx_db
+            .create_otp_entries(root_id, &otps)
+            .expect("Could not create entries");
+        assert_eq!(created.len(), 1);
+        assert!(rx_db.is_dirty());
+
+        let entry = rx_db.get_entry(created[0]).expect("No entry");
+        assert_eq!(entry.parent_group, root_id);
+        assert!(entry.has_otp());
+        assert!(rx_db.get_totp(&created[0].to_string()).is_ok());
+        assert_eq!(
+            entry
+                .title()
+                .and_then(|title| title.value())
+                .unwrap()
+                .as_str(),
+            "Example"
+        );
+
+        assert!(rx_db.create_otp_entries(Uuid::new_v4(), &otps).is_err());
+    }
+
+    #[test]
+    fn imports_entries_into_group_paths() {
+        let mut root = keepass::db::Group::new("root");
+        let work = keepass::db::Group::new("Work");
+        let work_id = work.uuid;
+        root.add_child(keepass::db::Node::Group(work));
+
+        let mut rx_db = load_db(keepass_db(root));
+        let root_id = rx_db.root_group().uuid;
+
+        let mut mail = RxImportedEntry::default();
+        mail.group_path = vec!["Work".to_string(), "Mail".to_string()];
+        mail.set_field("Title", "Mail", false);
+        mail.set_field("Password", "hunter2", false);
+
+        let mut chat = RxImportedEntry::default();
+        chat.group_path = vec!["Work".to_string()];
+        chat.set_field("Title", "Chat", false);
+
+        let created = rx_db
+            .import_entries(root_id, &[mail, chat])
+            .expect("Could not import entries");
+        assert_eq!(created.len(), 2);
+        assert!(rx_db.is_dirty());
+
+        // The existing Work group is reused, Mail is created inside it.
+        let work = rx_db.get_group(work_id).unwrap();
+        assert_eq!(work.subgroups.len(), 1);
+        assert_eq!(rx_db.get_entry(created[1]).unwrap().parent_group, work_id);
+
+        let mail = rx_db.get_entry(created[0]).unwrap();
+        assert_eq!(mail.parent_group, work.subgroups[0]);
+        assert_eq!(rx_db.get_group(mail.parent_group).unwrap().name, "Mail");
+        assert!(
+            mail.password()
+                .and_then(|password| password.value())
+                .is_some_and(|password| password.as_str() == "hunter2")
+        );
+
+        let kp_db = rx_db.to_keepass().expect("Could not convert database");
+        let kp_work = kp_db.root.groups().into_iter().next().expect("No subgroup");
+        let kp_mail = kp_work.groups().into_iter().next().expect("No subgroup");
+        let kp_entry = kp_mail.entries().into_iter().next().expect("No entry");
+        assert_eq!(kp_entry.get_title(), Some("Mail"));
+        assert!(matches!(
+            kp_entry.fields.get("Password"),
+            Some(keepass::db::Value::Protected(_))
+        ));
+
+        assert!(rx_db.import_entries(Uuid::new_v4(), &[]).is_err());
+    }
+
+    #[test]
+    fn imports_templated_entries() {
+        let mut rx_db = load_db(keepass_db(keepass::db::Group::new("root")));
+        let root_id = rx_db.root_group().uuid;
+
+        let card = || {
+            let mut card = RxImportedEntry::default();
+            card.template = Some(&crate::rx::rx_import::CARD_TEMPLATE);
+            card.set_field("Title", "Visa", false);
+            card.set_field("Number", "4111111111111111", true);
+            card
+        };
+
+        let created = rx_db
+            .import_entries(root_id, &[card(), card()])
+            .expect("Could not import entries");
+
+        // Both cards share one template, in a new templates group.
+        let templates: Vec<_> = rx_db.templates_iter().collect();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "Payment Card");
+        assert_eq!(templates[0].entry_uuids, created);
+
+        let templates_group = rx_db
+            .meta
+            .entry_templates_group
+            .expect("No templates group");
+        let template_entry = rx_db
+            .get_entry(templates[0].uuid)
+            .expect("No template entry");
+        assert_eq!(template_entry.parent_group, templates_group);
+
+        let visa = rx_db.get_entry(created[0]).unwrap();
+        assert_eq!(visa.template_uuid, Some(templates[0].uuid));
+
+        // Importing again reuses the template.
+        rx_db
+            .import_entries(root_id, &[card()])
+            .expect("Could not import entries");
+        assert_eq!(rx_db.templates_iter().count(), 1);
+        assert_eq!(rx_db.get_group(templates_group).unwrap().entries.len(), 1);
+    }
+
     // TODO move to rx_containers
     // #[test]
     // fn finds_entries_in_group() {
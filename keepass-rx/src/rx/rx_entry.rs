@@ -1,34 +1,33 @@
 use crate::crypto::{EncryptedValue, MasterKey};
 
+use super::entropy::{PasswordQuality, calculate_entropy};
 use super::icons::RxIcon;
+use super::rx_attachment::RxAttachment;
+use super::rx_otp::{
+    HOTP_COUNTER_FIELD, HOTP_FIELD_PREFIX, HOTP_SECRET_FIELDS, HOTP_URI_PREFIX,
+    LEGACY_TOTP_SEED_FIELD, LEGACY_TOTP_SETTINGS_FIELD, RxHotp, RxTotpParams, legacy_totp_uri,
+    normalize_otp_value, unix_time, with_hotp_counter, with_label,
+};
+use super::rx_times::RxTimes;
 use anyhow::{Result, anyhow};
 use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::NaiveDateTime;
 use humanize_duration::Truncate;
 use humanize_duration::prelude::DurationExt;
 use infer;
-use keepass::db::{CustomData, Entry, Icon, TOTP as KeePassTOTP, Value};
+use keepass::db::{CustomData, CustomDataItem, Entry, History, Icon, Value};
 use libsodium_rs::utils::{SecureVec, vec_utils};
-use querystring::querify;
 use secstr::SecStr;
 use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use std::{mem, sync::atomic::AtomicU64};
-use totp_rs::{Secret, TOTP};
-use uriparse::URI;
 use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
-macro_rules! expose {
-    ($masterkey:expr, $secret:expr) => {{
-        $secret
-            .as_ref()
-            .and_then(|secret| secret.value($masterkey).map(|value| value.to_string()))
-            .unwrap_or_default()
-    }};
-}
-
 macro_rules! expose_str {
     ($masterkey:expr, $secret:expr) => {{
         $secret
@@ -38,7 +37,7 @@ macro_rules! expose_str {
     }};
 }
 
-pub(crate) use {expose, expose_str};
+pub(crate) use expose_str;
 
 // Special field that indicates the entry is a templated entry (e.g.
 // credit card, wifi password, etc).
@@ -47,9 +46,13 @@ pub(crate) const TEMPLATE_FIELD_NAME: &str = "_etm_template_uuid";
 // Fields inserted by other KeePass programs that we do not want to
 // show as custom fields. They might be used for other things in the
 // app, though.
-const FIELDS_TO_HIDE: [&str; 3] = [
+const FIELDS_TO_HIDE: [&str; 5] = [
     // KeePassXC browser integration (list of URLs)
     "KeePassXC-Browser Settings",
+    // TOTP secret and settings from older KeePassXC versions and
+    // KeeOtp. Shown as the entry's TOTP instead.
+    LEGACY_TOTP_SEED_FIELD,
+    LEGACY_TOTP_SETTINGS_FIELD,
     // Last modified date
     "_LAST_MODIFIED",
     // UUID of a template (e.g. credit card entry), created by
@@ -59,7 +62,13 @@ const FIELDS_TO_HIDE: [&str; 3] = [
 
 // Like FIELDS_TO_HIDE, but does a starts_with check to see if the
 // value should be hidden.
-const WILDCARD_FIELDS_TO_HIDE: [&str; 3] = ["AndroidApp", "KP2A_URL", "KPEX_PASSKEY_"];
+const WILDCARD_FIELDS_TO_HIDE: [&str; 4] = [
+    "AndroidApp",
+    "KP2A_URL",
+    "KPEX_PASSKEY_",
+    // KeePass HOTP secret and counter. Shown as the entry's OTP.
+    HOTP_FIELD_PREFIX,
+];
 
 fn should_hide_field(field_name: &str) -> bool {
     FIELDS_TO_HIDE.contains(&field_name)
@@ -93,9 +102,62 @@ pub struct RxEntry {
 
     pub(super) url: Option<RxValue>,
     pub(super) raw_otp_value: Option<RxValue>,
+    pub(super) attachments: Vec<RxAttachment>,
+
+    /// Fields that are not shown in the UI (see FIELDS_TO_HIDE), but
+    /// must be preserved when the database is saved.
+    pub(super) hidden_fields: Vec<(String, RxValue)>,
 
     #[zeroize(skip)]
     pub icon: RxIcon,
+
+    #[zeroize(skip)]
+    pub times: RxTimes,
+
+    /// Rated when first needed, because zxcvbn is not cheap.
+    #[zeroize(skip)]
+    password_quality: OnceCell<Option<PasswordQuality>>,
+
+    /// Whether the password is in the local breached password list.
+    /// Only known after the database has been checked against it.
+    #[zeroize(skip)]
+    pub(super) pwned: bool,
+
+    /// Older revisions of this entry, oldest first. They are
+    /// extracted and encrypted the same way as the entry itself.
+    pub(super) history: Vec<RxEntry>,
+
+    /// Custom data of the entry (plugin settings and the like). Text
+    /// items are also shown among the custom fields, but are saved
+    /// back as custom data.
+    pub(super) custom_data: Vec<RxCustomDataItem>,
+
+    /// What is left of the original entry after fields, custom data
+    /// and history have been extracted (times, auto-type, colors,
+    /// etc). Nothing secret is left in it. Used to write the entry
+    /// back out.
+    #[zeroize(skip)]
+    pub(super) remainder: Entry,
+}
+
+fn extract_attachments(master_key: &MasterKey, entry: &mut Entry) -> Vec<RxAttachment> {
+    let attachment_names: Vec<String> = entry
+        .fields
+        .iter()
+        .filter(|(_, value)| matches!(value, Value::Bytes(_)))
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    let mut attachments: Vec<_> = attachment_names
+        .into_iter()
+        .flat_map(|name| match entry.fields.remove(&name) {
+            Some(Value::Bytes(bytes)) => RxAttachment::new(master_key, name, bytes).ok(),
+            _ => None,
+        })
+        .collect();
+
+    attachments.sort_by(|a, b| a.name.cmp(&b.name));
+    attachments
 }
 
 fn extract_remaining_fields(
@@ -113,7 +175,8 @@ fn extract_remaining_fields(
                 Value::Unprotected(val) => {
                     RxValue::try_from(val).ok().map(|rx_val| (key, rx_val))
                 }
-                _ => None, // discard binary for now. attachments later?
+                // Binary fields are extracted as attachments beforehand.
+                _ => None,
             }
         })
         .collect()
@@ -128,7 +191,7 @@ fn extract_value(
         match value {
             Value::Protected(val) => RxValue::encrypted(master_key, val).ok(),
             Value::Unprotected(val) => RxValue::try_from(val).ok(),
-            _ => None, // discard binary for now. attachments later?
+            _ => None, // binary fields are attachments, not values.
         }
     })
 }
@@ -143,7 +206,24 @@ impl RxEntry {
     ) -> Self {
         let master_key = master_key.clone();
 
-        let custom_data = mem::take(&mut entry.custom_data);
+        // History entries are full entries of their own (without
+        // further history).
+        let history: Vec<RxEntry> = entry
+            .history
+            .take()
+            .map(|history| {
+                history
+                    .get_entries()
+                    .iter()
+                    .cloned()
+                    .map(|old| RxEntry::new(&master_key, old, parent_uuid, None))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Custom data is kept apart from the fields, so that it is
+        // saved back as custom data.
+        let custom_data = RxCustomDataItem::extract(&mut entry.custom_data);
 
         let title = extract_value(&master_key, &mut entry, "Title");
         let username = extract_value(&master_key, &mut entry, "UserName");
@@ -168,10 +248,16 @@ impl RxEntry {
             .unwrap_or(RxIcon::None);
 
         // Has to come after the above, otherwise those fields end up
-        // in the custom fields.
-        let remaining_fields = extract_remaining_fields(&master_key, &mut entry);
+        // in the custom fields. Attachments are pulled out first so
+        // that only text fields remain.
+        let attachments = extract_attachments(&master_key, &mut entry);
+        let (hidden_fields, remaining_fields): (Vec<_>, Vec<_>) =
+            extract_remaining_fields(&master_key, &mut entry)
+                .into_iter()
+                .partition(|(key, _)| should_hide_field(key));
+
         let mut remaining_fields = RxCustomFields::from_vec(&master_key, remaining_fields);
-        let mut custom_fields = RxCustomFields::from_custom_data(&master_key, custom_data);
+        let mut custom_fields = RxCustomFields::from_custom_data(&master_key, &custom_data);
         custom_fields.append(&mut remaining_fields);
 
         Self {
@@ -186,9 +272,197 @@ impl RxEntry {
             custom_fields: custom_fields,
             url: url,
             raw_otp_value: raw_otp_value,
+            attachments: attachments,
+            hidden_fields: hidden_fields,
             icon: rx_icon,
+            times: RxTimes::from_keepass(&entry.times),
+            password_quality: OnceCell::new(),
+            pwned: false,
+            history: history,
             tags: mem::take(&mut entry.tags),
+            custom_data: custom_data,
+            remainder: entry,
+        }
+    }
+
+    /// Convert back into a KeePass entry, for saving. Standard fields
+    /// are protected according to the database's memory protection
+    /// settings. Other fields keep the protection they were loaded
+    /// with.
+    pub(super) fn to_keepass(&self, protection: &RxFieldProtection) -> Result<Entry> {
+        let mut entry = self.remainder.clone();
+        entry.uuid = self.uuid;
+        entry.tags = self.tags.clone();
+        entry.times = self.times.to_keepass(entry.times.clone());
+
+        let key: &MasterKey = &self.master_key;
+        let mut insert = |name: &str, value: &Option<RxValue>, protect: bool| -> Result<()> {
+            if let Some(value) = value {
+                entry
+                    .fields
+                    .insert(name.to_string(), value.to_keepass(key, protect)?);
+            }
+            Ok(())
+        };
+
+        insert("Title", &self.title, protection.title)?;
+        insert("UserName", &self.username, protection.username)?;
+        insert("Password", &self.password, protection.password)?;
+        insert("URL", &self.url, protection.url)?;
+        insert("Notes", &self.notes, protection.notes)?;
+
+        let otp_protected = self
+            .raw_otp_value
+            .as_ref()
+            .is_some_and(|otp| otp.is_hidden_by_default());
+        insert("otp", &self.raw_otp_value, otp_protected)?;
+
+        for (name, value) in self
+            .hidden_fields
+            .iter()
+            .chain(self.custom_fields.data.iter())
+        {
+            // Custom data was merged into the custom fields when
+            // loading. It is saved as custom data instead.
+            if self.custom_data.iter().any(|item| item.key == *name) {
+                continue;
+            }
+
+            let protect = value.is_hidden_by_default();
+            entry
+                .fields
+                .insert(name.clone(), value.to_keepass(key, protect)?);
+        }
+
+        for item in self.custom_data.iter() {
+            entry
+                .custom_data
+                .items
+                .insert(item.key.clone(), item.to_keepass());
+        }
+
+        if let Some(template_uuid) = self.template_uuid {
+            entry.fields.insert(
+                TEMPLATE_FIELD_NAME.to_string(),
+                Value::Unprotected(template_uuid.to_string()),
+            );
+        }
+
+        for attachment in self.attachments.iter() {
+            let data = attachment.data(key)?;
+            entry
+                .fields
+                .insert(attachment.name.clone(), Value::Bytes(data.to_vec()));
+        }
+
+        if !self.history.is_empty() {
+            // add_entry puts each entry at the front, so go newest
+            // to oldest to keep the original order.
+            let mut history = History::default();
+            for old in self.history.iter().rev() {
+                history.add_entry(old.to_keepass(protection)?);
+            }
+
+            entry.history = Some(history);
         }
+
+        Ok(entry)
+    }
+
+    /// Older revisions of this entry, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &RxEntry> {
+        self.history.iter()
+    }
+
+    pub fn get_history(&self, index: usize) -> Option<&RxEntry> {
+        self.history.get(index)
+    }
+
+    /// Whether the entry has an expiry time that has passed. The
+    /// expiry time is ignored unless the entry is set to expire.
+    pub fn is_expired(&self) -> bool {
+        self.times.is_expired()
+    }
+
+    /// Save the current state of the entry as a history revision,
+    /// before changing it. Drops the oldest revisions if there are
+    /// more than max_items, or if all revisions together take up
+    /// more than max_size bytes.
+    pub(super) fn push_history(&mut self, max_items: Option<usize>, max_size: Option<usize>) {
+        let mut snapshot = self.clone();
+        snapshot.history.clear();
+        self.history.push(snapshot);
+
+        if let Some(max_items) = max_items {
+            let excess = self.history.len().saturating_sub(max_items);
+            self.history.drain(..excess);
+        }
+
+        if let Some(max_size) = max_size {
+            // Keep the newest revisions that fit, like KeePassXC.
+            let mut total_size = 0;
+            let kept = self
+                .history
+                .iter()
+                .rev()
+                .take_while(|old| {
+                    total_size += old.approximate_size();
+                    total_size <= max_size
+                })
+                .count();
+
+            let excess = self.history.len() - kept;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Roughly how many bytes the entry takes up in the database:
+    /// its values, tags and attachments.
+    fn approximate_size(&self) -> usize {
+        let values = [
+            &self.title,
+            &self.username,
+            &self.password,
+            &self.notes,
+            &self.url,
+            &self.raw_otp_value,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.custom_fields.data.iter().map(|(_, value)| value))
+        .chain(self.hidden_fields.iter().map(|(_, value)| value))
+        .map(RxValue::len)
+        .sum::<usize>();
+
+        let tags: usize = self.tags.iter().map(String::len).sum();
+        let attachments: usize = self.attachments.iter().map(|a| a.size).sum();
+
+        values + tags + attachments
+    }
+
+    pub(super) fn touch_modified(&mut self) {
+        self.times.last_modified = Some(RxTimes::now());
+    }
+
+    /// Replace the contents of this entry with an older revision.
+    /// Identity, location, history, and times stay as they are.
+    pub(super) fn restore_revision(&mut self, revision: &RxEntry) {
+        self.template_uuid = revision.template_uuid;
+        self.title = revision.title.clone();
+        self.username = revision.username.clone();
+        self.password = revision.password.clone();
+        self.notes = revision.notes.clone();
+        self.tags = revision.tags.clone();
+        self.custom_fields = revision.custom_fields.clone();
+        self.url = revision.url.clone();
+        self.raw_otp_value = revision.raw_otp_value.clone();
+        self.attachments = revision.attachments.clone();
+        self.hidden_fields = revision.hidden_fields.clone();
+        self.custom_data = revision.custom_data.clone();
+        self.icon = revision.icon.clone();
+        self.remainder = revision.remainder.clone();
+        self.password_quality = OnceCell::new();
+        self.pwned = false;
     }
 
     pub fn username(&self) -> Option<RxValueKeyRef<'_>> {
@@ -215,12 +489,37 @@ impl RxEntry {
             .map(|u| RxValueKeyRef::new(u, &self.master_key))
     }
 
+    pub fn notes(&self) -> Option<RxValueKeyRef<'_>> {
+        self.notes
+            .as_ref()
+            .map(|n| RxValueKeyRef::new(n, &self.master_key))
+    }
+
     pub fn raw_otp_value(&self) -> Option<RxValueKeyRef<'_>> {
         self.raw_otp_value
             .as_ref()
             .map(|t| RxValueKeyRef::new(t, &self.master_key))
     }
 
+    pub fn attachments(&self) -> impl Iterator<Item = &RxAttachment> {
+        self.attachments.iter()
+    }
+
+    pub fn has_attachments(&self) -> bool {
+        self.attachments.len() > 0
+    }
+
+    pub fn get_attachment(&self, name: &str) -> Option<&RxAttachment> {
+        self.attachments.iter().find(|a| a.name == name)
+    }
+
+    /// Decrypt the contents of the named attachment.
+    pub fn attachment_data(&self, name: &str) -> Result<SecureVec<u8>> {
+        self.get_attachment(name)
+            .ok_or(anyhow!("No attachment named {}", name))
+            .and_then(|attachment| attachment.data(&self.master_key))
+    }
+
     pub(super) fn master_key(&self) -> &MasterKey {
         &self.master_key
     }
@@ -243,6 +542,10 @@ impl RxEntry {
                 .title
                 .as_ref()
                 .map(|val| RxValueKeyRef::new(val, &self.master_key)),
+            RxFieldName::Notes => self
+                .notes
+                .as_ref()
+                .map(|val| RxValueKeyRef::new(val, &self.master_key)),
             RxFieldName::CustomField(name) => {
                 self.custom_fields
                     .data
@@ -255,62 +558,233 @@ impl RxEntry {
         }
     }
 
+    /// Replace (or clear, if None) one of the standard fields of
+    /// this entry. The value must already be encrypted.
+    pub(super) fn set_field(
+        &mut self,
+        field_name: &RxFieldName,
+        value: Option<RxValue>,
+    ) -> Result<()> {
+        let field = match field_name {
+            RxFieldName::Title => &mut self.title,
+            RxFieldName::Username => &mut self.username,
+            RxFieldName::Password => &mut self.password,
+            RxFieldName::Url => &mut self.url,
+            RxFieldName::Notes => &mut self.notes,
+            RxFieldName::CustomField(name) => {
+                return Err(anyhow!("Cannot edit custom field {} yet", name));
+            }
+        };
+
+        // Dropping the old value zeroes it out.
+        *field = value;
+
+        if *field_name == RxFieldName::Password {
+            self.password_quality = OnceCell::new();
+            self.pwned = false;
+        }
+
+        Ok(())
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Password quality, rated the same way as KeePassXC does. None
+    /// if the entry has no password.
+    pub fn password_quality(&self) -> Option<PasswordQuality> {
+        *self.password_quality.get_or_init(|| {
+            let password = self.password().and_then(|p| p.value_secure())?;
+            match password.is_empty() {
+                true => None,
+                false => calculate_entropy(&password).ok().map(PasswordQuality::from),
+            }
+        })
+    }
+
+    pub fn is_password_weak(&self) -> bool {
+        self.password_quality()
+            .is_some_and(|quality| quality.is_weak())
+    }
+
+    /// Whether the password has shown up in a data breach, as of the
+    /// last check against the breached password list.
+    pub fn is_pwned(&self) -> bool {
+        self.pwned
+    }
+
     pub fn has_tags(&self) -> bool {
         self.tags.len() > 0
     }
 
+    fn hidden_field(&self, name: &str) -> Option<Zeroizing<String>> {
+        self.hidden_fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.value(&self.master_key))
+            .map(Zeroizing::new)
+    }
+
+    /// The otpauth URI that codes are generated from. Older KeePassXC
+    /// versions kept the secret in the TOTP Seed and TOTP Settings
+    /// fields instead of the otp field.
+    fn otp_uri(&self) -> Option<Zeroizing<String>> {
+        if self.raw_otp_value.is_some() {
+            let raw_otp = expose_str!(&self.master_key, self.raw_otp_value);
+            return normalize_otp_value(&raw_otp);
+        }
+
+        let seed = self.hidden_field(LEGACY_TOTP_SEED_FIELD)?;
+        let settings = self.hidden_field(LEGACY_TOTP_SETTINGS_FIELD);
+        legacy_totp_uri(&seed, settings.as_ref().map(|settings| settings.as_str()))
+    }
+
+    fn hotp_uri(&self) -> Option<Zeroizing<String>> {
+        self.otp_uri()
+            .filter(|uri| uri.starts_with(HOTP_URI_PREFIX))
+    }
+
     pub fn has_otp(&self) -> bool {
         self.raw_otp_value.is_some()
+            || self.hidden_field(LEGACY_TOTP_SEED_FIELD).is_some()
+            || self.has_hotp()
     }
 
-    pub fn has_steam_otp(&self) -> bool {
-        expose!(&self.master_key, self.raw_otp_value).starts_with("otpauth://totp/Steam:")
+    /// Whether the entry has a counter-based one-time password, either
+    /// as an otpauth://hotp URI or in the KeePass HmacOtp fields.
+    pub fn has_hotp(&self) -> bool {
+        self.hotp_uri().is_some()
+            || HOTP_SECRET_FIELDS
+                .iter()
+                .any(|(name, _)| self.hidden_field(name).is_some())
     }
 
-    pub fn steam_otp_digits(&self) -> Result<String> {
-        if !self.has_steam_otp() {
-            return Err(anyhow!("Not a Steam OTP entry"));
+    pub fn hotp(&self) -> Result<RxHotp> {
+        if let Some(uri) = self.hotp_uri() {
+            return RxHotp::from_uri(&uri);
         }
 
-        let raw_otp = expose_str!(&self.master_key, self.raw_otp_value);
-        let uri = URI::try_from(raw_otp.as_str())?;
+        let (secret, encoding) = HOTP_SECRET_FIELDS
+            .iter()
+            .find_map(|(name, encoding)| self.hidden_field(name).map(|s| (s, *encoding)))
+            .ok_or(anyhow!("Not an HOTP entry"))?;
+
+        let counter = self.hidden_field(HOTP_COUNTER_FIELD);
+        RxHotp::from_fields(
+            &secret,
+            encoding,
+            counter.as_ref().map(|counter| counter.as_str()),
+        )
+    }
 
-        let query = uri
-            .query()
-            .ok_or(anyhow!("No querystring for Steam OTP entry"))?;
+    /// Move the HOTP counter on by one, after its code has been used.
+    /// The counter is stored where it was read from, so it is saved
+    /// with the database.
+    pub(super) fn advance_hotp_counter(&mut self) -> Result<()> {
+        let counter = self
+            .hotp()?
+            .counter
+            .checked_add(1)
+            .ok_or(anyhow!("HOTP counter cannot go any higher"))?;
+
+        if let Some(uri) = self.hotp_uri() {
+            let uri = with_hotp_counter(&uri, counter);
+            let protected = self
+                .raw_otp_value
+                .as_ref()
+                .is_some_and(|otp| otp.is_hidden_by_default());
 
-        let query_values = querify(query);
+            self.raw_otp_value = Some(match protected {
+                true => RxValue::encrypted(&self.master_key, SecStr::from(uri.as_str()))?,
+                false => RxValue::try_from(uri.to_string())?,
+            });
 
-        let secret = query_values
-            .into_iter()
-            .find_map(|(key, value)| match key {
-                "secret" => Some(value.to_string()),
-                _ => None,
-            })
-            .ok_or(anyhow!("No Steam secret in OTP"))?;
+            return Ok(());
+        }
 
-        let steam_otp_code =
-            TOTP::new_steam(Secret::Encoded(secret).to_bytes()?).generate_current()?;
+        let counter = RxValue::try_from(counter.to_string())?;
+        match self
+            .hidden_fields
+            .iter_mut()
+            .find(|(key, _)| key == HOTP_COUNTER_FIELD)
+        {
+            Some((_, value)) => *value = counter,
+            None => self
+                .hidden_fields
+                .push((HOTP_COUNTER_FIELD.to_string(), counter)),
+        }
 
-        Ok(steam_otp_code)
+        Ok(())
     }
 
-    pub fn totp(&self) -> Result<RxTotp> {
-        let otp =
-            KeePassTOTP::from_str(expose_str!(&self.master_key, self.raw_otp_value).as_str())?;
+    /// The one-time password as an otpauth URI, for moving it to
+    /// another app or device. Entries without a URI of their own
+    /// (legacy fields, HmacOtp fields) get one, labeled with the
+    /// entry's title and username.
+    pub fn otp_export_uri(&self) -> Result<Zeroizing<String>> {
+        let title = self.title().and_then(|title| title.value());
+        let username = self.username().and_then(|username| username.value());
+        let label = Zeroizing::new(match (title, username) {
+            (Some(title), Some(username)) => format!("{}:{}", *title, *username),
+            (Some(name), None) | (None, Some(name)) => name.to_string(),
+            (None, None) => "KeePassRX".to_string(),
+        });
+
+        if let Some(uri) = self.otp_uri() {
+            return Ok(with_label(&uri, &label));
+        }
+
+        match self.has_hotp() {
+            true => Ok(self.hotp()?.to_uri(&label)),
+            false => Err(anyhow!("Entry has no one-time password")),
+        }
+    }
 
-        let otp_code = otp.value_now()?;
+    /// Settings of the entry's time-based one-time password. Fails
+    /// with an RxOtpError for kinds that codes cannot be made for.
+    pub fn totp_params(&self) -> Result<RxTotpParams> {
+        let otp_uri = self.otp_uri().ok_or(anyhow!("No OTP for entry"))?;
+        RxTotpParams::from_uri(&otp_uri)
+    }
 
-        let otp_digits = match self.has_steam_otp() {
-            true => self.steam_otp_digits()?,
-            false => otp_code.code,
-        };
+    pub fn has_steam_otp(&self) -> bool {
+        self.totp_params().is_ok_and(|params| params.is_steam())
+    }
 
-        let otp_valid_for = format!("{}", otp_code.valid_for.human(Truncate::Second));
+    pub fn steam_otp_digits(&self) -> Result<String> {
+        let params = self.totp_params()?;
+        if !params.is_steam() {
+            return Err(anyhow!("Not a Steam OTP entry"));
+        }
+
+        Ok(params.code_at(unix_time()))
+    }
+
+    pub fn totp(&self) -> Result<RxTotp> {
+        // HOTP codes do not expire. They are used up instead, see
+        // advance_hotp_counter.
+        if self.has_hotp() {
+            let hotp = self.hotp()?;
+            return Ok(RxTotp {
+                code: hotp.code(),
+                counter: Some(hotp.counter),
+                ..Default::default()
+            });
+        }
+
+        let params = self.totp_params()?;
+        let now = unix_time();
+        let remaining = params.remaining_at(now);
+        let otp_valid_for =
+            format!("{}", Duration::from_secs(remaining).human(Truncate::Second));
 
         Ok(RxTotp {
-            code: otp_digits,
+            code: params.code_at(now),
             valid_for: otp_valid_for,
+            period: params.period,
+            remaining,
+            counter: None,
         })
     }
 
@@ -344,6 +818,11 @@ impl<'a> RxValueKeyRef<'a> {
         self.0.value(self.1).map(Zeroizing::new)
     }
 
+    /// The raw bytes of the value, without going through a String.
+    pub fn value_secure(&self) -> Option<SecureVec<u8>> {
+        self.0.value_secure(self.1)
+    }
+
     pub fn is_hidden_by_default(&self) -> bool {
         self.0.is_hidden_by_default()
     }
@@ -399,6 +878,11 @@ impl Zeroize for RxValue {
 
 static ID_COUNTER: AtomicU64 = AtomicU64::new(1);
 
+/// Unique ID for a newly encrypted value, used to derive its subkey.
+pub(super) fn next_value_id() -> u64 {
+    ID_COUNTER.fetch_add(1, Ordering::SeqCst)
+}
+
 impl RxValue {
     pub fn encrypted(master_key: &MasterKey, mut value: SecStr) -> Result<Self> {
         let value_unsecure = value.unsecure();
@@ -406,15 +890,21 @@ impl RxValue {
         secure_vec.copy_from_slice(&value_unsecure);
         value.zero_out();
 
-        let encrypted_value = EncryptedValue::new(
-            master_key,
-            ID_COUNTER.fetch_add(1, Ordering::SeqCst),
-            secure_vec,
-        )?;
+        let encrypted_value = EncryptedValue::new(master_key, next_value_id(), secure_vec)?;
 
         Ok(Self::Protected(encrypted_value))
     }
 
+    /// Length of the value in bytes, without decrypting it.
+    pub fn len(&self) -> usize {
+        match self {
+            RxValue::Protected(val) => val.len(),
+            RxValue::Sensitive(val) => val.len(),
+            RxValue::Unprotected(val) => val.len(),
+            _ => 0,
+        }
+    }
+
     pub fn is_hidden_by_default(&self) -> bool {
         match self {
             RxValue::Protected(_) => true,
@@ -436,12 +926,148 @@ impl RxValue {
         }
     }
 
+    pub fn value_secure(&self, master_key: &MasterKey) -> Option<SecureVec<u8>> {
+        use RxValue::*;
+        match self {
+            Protected(val) => val.expose(master_key).ok(),
+            Sensitive(val) => Some(val.clone()),
+            Unprotected(value) => {
+                vec_utils::secure_vec::<u8>(value.len())
+                    .ok()
+                    .map(|mut secure_vec| {
+                        secure_vec.copy_from_slice(value.as_bytes());
+                        secure_vec
+                    })
+            }
+            _ => None,
+        }
+    }
+
     pub fn totp_value(&self) -> Option<&RxTotp> {
         match self {
             RxValue::CurrentTotp(totp) => Some(totp),
             _ => None,
         }
     }
+
+    /// Decrypt into a KeePass value, for saving.
+    pub(super) fn to_keepass(&self, master_key: &MasterKey, protect: bool) -> Result<Value> {
+        let value = self
+            .value(master_key)
+            .ok_or(anyhow!("Value cannot be saved: {}", self))?;
+
+        Ok(match protect {
+            true => Value::Protected(SecStr::from(value)),
+            false => Value::Unprotected(value),
+        })
+    }
+}
+
+/// The text of a value held in secure memory, which needs no key to
+/// read.
+pub(super) fn sensitive_text(value: &RxValue) -> Option<String> {
+    match value {
+        RxValue::Sensitive(bytes) => std::str::from_utf8(bytes).ok().map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+fn sensitive_bytes(bytes: &[u8]) -> Option<RxValue> {
+    let mut secure_vec = vec_utils::secure_vec::<u8>(bytes.len()).ok()?;
+    secure_vec.copy_from_slice(bytes);
+    Some(RxValue::Sensitive(secure_vec))
+}
+
+/// An item of KeePass custom data, of an entry or a group. The value
+/// is kept in secure memory until it is saved back.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub(super) struct RxCustomDataItem {
+    pub(super) key: String,
+    value: Option<RxValue>,
+
+    #[zeroize(skip)]
+    protected: bool,
+
+    #[zeroize(skip)]
+    last_modified: Option<NaiveDateTime>,
+}
+
+impl RxCustomDataItem {
+    /// Take all items out of the custom data. Only text can be
+    /// custom data, so anything else is dropped.
+    pub(super) fn extract(custom_data: &mut CustomData) -> Vec<Self> {
+        std::mem::take(&mut custom_data.items)
+            .into_iter()
+            .map(|(key, item)| {
+                let protected = matches!(item.value, Some(Value::Protected(_)));
+                let value = item.value.and_then(|value| match value {
+                    Value::Protected(mut val) => {
+                        let value = sensitive_bytes(val.unsecure());
+                        val.zero_out();
+                        value
+                    }
+                    Value::Unprotected(val) => RxValue::try_from(val).ok(),
+                    Value::Bytes(_) => None,
+                });
+
+                Self {
+                    key: key,
+                    value: value,
+                    protected: protected,
+                    last_modified: item.last_modification_time,
+                }
+            })
+            .collect()
+    }
+
+    /// The value as a custom field. Protected items are encrypted
+    /// like protected fields are.
+    fn to_field(&self, master_key: &MasterKey) -> Option<RxValue> {
+        let value = self.value.as_ref()?;
+        match self.protected {
+            true => value.value_secure(master_key).and_then(|bytes| {
+                RxValue::encrypted(master_key, SecStr::new(bytes.to_vec())).ok()
+            }),
+            false => Some(value.clone()),
+        }
+    }
+
+    pub(super) fn to_keepass(&self) -> CustomDataItem {
+        let value = self.value.as_ref().and_then(sensitive_text);
+
+        CustomDataItem {
+            value: value.map(|value| match self.protected {
+                true => Value::Protected(SecStr::from(value)),
+                false => Value::Unprotected(value),
+            }),
+            last_modification_time: self.last_modified,
+        }
+    }
+}
+
+/// Which standard fields are stored protected (encrypted within the
+/// database file). Comes from the database's memory protection
+/// settings.
+#[derive(Clone, Copy)]
+pub struct RxFieldProtection {
+    pub title: bool,
+    pub username: bool,
+    pub password: bool,
+    pub url: bool,
+    pub notes: bool,
+}
+
+impl Default for RxFieldProtection {
+    fn default() -> Self {
+        // KeePass default: only the password is protected.
+        Self {
+            title: false,
+            username: false,
+            password: true,
+            url: false,
+            notes: false,
+        }
+    }
 }
 
 impl std::fmt::Display for RxValue {
@@ -488,6 +1114,16 @@ impl TryFrom<String> for RxValue {
 pub struct RxTotp {
     pub code: String,
     pub valid_for: String,
+
+    /// Seconds that each code is valid for. 0 for HOTP codes, which
+    /// do not expire.
+    pub period: u64,
+
+    /// Seconds until the code rolls over.
+    pub remaining: u64,
+
+    /// Counter the code was generated from, for HOTP codes.
+    pub counter: Option<u64>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -496,6 +1132,7 @@ pub enum RxFieldName {
     Username,
     Password,
     Url,
+    Notes,
     CustomField(String),
 }
 
@@ -506,6 +1143,7 @@ impl ToString for RxFieldName {
             RxFieldName::Password => "Password".to_string(),
             RxFieldName::Title => "Title".to_string(),
             RxFieldName::Url => "URL".to_string(),
+            RxFieldName::Notes => "Notes".to_string(),
             RxFieldName::CustomField(name) => name.to_owned(),
         }
     }
@@ -518,6 +1156,7 @@ impl From<String> for RxFieldName {
             "password" => RxFieldName::Password,
             "title" => RxFieldName::Title,
             "url" => RxFieldName::Url,
+            "notes" => RxFieldName::Notes,
             _ => RxFieldName::CustomField(value),
         }
     }
@@ -555,24 +1194,13 @@ impl RxCustomFields {
             .map(|(key, value)| (key, RxValueKeyRef::new(value, &self.master_key)))
     }
 
-    fn from_custom_data(master_key: &Rc<MasterKey>, value: CustomData) -> Self {
-        let custom_fields: Vec<_> = value
-            .items
-            .into_iter()
-            .flat_map(|(key, item)| {
-                if !should_hide_field(&key.as_ref()) {
-                    item.value.and_then(|value| match value {
-                        Value::Protected(val) => RxValue::encrypted(master_key, val)
-                            .ok()
-                            .map(|secret| (key, secret)),
-                        Value::Unprotected(val) => {
-                            RxValue::try_from(val).ok().map(|secret| (key, secret))
-                        }
-                        _ => None,
-                    })
-                } else {
-                    None
-                }
+    fn from_custom_data(master_key: &Rc<MasterKey>, items: &[RxCustomDataItem]) -> Self {
+        let custom_fields: Vec<_> = items
+            .iter()
+            .filter(|item| !should_hide_field(&item.key))
+            .flat_map(|item| {
+                item.to_field(master_key)
+                    .map(|secret| (item.key.clone(), secret))
             })
             .collect();
 
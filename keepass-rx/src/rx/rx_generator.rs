@@ -0,0 +1,301 @@
+use anyhow::{Result, anyhow};
+use libsodium_rs::random;
+use libsodium_rs::utils::{SecureVec, vec_utils};
+use zeroize::Zeroizing;
+
+use super::entropy::{PasswordQuality, calculate_entropy};
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Characters that are easily mistaken for each other, as excluded
+/// by KeePassXC.
+const LOOK_ALIKES: &str = "0Oo1Il|B8G6";
+
+/// The EFF large wordlist, bundled so passphrases can always be
+/// generated.
+const EFF_LARGE_WORDLIST: &str = include_str!("../../assets/eff_large_wordlist.txt");
+
+/// Random number in 0..upper_bound, without modulo bias.
+fn random_index(upper_bound: usize) -> usize {
+    random::uniform(upper_bound as u32) as usize
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RxCharClass {
+    Lowercase,
+    Uppercase,
+    Digits,
+    Symbols,
+}
+
+impl RxCharClass {
+    pub const ALL: [RxCharClass; 4] = [
+        RxCharClass::Lowercase,
+        RxCharClass::Uppercase,
+        RxCharClass::Digits,
+        RxCharClass::Symbols,
+    ];
+
+    fn chars(&self) -> &'static str {
+        match self {
+            RxCharClass::Lowercase => LOWERCASE,
+            RxCharClass::Uppercase => UPPERCASE,
+            RxCharClass::Digits => DIGITS,
+            RxCharClass::Symbols => SYMBOLS,
+        }
+    }
+
+    fn pool(&self, exclude_look_alikes: bool) -> Vec<u8> {
+        self.chars()
+            .bytes()
+            .filter(|c| !exclude_look_alikes || !LOOK_ALIKES.as_bytes().contains(c))
+            .collect()
+    }
+}
+
+/// What a generated password is made of.
+#[derive(Clone, Debug)]
+pub struct RxPasswordProfile {
+    pub length: usize,
+    pub classes: Vec<RxCharClass>,
+    pub exclude_look_alikes: bool,
+
+    /// Minimum number of characters from each of the classes.
+    pub min_per_class: usize,
+}
+
+impl Default for RxPasswordProfile {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            classes: RxCharClass::ALL.to_vec(),
+            exclude_look_alikes: false,
+            min_per_class: 1,
+        }
+    }
+}
+
+/// How a generated passphrase is put together.
+#[derive(Clone, Debug)]
+pub struct RxPassphraseProfile {
+    pub word_count: usize,
+    pub separator: String,
+
+    /// Upper case the first letter of every word.
+    pub capitalize: bool,
+}
+
+impl Default for RxPassphraseProfile {
+    fn default() -> Self {
+        Self {
+            word_count: 7,
+            separator: " ".to_string(),
+            capitalize: false,
+        }
+    }
+}
+
+/// A list of words to build passphrases from. Reads the EFF diceware
+/// format (dice roll, then the word) as well as one word per line.
+pub struct RxWordlist(Vec<String>);
+
+impl RxWordlist {
+    pub fn parse(text: &str) -> Result<Self> {
+        let words: Vec<_> = text
+            .lines()
+            .filter_map(|line| line.split_whitespace().last())
+            .map(|word| word.to_string())
+            .collect();
+
+        match words.len() {
+            0 | 1 => Err(anyhow!("Wordlist needs at least two words")),
+            _ => Ok(Self(words)),
+        }
+    }
+
+    pub fn eff_large() -> Self {
+        Self::parse(EFF_LARGE_WORDLIST).expect("Bundled wordlist is valid")
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// A generated password or passphrase, and how strong it is.
+pub struct RxGenerated {
+    pub value: SecureVec<u8>,
+    pub entropy: f64,
+}
+
+impl RxGenerated {
+    fn rate(value: SecureVec<u8>) -> Result<Self> {
+        let entropy = calculate_entropy(&value)?;
+        Ok(Self { value, entropy })
+    }
+
+    pub fn quality(&self) -> PasswordQuality {
+        PasswordQuality::from(self.entropy)
+    }
+}
+
+/// Generate a random password. The minimum for each class is placed
+/// first, the rest is drawn from all classes, and then everything is
+/// shuffled.
+pub fn generate_password(profile: &RxPasswordProfile) -> Result<RxGenerated> {
+    let pools: Vec<_> = profile
+        .classes
+        .iter()
+        .map(|class| class.pool(profile.exclude_look_alikes))
+        .filter(|pool| !pool.is_empty())
+        .collect();
+
+    if pools.is_empty() || profile.length == 0 {
+        return Err(anyhow!("Nothing to generate a password from"));
+    }
+
+    if pools.len() * profile.min_per_class > profile.length {
+        return Err(anyhow!(
+            "Password of length {} cannot have {} characters of each type",
+            profile.length,
+            profile.min_per_class
+        ));
+    }
+
+    let all_chars: Vec<u8> = pools.iter().flatten().copied().collect();
+    let mut password = vec_utils::secure_vec::<u8>(profile.length)?;
+    let mut position = 0;
+
+    for pool in &pools {
+        for _ in 0..profile.min_per_class {
+            password[position] = pool[random_index(pool.len())];
+            position += 1;
+        }
+    }
+
+    for slot in password[position..].iter_mut() {
+        *slot = all_chars[random_index(all_chars.len())];
+    }
+
+    // Fisher-Yates, so the required characters are not all at the
+    // front.
+    for i in (1..password.len()).rev() {
+        password.swap(i, random_index(i + 1));
+    }
+
+    RxGenerated::rate(password)
+}
+
+/// Generate a diceware passphrase from the wordlist.
+pub fn generate_passphrase(
+    wordlist: &RxWordlist,
+    profile: &RxPassphraseProfile,
+) -> Result<RxGenerated> {
+    if profile.word_count == 0 {
+        return Err(anyhow!("Passphrase needs at least one word"));
+    }
+
+    let picks = Zeroizing::new(
+        (0..profile.word_count)
+            .map(|_| random_index(wordlist.len()))
+            .collect::<Vec<_>>(),
+    );
+
+    let words_len: usize = picks.iter().map(|&pick| wordlist.0[pick].len()).sum();
+    let separators_len = profile.separator.len() * (profile.word_count - 1);
+    let mut passphrase = vec_utils::secure_vec::<u8>(words_len + separators_len)?;
+    let mut position = 0;
+
+    for (index, &pick) in picks.iter().enumerate() {
+        if index > 0 {
+            let separator = profile.separator.as_bytes();
+            passphrase[position..position + separator.len()].copy_from_slice(separator);
+            position += separator.len();
+        }
+
+        let word = wordlist.0[pick].as_bytes();
+        passphrase[position..position + word.len()].copy_from_slice(word);
+
+        // Wordlists are ASCII, so the first byte is the first letter.
+        if profile.capitalize {
+            passphrase[position] = passphrase[position].to_ascii_uppercase();
+        }
+
+        position += word.len();
+    }
+
+    RxGenerated::rate(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_follows_profile() {
+        let profile = RxPasswordProfile {
+            length: 12,
+            classes: vec![RxCharClass::Uppercase, RxCharClass::Digits],
+            exclude_look_alikes: true,
+            min_per_class: 3,
+        };
+
+        let generated = generate_password(&profile).expect("Could not generate password");
+        let password = &generated.value[..];
+
+        assert_eq!(password.len(), 12);
+        assert!(password.iter().filter(|c| c.is_ascii_digit()).count() >= 3);
+        assert!(password.iter().filter(|c| c.is_ascii_uppercase()).count() >= 3);
+        assert!(
+            password
+                .iter()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        );
+        assert!(password.iter().all(|c| !LOOK_ALIKES.as_bytes().contains(c)));
+        assert!(generated.entropy > 0.0);
+    }
+
+    #[test]
+    fn rejects_impossible_profile() {
+        let profile = RxPasswordProfile {
+            length: 4,
+            min_per_class: 2,
+            ..Default::default()
+        };
+
+        assert!(generate_password(&profile).is_err());
+    }
+
+    #[test]
+    fn passphrase_uses_wordlist() {
+        let wordlist = RxWordlist::parse("11111\tabacus\n11112\tabdomen\n11113\tabdominal\n")
+            .expect("Could not parse wordlist");
+        assert_eq!(wordlist.len(), 3);
+
+        let profile = RxPassphraseProfile {
+            word_count: 4,
+            separator: "-".to_string(),
+            capitalize: true,
+        };
+
+        let generated =
+            generate_passphrase(&wordlist, &profile).expect("Could not generate passphrase");
+        let passphrase = std::str::from_utf8(&generated.value).unwrap();
+        let words: Vec<_> = passphrase.split('-').collect();
+
+        assert_eq!(words.len(), 4);
+        assert!(words.iter().all(|word| word.starts_with("Ab")));
+    }
+
+    #[test]
+    fn bundles_eff_wordlist() {
+        assert_eq!(RxWordlist::eff_large().len(), 6usize.pow(5));
+    }
+}
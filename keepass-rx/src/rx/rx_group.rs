@@ -3,6 +3,8 @@ use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use super::icons::RxIcon;
+use super::rx_entry::{RxCustomDataItem, RxValue, sensitive_text};
+use super::rx_times::RxTimes;
 
 #[derive(Zeroize, ZeroizeOnDrop, Default, Clone)]
 pub struct RxGroup {
@@ -23,6 +25,20 @@ pub struct RxGroup {
 
     #[zeroize(skip)]
     pub entries: Vec<Uuid>,
+
+    #[zeroize(skip)]
+    pub times: RxTimes,
+
+    /// Notes and custom data are kept in secure memory, like the
+    /// fields of entries.
+    pub(super) notes: Option<RxValue>,
+    pub(super) custom_data: Vec<RxCustomDataItem>,
+
+    /// What is left of the original group after loading (times,
+    /// auto-type settings, etc). Nothing secret is left in it. Used
+    /// to write the group back out.
+    #[zeroize(skip)]
+    pub(super) remainder: Group,
 }
 
 impl RxGroup {
@@ -45,8 +61,37 @@ impl RxGroup {
             entries: entries,
             parent: parent,
             icon: icon,
+            times: RxTimes::from_keepass(&group.times),
+            notes: group
+                .notes
+                .take()
+                .and_then(|notes| RxValue::try_from(notes).ok()),
+            custom_data: RxCustomDataItem::extract(&mut group.custom_data),
+            remainder: std::mem::take(group),
         }
     }
+
+    /// Whether the group has an expiry time that has passed.
+    pub fn is_expired(&self) -> bool {
+        self.times.is_expired()
+    }
+
+    /// Convert back into a KeePass group, without any children. The
+    /// children are filled in by the database.
+    pub(super) fn to_keepass(&self) -> Group {
+        let mut group = self.remainder.clone();
+        group.uuid = self.uuid;
+        group.name = self.name.clone();
+        group.notes = self.notes.as_ref().and_then(sensitive_text);
+        group.custom_data.items = self
+            .custom_data
+            .iter()
+            .map(|item| (item.key.clone(), item.to_keepass()))
+            .collect();
+        group.times = self.times.to_keepass(group.times.clone());
+        group.children = vec![];
+        group
+    }
 }
 
 #[derive(Zeroize, ZeroizeOnDrop, Default, Clone)]
@@ -80,3 +125,62 @@ impl RxTag {
         }
     }
 }
+
+/// A search saved in the database by KeePassXC. The query uses the
+/// KeePassXC search language.
+#[derive(Zeroize, ZeroizeOnDrop, Default, Clone)]
+pub struct RxSavedSearch {
+    #[zeroize(skip)]
+    pub(crate) uuid: Uuid,
+    pub(crate) name: String,
+    pub(crate) query: String,
+    #[zeroize(skip)]
+    pub(crate) entry_uuids: Vec<Uuid>,
+}
+
+/// Namespace for the UUIDs of virtual containers that have no UUID
+/// of their own in the database. They are derived from the name, so
+/// they stay the same when a view is rebuilt.
+const VIRTUAL_CONTAINER_NAMESPACE: Uuid =
+    Uuid::from_u128(0x6b1f3c2e_9a4d_4e57_8c0b_d2a7e5f41c93);
+
+impl RxSavedSearch {
+    pub fn new(name: String, query: String, entry_uuids: Vec<Uuid>) -> Self {
+        let uuid_name = format!("saved-search:{}", name);
+
+        Self {
+            uuid: Uuid::new_v5(&VIRTUAL_CONTAINER_NAMESPACE, uuid_name.as_bytes()),
+            name: name,
+            query: query,
+            entry_uuids: entry_uuids,
+        }
+    }
+}
+
+/// A grouping of entries worked out by KeePassRx itself, such as the
+/// password quality buckets of the health report.
+#[derive(Zeroize, ZeroizeOnDrop, Default, Clone)]
+pub struct RxCategory {
+    #[zeroize(skip)]
+    pub(crate) uuid: Uuid,
+    pub(crate) name: String,
+    pub(crate) description: String,
+    #[zeroize(skip)]
+    pub(crate) entry_uuids: Vec<Uuid>,
+}
+
+impl RxCategory {
+    /// The id only has to be unique among the categories of a view.
+    /// Names are not (two reused password clusters can have the same
+    /// titles), so the UUID is derived from the id instead.
+    pub fn new(id: &str, name: String, description: String, entry_uuids: Vec<Uuid>) -> Self {
+        let uuid_name = format!("category:{}", id);
+
+        Self {
+            uuid: Uuid::new_v5(&VIRTUAL_CONTAINER_NAMESPACE, uuid_name.as_bytes()),
+            name: name,
+            description: description,
+            entry_uuids: entry_uuids,
+        }
+    }
+}
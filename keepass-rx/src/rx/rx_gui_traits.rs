@@ -1,7 +1,10 @@
 use qmetaobject::{QMetaType, QString, QVariant, QVariantMap};
 use std::collections::HashMap;
 
-use crate::rx::{RxCustomFields, RxEntry, RxFieldName, RxValue};
+use crate::rx::{
+    PasswordQuality, RxCustomFields, RxEntry, RxFieldName, RxOtpError, RxPasswordHealth,
+    RxTimes, RxValue,
+};
 
 use super::{RxMetadata, RxValueKeyRef, virtual_hierarchy::RxViewFeature};
 
@@ -91,12 +94,73 @@ impl From<&RxEntry> for QVariantMap {
             value.custom_fields.clone().into(),
         );
 
+        map.insert(
+            "attachmentCount".to_string(),
+            (value.attachments().count() as i32).into(),
+        );
+
+        let times = &value.times;
+        for (key, time) in [
+            ("created", &times.created),
+            ("lastModified", &times.last_modified),
+            ("lastAccessed", &times.last_accessed),
+            ("expiryTime", &times.expiry),
+        ] {
+            if let Some(time) = RxTimes::format(time) {
+                map.insert(key.to_string(), QString::from(time).into());
+            }
+        }
+
+        map.insert("expires".to_string(), times.expires.into());
+        map.insert("isExpired".to_string(), value.is_expired().into());
+
+        map.insert("isPwned".to_string(), value.is_pwned().into());
+
+        if let Some(quality) = value.password_quality() {
+            map.insert(
+                "passwordQuality".to_string(),
+                QString::from(quality.as_str()).into(),
+            );
+        }
+
         if let Ok(_) = totp {
             map.insert("hasTotp".to_string(), true.into());
         } else {
             map.insert("hasTotp".to_string(), false.into());
         }
 
+        // Settings of the one-time password, for display. HOTP codes
+        // have a counter instead of a period.
+        let otp_settings = match value.has_hotp() {
+            true => value
+                .hotp()
+                .ok()
+                .map(|hotp| (hotp.algorithm_name(), hotp.digits, 0)),
+            false => value
+                .totp_params()
+                .ok()
+                .map(|params| (params.algorithm_name(), params.digits, params.period)),
+        };
+
+        if let Some((algorithm, digits, period)) = otp_settings {
+            map.insert("otpAlgorithm".to_string(), QString::from(algorithm).into());
+            map.insert("otpDigits".to_string(), (digits as i32).into());
+            map.insert("otpPeriod".to_string(), (period as i32).into());
+        }
+
+        // Entries with a kind of OTP that is not supported say why,
+        // instead of just not having a code.
+        if let Some(otp_error) = totp
+            .as_ref()
+            .err()
+            .and_then(|err| err.downcast_ref::<RxOtpError>())
+        {
+            map.insert(
+                "otpError".to_string(),
+                QString::from(otp_error.to_string()).into(),
+            );
+        }
+
         map.into()
     }
 }
@@ -199,3 +263,22 @@ impl From<&RxMetadata> for QVariant {
         QVariantMap::from(value).into()
     }
 }
+
+impl From<&RxPasswordHealth> for QVariantMap {
+    fn from(value: &RxPasswordHealth) -> Self {
+        let mut map = QVariantMap::default();
+
+        for quality in PasswordQuality::ALL {
+            let key = quality.as_str().to_lowercase();
+            map.insert(
+                QString::from(key.as_str()),
+                (value.count(quality) as i32).into(),
+            );
+        }
+
+        map.insert("weakTotal".into(), (value.weak_count() as i32).into());
+        map.insert("ratedTotal".into(), (value.rated_count() as i32).into());
+        map.insert("pwned".into(), (value.pwned_entries().len() as i32).into());
+        map
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use libsodium_rs::crypto_generichash;
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+use super::entropy::PasswordQuality;
+use super::rx_db::RxDatabase;
+
+/// Subkey ID reserved for hashing passwords when looking for reuse.
+/// Value IDs count up from 1, so this never collides with them.
+const REUSE_HASH_KEY_ID: u64 = u64::MAX;
+
+/// Password quality of every entry in the database, like the
+/// KeePassXC health check. Entries in the recycle bin, and entries
+/// without a password, are not rated.
+#[derive(Default, Clone)]
+pub struct RxPasswordHealth {
+    /// Sorted worst quality first.
+    ratings: Vec<(Uuid, PasswordQuality)>,
+
+    /// Entries flagged by the last breached password check.
+    pwned: Vec<Uuid>,
+}
+
+impl RxPasswordHealth {
+    pub fn new(db: &RxDatabase) -> Self {
+        let mut ratings: Vec<_> = db
+            .all_entries_iter()
+            .filter(|entry| !db.is_in_recycle_bin(entry.parent_group))
+            .filter_map(|entry| entry.password_quality().map(|q| (entry.uuid, q)))
+            .collect();
+
+        ratings.sort_by_key(|(_, quality)| *quality);
+
+        let pwned = db
+            .all_entries_iter()
+            .filter(|entry| entry.is_pwned() && !db.is_in_recycle_bin(entry.parent_group))
+            .map(|entry| entry.uuid)
+            .collect();
+
+        Self { ratings, pwned }
+    }
+
+    pub fn quality(&self, entry_uuid: Uuid) -> Option<PasswordQuality> {
+        self.ratings
+            .iter()
+            .find(|(uuid, _)| *uuid == entry_uuid)
+            .map(|(_, quality)| *quality)
+    }
+
+    /// Number of entries rated with exactly this quality.
+    pub fn count(&self, quality: PasswordQuality) -> usize {
+        self.ratings.iter().filter(|(_, q)| *q == quality).count()
+    }
+
+    /// Number of entries with a Bad, Poor, or Weak password.
+    pub fn weak_count(&self) -> usize {
+        self.ratings.iter().filter(|(_, q)| q.is_weak()).count()
+    }
+
+    pub fn rated_count(&self) -> usize {
+        self.ratings.len()
+    }
+
+    /// Entries whose password is in the breached password list.
+    pub fn pwned_entries(&self) -> &[Uuid] {
+        &self.pwned
+    }
+
+    pub fn entries_with(&self, quality: PasswordQuality) -> Vec<Uuid> {
+        self.ratings
+            .iter()
+            .filter(|(_, q)| *q == quality)
+            .map(|(uuid, _)| *uuid)
+            .collect()
+    }
+}
+
+/// Entries that share a password, like the KeePassXC health check.
+/// Passwords are compared by a hash keyed with a subkey of the master
+/// key, so no plaintext is kept around. Entries in the recycle bin
+/// are left out.
+#[derive(Default, Clone)]
+pub struct RxPasswordReuse {
+    /// Each cluster has at least two entries. Largest first.
+    clusters: Vec<Vec<Uuid>>,
+}
+
+impl RxPasswordReuse {
+    pub fn new(db: &RxDatabase) -> Result<Self> {
+        let hash_key = db.master_key().derive_subkey(REUSE_HASH_KEY_ID)?;
+        let mut by_hash: IndexMap<Vec<u8>, Vec<Uuid>> = IndexMap::new();
+
+        let entries = db
+            .all_entries_iter()
+            .filter(|entry| !db.is_in_recycle_bin(entry.parent_group));
+
+        for entry in entries {
+            let password = match entry.password().and_then(|p| p.value_secure()) {
+                Some(password) if !password.is_empty() => password,
+                _ => continue,
+            };
+
+            let hash = crypto_generichash::generichash(
+                &password[..],
+                Some(&hash_key[..]),
+                crypto_generichash::BYTES,
+            )?;
+
+            by_hash.entry(hash).or_default().push(entry.uuid);
+        }
+
+        let mut clusters: Vec<_> = by_hash
+            .into_values()
+            .filter(|entry_uuids| entry_uuids.len() > 1)
+            .collect();
+
+        clusters.sort_by_key(|entry_uuids| Reverse(entry_uuids.len()));
+        Ok(Self { clusters })
+    }
+
+    pub fn clusters(&self) -> &[Vec<Uuid>] {
+        &self.clusters
+    }
+
+    pub fn is_reused(&self, entry_uuid: Uuid) -> bool {
+        self.clusters
+            .iter()
+            .any(|entry_uuids| entry_uuids.contains(&entry_uuid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rx::test_support::{keepass_db, load_db};
+    use keepass::db::{Entry, Group, Node, Value};
+    use secstr::SecStr;
+
+    fn entry_with_password(password: &str) -> Entry {
+        let mut entry = Entry::new();
+        entry.fields.insert(
+            "Password".to_string(),
+            Value::Protected(SecStr::from(password)),
+        );
+        entry
+    }
+
+    #[test]
+    fn rates_entries_outside_recycle_bin() {
+        let mut root = Group::new("root");
+        let mut bin = Group::new("Recycle Bin");
+        let bin_id = bin.uuid;
+
+        let weak = entry_with_password("password");
+        let weak_id = weak.uuid;
+        let strong = entry_with_password("correct-Horse-battery-staple-Vortex-91-quill");
+        let strong_id = strong.uuid;
+        let empty = Entry::new();
+        let empty_id = empty.uuid;
+        let binned = entry_with_password("password");
+        let binned_id = binned.uuid;
+
+        bin.add_child(Node::Entry(binned));
+        root.add_child(Node::Entry(weak));
+        root.add_child(Node::Entry(strong));
+        root.add_child(Node::Entry(empty));
+        root.add_child(Node::Group(bin));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_uuid = Some(bin_id);
+
+        let rx_db = load_db(db);
+        let health = RxPasswordHealth::new(&rx_db);
+
+        assert_eq!(health.rated_count(), 2);
+        assert_eq!(health.weak_count(), 1);
+        assert!(health.quality(weak_id).is_some_and(|q| q.is_weak()));
+        assert!(health.quality(strong_id).is_some_and(|q| !q.is_weak()));
+        assert_eq!(health.quality(empty_id), None);
+        assert_eq!(health.quality(binned_id), None);
+
+        assert!(rx_db.get_entry(weak_id).unwrap().is_password_weak());
+        assert!(!rx_db.get_entry(empty_id).unwrap().is_password_weak());
+    }
+
+    #[test]
+    fn finds_reused_passwords() {
+        let mut root = Group::new("root");
+        let mut bin = Group::new("Recycle Bin");
+        let bin_id = bin.uuid;
+
+        let first = entry_with_password("hunter2");
+        let first_id = first.uuid;
+        let second = entry_with_password("hunter2");
+        let second_id = second.uuid;
+        let unique = entry_with_password("something else");
+        let unique_id = unique.uuid;
+
+        bin.add_child(Node::Entry(entry_with_password("hunter2")));
+        root.add_child(Node::Entry(first));
+        root.add_child(Node::Entry(second));
+        root.add_child(Node::Entry(unique));
+        root.add_child(Node::Entry(Entry::new()));
+        root.add_child(Node::Entry(Entry::new()));
+        root.add_child(Node::Group(bin));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_uuid = Some(bin_id);
+
+        let rx_db = load_db(db);
+        let reuse = RxPasswordReuse::new(&rx_db).expect("Could not check for reuse");
+
+        assert_eq!(reuse.clusters(), &[vec![first_id, second_id]]);
+        assert!(reuse.is_reused(first_id));
+        assert!(!reuse.is_reused(unique_id));
+    }
+}
@@ -0,0 +1,376 @@
+use anyhow::{Result, anyhow};
+use keepass::db::{Entry, Value};
+use secstr::SecStr;
+use std::fs::File;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use super::{RxCsvColumn, read_1pux, read_bitwarden_json, read_csv};
+
+/// Standard KeePass field names, as imported entries are built
+/// with them.
+pub(crate) const TITLE_FIELD: &str = "Title";
+pub(crate) const USERNAME_FIELD: &str = "UserName";
+pub(crate) const PASSWORD_FIELD: &str = "Password";
+pub(crate) const URL_FIELD: &str = "URL";
+pub(crate) const NOTES_FIELD: &str = "Notes";
+pub(crate) const OTP_FIELD: &str = "otp";
+
+/// Text read from an export that may be a secret. Wiped when dropped.
+pub(crate) type Secret = Option<Zeroizing<String>>;
+
+/// The text of a secret, or nothing if it is missing.
+pub(crate) fn text(value: &Secret) -> &str {
+    value.as_deref().map_or("", String::as_str)
+}
+
+/// Extra URLs go in numbered fields, like KeePassXC and KeePass2Android
+/// do it.
+const EXTRA_URL_FIELD_PREFIX: &str = "KP2A_URL_";
+
+/// A KeePassDX-style template for entries that are not logins, like
+/// payment cards. Each field has a name, and whether it is protected.
+pub struct RxImportTemplate {
+    pub name: &'static str,
+    pub fields: &'static [(&'static str, bool)],
+}
+
+pub(crate) const CARD_TEMPLATE: RxImportTemplate = RxImportTemplate {
+    name: "Payment Card",
+    fields: &[
+        ("Cardholder", false),
+        ("Brand", false),
+        ("Number", true),
+        ("Expiration", false),
+        ("CVV", true),
+    ],
+};
+
+pub(crate) const IDENTITY_TEMPLATE: RxImportTemplate = RxImportTemplate {
+    name: "Identity",
+    fields: &[
+        ("Honorific", false),
+        ("First Name", false),
+        ("Middle Name", false),
+        ("Last Name", false),
+        ("Company", false),
+        ("Email", false),
+        ("Phone", false),
+        ("Address 1", false),
+        ("Address 2", false),
+        ("Address 3", false),
+        ("City", false),
+        ("State", false),
+        ("Postal Code", false),
+        ("Country", false),
+        ("Social Security Number", true),
+        ("Passport Number", true),
+        ("License Number", true),
+    ],
+};
+
+/// An entry read from another password manager's export, before it
+/// is put in the database with RxDatabase::import_entries.
+#[derive(Default)]
+pub struct RxImportedEntry {
+    /// Names of the groups that the entry goes in, below the group
+    /// that is imported into. Missing groups are created.
+    pub group_path: Vec<String>,
+    pub tags: Vec<String>,
+
+    /// The template the entry is made from, if it is not a login.
+    pub template: Option<&'static RxImportTemplate>,
+    fields: Vec<(String, Zeroizing<String>, bool)>,
+    attachments: Vec<(String, Zeroizing<Vec<u8>>)>,
+}
+
+impl RxImportedEntry {
+    /// Set a field, replacing any earlier value. Empty values are
+    /// left out, like KeePassXC does.
+    pub fn set_field(&mut self, name: &str, value: &str, protected: bool) {
+        self.fields.retain(|(key, _, _)| key != name);
+
+        if !value.is_empty() {
+            let value = Zeroizing::new(value.to_string());
+            self.fields.push((name.to_string(), value, protected));
+        }
+    }
+
+    /// Add a custom field. If the name is taken, a number is added to
+    /// it, so that nothing is lost.
+    pub fn add_custom_field(&mut self, name: &str, value: &str, protected: bool) {
+        let name = match name.trim() {
+            "" => "Field",
+            name => name,
+        };
+
+        let unique_name = numbered_name(name, |name| self.field(name).is_some());
+        self.set_field(&unique_name, value, protected);
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _, _)| key == name)
+            .map(|(_, value, _)| value.as_str())
+    }
+
+    /// The first URL goes in the URL field, the rest in numbered
+    /// custom fields.
+    pub fn add_urls<'a>(&mut self, urls: impl IntoIterator<Item = &'a str>) {
+        let mut urls = urls
+            .into_iter()
+            .map(str::trim)
+            .filter(|url| !url.is_empty());
+
+        if self.field(URL_FIELD).is_none() {
+            if let Some(url) = urls.next() {
+                self.set_field(URL_FIELD, url, false);
+            }
+        }
+
+        for url in urls {
+            let mut number = 1;
+            while self
+                .field(&format!("{}{}", EXTRA_URL_FIELD_PREFIX, number))
+                .is_some()
+            {
+                number += 1;
+            }
+
+            self.set_field(&format!("{}{}", EXTRA_URL_FIELD_PREFIX, number), url, false);
+        }
+    }
+
+    /// Attach a file. If the name is taken by another attachment or
+    /// a field, a number is added to it.
+    pub fn add_attachment(&mut self, name: &str, data: Zeroizing<Vec<u8>>) {
+        let unique_name = numbered_name(name, |name| {
+            self.field(name).is_some() || self.attachments.iter().any(|(key, _)| key == name)
+        });
+
+        self.attachments.push((unique_name, data));
+    }
+
+    /// Whether there is anything worth importing.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.attachments.is_empty()
+    }
+
+    /// Fill in a missing title from the URL or the username, so the
+    /// entry can be found in the list.
+    pub fn ensure_title(&mut self) {
+        if self.field(TITLE_FIELD).is_some() {
+            return;
+        }
+
+        let title = self
+            .field(URL_FIELD)
+            .map(url_host)
+            .or(self.field(USERNAME_FIELD))
+            .unwrap_or_default()
+            .to_string();
+
+        self.set_field(TITLE_FIELD, &title, false);
+    }
+
+    /// A KeePass entry with these fields. The password and one-time
+    /// password are always protected.
+    pub(super) fn to_keepass(&self) -> Entry {
+        let mut entry = Entry::new();
+
+        for (name, value, protected) in &self.fields {
+            let protected = *protected || name == PASSWORD_FIELD || name == OTP_FIELD;
+
+            let value = match protected {
+                true => Value::Protected(SecStr::from(value.as_str())),
+                false => Value::Unprotected(value.to_string()),
+            };
+
+            entry.fields.insert(name.clone(), value);
+        }
+
+        // Attachments share names with the fields, which can be set
+        // after a file is attached.
+        for (name, data) in &self.attachments {
+            let name = numbered_name(name, |name| entry.fields.contains_key(name));
+            entry.fields.insert(name, Value::Bytes(data.to_vec()));
+        }
+
+        entry.tags = self.tags.clone();
+        entry
+    }
+}
+
+/// The name, with a number added to it if it is taken.
+fn numbered_name(name: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    let mut unique_name = name.to_string();
+    let mut number = 1;
+    while is_taken(&unique_name) {
+        number += 1;
+        unique_name = format!("{} ({})", name, number);
+    }
+
+    unique_name
+}
+
+/// The host of a URL, or the URL itself if it has none.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(rest)
+        .rsplit('@')
+        .next()
+        .unwrap_or(rest);
+
+    let host = host.split(':').next().unwrap_or(host);
+    match host.is_empty() {
+        true => url,
+        false => host.strip_prefix("www.").unwrap_or(host),
+    }
+}
+
+/// Something in an export that could not be imported. The position
+/// is the row of a CSV file, or the number of the item in other
+/// exports, counting from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RxImportError {
+    pub position: usize,
+    pub reason: String,
+}
+
+/// Everything read from an export: the entries to import, and what
+/// was skipped.
+#[derive(Default)]
+pub struct RxImport {
+    pub entries: Vec<RxImportedEntry>,
+    pub errors: Vec<RxImportError>,
+
+    /// Parts of imported entries that were left out, like files that
+    /// are missing from an archive.
+    pub warnings: Vec<RxImportError>,
+}
+
+impl RxImport {
+    pub(crate) fn skip(&mut self, position: usize, reason: impl Into<String>) {
+        self.errors.push(RxImportError {
+            position,
+            reason: reason.into(),
+        });
+    }
+
+    pub(crate) fn warn(&mut self, position: usize, reason: impl Into<String>) {
+        self.warnings.push(RxImportError {
+            position,
+            reason: reason.into(),
+        });
+    }
+}
+
+/// The kinds of export that can be imported.
+pub enum RxImportFormat {
+    /// CSV, with the given meaning for each column.
+    Csv(Vec<RxCsvColumn>),
+    /// Bitwarden's unencrypted JSON export.
+    BitwardenJson,
+    /// 1Password's 1PUX archive.
+    OnePux,
+}
+
+impl RxImportFormat {
+    /// Read the entries of an export file. Group paths in the export
+    /// that start with the root group's name have it left out.
+    pub fn read(&self, path: &Path, root_name: &str) -> Result<RxImport> {
+        match self {
+            RxImportFormat::Csv(columns) => {
+                let text = Zeroizing::new(std::fs::read_to_string(path)?);
+                read_csv(&text, columns, root_name)
+            }
+            RxImportFormat::BitwardenJson => {
+                let json = Zeroizing::new(std::fs::read(path)?);
+                read_bitwarden_json(&json)
+            }
+            RxImportFormat::OnePux => read_1pux(File::open(path)?),
+        }
+    }
+}
+
+impl TryFrom<&str> for RxImportFormat {
+    type Error = anyhow::Error;
+
+    /// Formats that need nothing more than the file. CSV needs its
+    /// columns mapped first.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "bitwarden" => Ok(RxImportFormat::BitwardenJson),
+            "1pux" => Ok(RxImportFormat::OnePux),
+            other => Err(anyhow!("Unknown import format {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_imported_entries() {
+        let mut entry = RxImportedEntry::default();
+        entry.set_field(USERNAME_FIELD, "alice", false);
+        entry.set_field(PASSWORD_FIELD, "hunter2", false);
+        entry.add_urls(["https://www.example.com/login", "", "https://example.org"]);
+        entry.add_custom_field("PIN", "1234", true);
+        entry.add_custom_field("PIN", "5678", true);
+        entry.ensure_title();
+
+        assert_eq!(entry.field(TITLE_FIELD), Some("example.com"));
+        assert_eq!(entry.field("KP2A_URL_1"), Some("https://example.org"));
+        assert_eq!(entry.field("PIN (2)"), Some("5678"));
+
+        let kp_entry = entry.to_keepass();
+        assert!(matches!(
+            kp_entry.fields.get(PASSWORD_FIELD),
+            Some(Value::Protected(_))
+        ));
+        assert!(matches!(
+            kp_entry.fields.get(USERNAME_FIELD),
+            Some(Value::Unprotected(_))
+        ));
+    }
+
+    #[test]
+    fn keeps_attachments_apart_from_fields() {
+        let mut entry = RxImportedEntry::default();
+        entry.add_custom_field("key.txt", "not a file", false);
+        entry.add_attachment("key.txt", Zeroizing::new(b"first".to_vec()));
+        entry.add_attachment("key.txt", Zeroizing::new(b"second".to_vec()));
+        entry.add_attachment("notes.txt", Zeroizing::new(b"third".to_vec()));
+
+        // Set after the file was attached.
+        entry.add_custom_field("notes.txt", "a field", false);
+
+        let kp_entry = entry.to_keepass();
+        assert!(matches!(
+            kp_entry.fields.get("key.txt"),
+            Some(Value::Unprotected(value)) if value == "not a file"
+        ));
+        assert!(matches!(
+            kp_entry.fields.get("key.txt (2)"),
+            Some(Value::Bytes(data)) if data == b"first"
+        ));
+        assert!(matches!(
+            kp_entry.fields.get("key.txt (3)"),
+            Some(Value::Bytes(data)) if data == b"second"
+        ));
+        assert!(matches!(
+            kp_entry.fields.get("notes.txt"),
+            Some(Value::Unprotected(value)) if value == "a field"
+        ));
+        assert!(matches!(
+            kp_entry.fields.get("notes.txt (2)"),
+            Some(Value::Bytes(data)) if data == b"third"
+        ));
+    }
+}
@@ -3,7 +3,8 @@ use crate::crypto::MasterKey;
 use super::{RxEntry, RxGroup, RxMetadata, RxTemplate, ZeroableDatabase};
 use anyhow::Result;
 use indexmap::IndexMap;
-use keepass::db::{Group, Icon, Node};
+use keepass::config::DatabaseConfig;
+use keepass::db::{DeletedObjects, Group, HeaderAttachment, Icon, Meta, Node};
 use std::collections::HashMap;
 use std::mem;
 use std::rc::Rc;
@@ -22,6 +23,12 @@ pub struct Loaded {
     pub state: LoadState,
     pub metadata: RxMetadata,
     pub master_key: Rc<MasterKey>,
+
+    /// Kept around so the database can be written back out.
+    pub config: DatabaseConfig,
+    pub meta: Meta,
+    pub header_attachments: Vec<HeaderAttachment>,
+    pub deleted_objects: DeletedObjects,
 }
 
 impl RxLoader {
@@ -63,10 +70,11 @@ impl RxLoader {
             .all_groups
             .insert(root_group.uuid, Rc::new(root_group));
 
-        let rx_metadata = RxMetadata::new(
-            mem::take(&mut self.db().config),
-            mem::take(&mut self.db().meta),
-        );
+        let config = mem::take(&mut self.db().config);
+        let meta = mem::take(&mut self.db().meta);
+        let rx_metadata = RxMetadata::new(&config, &meta);
+        let header_attachments = mem::take(&mut self.db().header_attachments);
+        let deleted_objects = mem::take(&mut self.db().deleted_objects);
 
         self.db.zeroize();
 
@@ -75,6 +83,10 @@ impl RxLoader {
             state: self.state,
             metadata: rx_metadata,
             root_uuid: root_uuid,
+            config: config,
+            meta: meta,
+            header_attachments: header_attachments,
+            deleted_objects: deleted_objects,
         })
     }
 
@@ -93,9 +105,8 @@ impl RxLoader {
         for node in children.into_iter() {
             match node {
                 Node::Group(mut subgroup) => {
-                    let subgroup_id = subgroup.uuid;
                     let rx_subgroup =
-                        self.load_groups_recursive(&mut subgroup, Some(subgroup_id));
+                        self.load_groups_recursive(&mut subgroup, Some(group.uuid));
 
                     subgroups.push(rx_subgroup);
                 }
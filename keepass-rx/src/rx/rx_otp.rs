@@ -0,0 +1,828 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use image::{ImageFormat, Luma};
+use qrcode::QrCode;
+use qrcode::render::svg;
+use querystring::querify;
+use std::fmt;
+use std::io::Cursor;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use totp_rs::{Algorithm, Secret, TOTP};
+use zeroize::Zeroizing;
+
+/// Fields used by older KeePassXC versions (and KeeOtp) for the TOTP
+/// secret and its settings, before the otp field held a URI.
+pub(crate) const LEGACY_TOTP_SEED_FIELD: &str = "TOTP Seed";
+pub(crate) const LEGACY_TOTP_SETTINGS_FIELD: &str = "TOTP Settings";
+
+/// Digits value in the legacy settings that means a Steam code.
+const LEGACY_STEAM_DIGITS: &str = "S";
+
+const DEFAULT_PERIOD: u64 = 30;
+const DEFAULT_DIGITS: u32 = 6;
+const STEAM_DIGITS: u32 = 5;
+
+/// Digit counts that codes can have. Fewer than 6 is not allowed by
+/// RFC 4226, and more than 8 is not allowed by totp-rs (which panics
+/// on 10).
+const SUPPORTED_DIGITS: std::ops::RangeInclusive<u32> = 6..=8;
+
+/// Prefix of the URI of Steam codes, which are generated differently.
+pub(crate) const STEAM_URI_PREFIX: &str = "otpauth://totp/Steam:";
+
+pub(crate) const HOTP_URI_PREFIX: &str = "otpauth://hotp/";
+
+/// Label of URIs built from fields, which have no label of their own.
+/// Replaced by the entry's title when exporting.
+const PLACEHOLDER_LABEL: &str = "KeePassRX";
+
+/// Smallest size of generated QR codes, in pixels.
+const QR_CODE_SIZE: u32 = 256;
+
+/// KeePass 2.x keeps HMAC-based one-time passwords in fields starting
+/// with this. The secret is in one of HOTP_SECRET_FIELDS, depending
+/// on how it is encoded.
+pub(crate) const HOTP_FIELD_PREFIX: &str = "HmacOtp-";
+pub(crate) const HOTP_COUNTER_FIELD: &str = "HmacOtp-Counter";
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SecretEncoding {
+    Utf8,
+    Hex,
+    Base32,
+    Base64,
+}
+
+pub(crate) const HOTP_SECRET_FIELDS: [(&str, SecretEncoding); 4] = [
+    ("HmacOtp-Secret", SecretEncoding::Utf8),
+    ("HmacOtp-Secret-Hex", SecretEncoding::Hex),
+    ("HmacOtp-Secret-Base32", SecretEncoding::Base32),
+    ("HmacOtp-Secret-Base64", SecretEncoding::Base64),
+];
+
+/// Base32 secrets are case insensitive, and are often written with
+/// spaces or padding.
+fn clean_secret(secret: &str) -> Zeroizing<String> {
+    Zeroizing::new(
+        secret
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '=')
+            .collect::<String>()
+            .to_uppercase(),
+    )
+}
+
+fn totp_uri(
+    secret: &str,
+    period: u64,
+    digits: u32,
+    algorithm: Option<&str>,
+    steam: bool,
+) -> Option<Zeroizing<String>> {
+    let secret = clean_secret(secret);
+    if secret.is_empty() {
+        return None;
+    }
+
+    let mut uri = match steam {
+        true => format!(
+            "{}Steam?secret={}&period={}&digits={}&issuer=Steam&encoder=steam",
+            STEAM_URI_PREFIX, *secret, period, STEAM_DIGITS
+        ),
+        false => format!(
+            "otpauth://totp/{}?secret={}&period={}&digits={}",
+            PLACEHOLDER_LABEL, *secret, period, digits
+        ),
+    };
+
+    if let Some(algorithm) = algorithm.filter(|_| !steam) {
+        uri.push_str(&format!("&algorithm={}", algorithm.to_uppercase()));
+    }
+
+    Some(Zeroizing::new(uri))
+}
+
+/// Build an otpauth URI from the legacy TOTP Seed and TOTP Settings
+/// fields. The settings are "period;digits", where the digits can be
+/// S for Steam. Missing settings default to 30 seconds and 6 digits,
+/// like KeePassXC.
+pub(crate) fn legacy_totp_uri(
+    seed: &str,
+    settings: Option<&str>,
+) -> Option<Zeroizing<String>> {
+    let mut settings = settings.unwrap_or_default().split(';').map(str::trim);
+
+    let period = settings
+        .next()
+        .and_then(|period| period.parse::<u64>().ok())
+        .filter(|period| *period > 0)
+        .unwrap_or(DEFAULT_PERIOD);
+
+    match settings.next() {
+        Some(LEGACY_STEAM_DIGITS) => totp_uri(seed, period, STEAM_DIGITS, None, true),
+        digits => {
+            let digits = digits
+                .and_then(|digits| digits.parse::<u32>().ok())
+                .filter(|digits| SUPPORTED_DIGITS.contains(digits))
+                .unwrap_or(DEFAULT_DIGITS);
+
+            totp_uri(seed, period, digits, None, false)
+        }
+    }
+}
+
+/// Normalize the value of an otp field into an otpauth URI. Besides
+/// URIs, this understands the KeeOtp format that some databases have
+/// in the otp field (key=SECRET&step=30&size=6&otpHashMode=Sha256).
+pub(crate) fn normalize_otp_value(raw: &str) -> Option<Zeroizing<String>> {
+    // Other schemes (motp://, yaotp://) are kept as they are, so they
+    // can be reported as unsupported.
+    let raw = raw.trim();
+    if raw.contains("://") {
+        return Some(Zeroizing::new(raw.to_string()));
+    }
+
+    let params = querify(raw);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find_map(|(key, value)| (*key == name).then_some(*value))
+    };
+
+    let secret = param("key")?;
+    let period = param("step")
+        .and_then(|step| step.parse::<u64>().ok())
+        .filter(|step| *step > 0)
+        .unwrap_or(DEFAULT_PERIOD);
+    let digits = param("size")
+        .and_then(|size| size.parse::<u32>().ok())
+        .filter(|size| SUPPORTED_DIGITS.contains(size))
+        .unwrap_or(DEFAULT_DIGITS);
+
+    totp_uri(secret, period, digits, param("otpHashMode"), false)
+}
+
+/// Why a one-time password cannot be used. These are OTPs that are
+/// valid, but of a kind that codes cannot be generated for, so the UI
+/// can tell the user instead of showing a parse error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RxOtpError {
+    /// Yandex Key, mOTP and other schemes besides TOTP and HOTP.
+    UnsupportedType(String),
+    UnsupportedAlgorithm(String),
+    UnsupportedDigits(String),
+    UnsupportedPeriod(String),
+    MissingSecret,
+}
+
+impl fmt::Display for RxOtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RxOtpError::UnsupportedType(kind) => {
+                write!(f, "{} one-time passwords are not supported", kind)
+            }
+            RxOtpError::UnsupportedAlgorithm(algorithm) => {
+                write!(
+                    f,
+                    "One-time password algorithm {} is not supported",
+                    algorithm
+                )
+            }
+            RxOtpError::UnsupportedDigits(digits) => {
+                write!(
+                    f,
+                    "One-time passwords with {} digits are not supported",
+                    digits
+                )
+            }
+            RxOtpError::UnsupportedPeriod(period) => {
+                write!(f, "One-time password period {} is not supported", period)
+            }
+            RxOtpError::MissingSecret => write!(f, "One-time password has no secret"),
+        }
+    }
+}
+
+impl std::error::Error for RxOtpError {}
+
+fn parse_algorithm(name: &str) -> Result<Algorithm, RxOtpError> {
+    match name.to_uppercase().as_str() {
+        "SHA1" => Ok(Algorithm::SHA1),
+        "SHA256" => Ok(Algorithm::SHA256),
+        "SHA512" => Ok(Algorithm::SHA512),
+        _ => Err(RxOtpError::UnsupportedAlgorithm(name.to_string())),
+    }
+}
+
+fn algorithm_name(algorithm: Algorithm) -> &'static str {
+    match algorithm {
+        Algorithm::SHA256 => "SHA256",
+        Algorithm::SHA512 => "SHA512",
+        Algorithm::Steam => "Steam",
+        _ => "SHA1",
+    }
+}
+
+/// Turn the TOTP value of another password manager's export into an
+/// otpauth URI. Some export a URI, others just the secret, and
+/// Bitwarden writes Steam secrets as steam://SECRET. Secrets that are
+/// not valid base32 give None.
+pub(crate) fn imported_otp_uri(value: &str) -> Option<Zeroizing<String>> {
+    let value = value.trim();
+    if value.starts_with("otpauth://") || value.starts_with("key=") {
+        return normalize_otp_value(value);
+    }
+
+    let (secret, steam) = match value.strip_prefix("steam://") {
+        Some(secret) => (secret, true),
+        None => (value, false),
+    };
+
+    decode_secret(secret, SecretEncoding::Base32).ok()?;
+    totp_uri(secret, DEFAULT_PERIOD, DEFAULT_DIGITS, None, steam)
+}
+
+fn decode_secret(secret: &str, encoding: SecretEncoding) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes = match encoding {
+        SecretEncoding::Utf8 => secret.as_bytes().to_vec(),
+        SecretEncoding::Hex => {
+            let secret = clean_secret(secret);
+            if !secret.is_ascii() || secret.len() % 2 != 0 {
+                return Err(anyhow!("Hex secret is not valid"));
+            }
+
+            (0..secret.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&secret[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| anyhow!("Hex secret has invalid digits"))?
+        }
+        SecretEncoding::Base32 => {
+            Secret::Encoded(clean_secret(secret).to_string()).to_bytes()?
+        }
+        SecretEncoding::Base64 => BASE64_STANDARD.decode(secret.trim())?,
+    };
+
+    Ok(Zeroizing::new(bytes))
+}
+
+/// A counter-based one-time password (RFC 4226). The counter is the
+/// one that the next code is generated from.
+pub struct RxHotp {
+    secret: Zeroizing<Vec<u8>>,
+    algorithm: Algorithm,
+    pub counter: u64,
+    pub digits: usize,
+}
+
+impl RxHotp {
+    /// Read an otpauth://hotp URI. The counter is required, like in
+    /// the Key Uri Format.
+    pub(crate) fn from_uri(uri: &str) -> Result<Self> {
+        let query = uri
+            .strip_prefix(HOTP_URI_PREFIX)
+            .and_then(|rest| rest.split_once('?'))
+            .map(|(_, query)| query)
+            .ok_or(anyhow!("Not an HOTP URI"))?;
+
+        let params = querify(query);
+        let param = |name: &str| {
+            params
+                .iter()
+                .find_map(|(key, value)| (*key == name).then_some(*value))
+        };
+
+        let secret = param("secret").ok_or(anyhow!("No secret in HOTP URI"))?;
+        let counter = param("counter").ok_or(anyhow!("No counter in HOTP URI"))?;
+
+        let algorithm = parse_algorithm(param("algorithm").unwrap_or("SHA1"))?;
+
+        let digits = match param("digits") {
+            Some(digits) => digits
+                .parse::<u32>()
+                .ok()
+                .filter(|digits| SUPPORTED_DIGITS.contains(digits))
+                .ok_or(RxOtpError::UnsupportedDigits(digits.to_string()))?,
+            None => DEFAULT_DIGITS,
+        };
+
+        Ok(Self {
+            secret: decode_secret(secret, SecretEncoding::Base32)?,
+            algorithm,
+            counter: counter.parse()?,
+            digits: digits as usize,
+        })
+    }
+
+    /// Read the KeePass HmacOtp fields. A missing counter starts at 0.
+    pub(crate) fn from_fields(
+        secret: &str,
+        encoding: SecretEncoding,
+        counter: Option<&str>,
+    ) -> Result<Self> {
+        let counter = counter
+            .map(|counter| counter.trim().parse::<u64>())
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok(Self {
+            secret: decode_secret(secret, encoding)?,
+            algorithm: Algorithm::SHA1,
+            counter,
+            digits: DEFAULT_DIGITS as usize,
+        })
+    }
+
+    /// An otpauth://hotp URI, for moving the HOTP to another app.
+    pub(crate) fn to_uri(&self, label: &str) -> Zeroizing<String> {
+        let secret =
+            Zeroizing::new(Secret::Raw(self.secret.to_vec()).to_encoded().to_string());
+
+        Zeroizing::new(format!(
+            "{}{}?secret={}&counter={}&digits={}&algorithm={}",
+            HOTP_URI_PREFIX,
+            encode_label(label),
+            *secret,
+            self.counter,
+            self.digits,
+            self.algorithm_name()
+        ))
+    }
+
+    pub fn algorithm_name(&self) -> &'static str {
+        algorithm_name(self.algorithm)
+    }
+
+    /// The code for the current counter. HOTP is TOTP with the
+    /// counter in place of the time step, so a step of one second
+    /// turns the counter into the "time".
+    pub fn code(&self) -> String {
+        TOTP::new_unchecked(self.algorithm, self.digits, 0, 1, self.secret.to_vec())
+            .generate(self.counter)
+    }
+}
+
+/// A time-based one-time password (RFC 6238), as read from an
+/// otpauth://totp URI.
+pub struct RxTotpParams {
+    secret: Zeroizing<Vec<u8>>,
+    algorithm: Algorithm,
+    pub digits: usize,
+    pub period: u64,
+}
+
+impl RxTotpParams {
+    /// Read an otpauth://totp URI. Steam codes are recognized by
+    /// their label or by KeePassXC's encoder parameter. Anything that
+    /// codes cannot be generated for is an RxOtpError.
+    pub(crate) fn from_uri(uri: &str) -> Result<Self> {
+        let (scheme, rest) = uri.split_once("://").unwrap_or(("", uri));
+        let (kind, rest) = match scheme {
+            "otpauth" => rest.split_once('/').unwrap_or((rest, "")),
+            other => (other, rest),
+        };
+
+        match kind.to_lowercase().as_str() {
+            "totp" => (),
+            "yaotp" => return Err(RxOtpError::UnsupportedType("Yandex".to_string()).into()),
+            "motp" => return Err(RxOtpError::UnsupportedType("mOTP".to_string()).into()),
+            other => return Err(RxOtpError::UnsupportedType(other.to_string()).into()),
+        }
+
+        let query = rest
+            .split_once('?')
+            .map(|(_, query)| query)
+            .unwrap_or_default();
+        let params = querify(query);
+        let param = |name: &str| {
+            params
+                .iter()
+                .find_map(|(key, value)| (*key == name).then_some(*value))
+        };
+
+        let steam = match param("encoder") {
+            None => uri.starts_with(STEAM_URI_PREFIX),
+            Some(encoder) if encoder.eq_ignore_ascii_case("steam") => true,
+            Some(encoder) if encoder.eq_ignore_ascii_case("yandex") => {
+                return Err(RxOtpError::UnsupportedType("Yandex".to_string()).into());
+            }
+            Some(encoder) => {
+                return Err(RxOtpError::UnsupportedType(encoder.to_string()).into());
+            }
+        };
+
+        let secret = param("secret")
+            .filter(|secret| !secret.trim().is_empty())
+            .ok_or(RxOtpError::MissingSecret)?;
+        let secret = decode_secret(secret, SecretEncoding::Base32)?;
+
+        // Steam codes always use the same settings, whatever the URI
+        // says.
+        if steam {
+            return Ok(Self {
+                secret,
+                algorithm: Algorithm::Steam,
+                digits: STEAM_DIGITS as usize,
+                period: DEFAULT_PERIOD,
+            });
+        }
+
+        let algorithm = parse_algorithm(param("algorithm").unwrap_or("SHA1"))?;
+
+        let digits = match param("digits") {
+            Some(digits) => digits
+                .parse::<u32>()
+                .ok()
+                .filter(|digits| SUPPORTED_DIGITS.contains(digits))
+                .ok_or(RxOtpError::UnsupportedDigits(digits.to_string()))?,
+            None => DEFAULT_DIGITS,
+        };
+
+        let period = match param("period") {
+            Some(period) => period
+                .parse::<u64>()
+                .ok()
+                .filter(|period| *period > 0)
+                .ok_or(RxOtpError::UnsupportedPeriod(period.to_string()))?,
+            None => DEFAULT_PERIOD,
+        };
+
+        Ok(Self {
+            secret,
+            algorithm,
+            digits: digits as usize,
+            period,
+        })
+    }
+
+    pub fn algorithm_name(&self) -> &'static str {
+        algorithm_name(self.algorithm)
+    }
+
+    pub fn is_steam(&self) -> bool {
+        self.algorithm == Algorithm::Steam
+    }
+
+    /// The code for a time, in seconds since the epoch.
+    pub fn code_at(&self, time: u64) -> String {
+        match self.is_steam() {
+            true => TOTP::new_steam(self.secret.to_vec()).generate(time),
+            false => TOTP::new_unchecked(
+                self.algorithm,
+                self.digits,
+                1,
+                self.period,
+                self.secret.to_vec(),
+            )
+            .generate(time),
+        }
+    }
+
+    /// Seconds that the code for a time is still valid for.
+    pub fn remaining_at(&self, time: u64) -> u64 {
+        self.period - time % self.period
+    }
+}
+
+/// Percent-encode a part of an otpauth URI.
+pub(crate) fn encode_component(component: &str) -> String {
+    component
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Percent-encode the label of an otpauth URI. The colon between
+/// issuer and account name is kept.
+pub(crate) fn encode_label(label: &str) -> String {
+    label
+        .split(':')
+        .map(encode_component)
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Give a URI built from fields a proper label, so the other app can
+/// tell it apart from the rest. URIs with a label are left alone.
+pub(crate) fn with_label(uri: &str, label: &str) -> Zeroizing<String> {
+    for prefix in ["otpauth://totp/", HOTP_URI_PREFIX] {
+        let placeholder = format!("{}{}?", prefix, PLACEHOLDER_LABEL);
+        if let Some(query) = uri.strip_prefix(&placeholder) {
+            return Zeroizing::new(format!("{}{}?{}", prefix, encode_label(label), query));
+        }
+    }
+
+    Zeroizing::new(uri.to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RxQrFormat {
+    Png,
+    Svg,
+}
+
+impl RxQrFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            RxQrFormat::Png => "image/png",
+            RxQrFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+impl TryFrom<&str> for RxQrFormat {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "png" => Ok(RxQrFormat::Png),
+            "svg" => Ok(RxQrFormat::Svg),
+            other => Err(anyhow!("Unknown QR code format {}", other)),
+        }
+    }
+}
+
+/// Render the data (usually an otpauth URI) as a QR code image. The
+/// image holds the secret, so it is wiped when dropped.
+pub fn qr_code(data: &str, format: RxQrFormat) -> Result<Zeroizing<Vec<u8>>> {
+    let code = QrCode::new(data.as_bytes())?;
+
+    let image = match format {
+        RxQrFormat::Svg => code
+            .render::<svg::Color>()
+            .min_dimensions(QR_CODE_SIZE, QR_CODE_SIZE)
+            .build()
+            .into_bytes(),
+        RxQrFormat::Png => {
+            let image = code
+                .render::<Luma<u8>>()
+                .min_dimensions(QR_CODE_SIZE, QR_CODE_SIZE)
+                .build();
+
+            let mut png = Vec::new();
+            image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            png
+        }
+    };
+
+    Ok(Zeroizing::new(image))
+}
+
+/// Time left until codes with this period roll over. More precise
+/// than the remaining seconds of an RxTotp, for scheduling refreshes.
+pub fn until_rollover(period: u64) -> Duration {
+    let period_millis = period.max(1) * 1000;
+    let now_millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    Duration::from_millis(period_millis - now_millis % period_millis)
+}
+
+/// Which time step codes with this period are in right now. Codes
+/// change when the step does.
+pub fn current_time_step(period: u64) -> u64 {
+    unix_time() / period.max(1)
+}
+
+/// Seconds since the epoch, which is the time that TOTP codes are
+/// generated for.
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Replace the counter in an otpauth://hotp URI, keeping everything
+/// else as it was.
+pub(crate) fn with_hotp_counter(uri: &str, counter: u64) -> Zeroizing<String> {
+    let (base, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let mut params = Zeroizing::new(
+        query
+            .split('&')
+            .filter(|param| !param.is_empty() && !param.starts_with("counter="))
+            .map(str::to_string)
+            .collect::<Vec<_>>(),
+    );
+
+    params.push(format!("counter={}", counter));
+    Zeroizing::new(format!("{}?{}", base, params.join("&")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_legacy_fields() {
+        let uri = legacy_totp_uri("jbsw y3dp ehpk 3pxp", Some("60;8")).unwrap();
+        assert_eq!(
+            uri.as_str(),
+            "otpauth://totp/KeePassRX?secret=JBSWY3DPEHPK3PXP&period=60&digits=8"
+        );
+
+        let uri = legacy_totp_uri("JBSWY3DPEHPK3PXP", None).unwrap();
+        assert_eq!(
+            uri.as_str(),
+            "otpauth://totp/KeePassRX?secret=JBSWY3DPEHPK3PXP&period=30&digits=6"
+        );
+
+        let uri = legacy_totp_uri("JBSWY3DPEHPK3PXP", Some("30;S")).unwrap();
+        assert!(uri.starts_with(STEAM_URI_PREFIX));
+
+        // Digits that codes cannot be generated with fall back to 6.
+        let uri = legacy_totp_uri("JBSWY3DPEHPK3PXP", Some("30;10")).unwrap();
+        assert!(uri.ends_with("&digits=6"));
+
+        assert!(legacy_totp_uri("  ", Some("30;6")).is_none());
+    }
+
+    #[test]
+    fn converts_keeotp_value() {
+        let uri = normalize_otp_value("key=JBSWY3DPEHPK3PXP&step=45&size=7").unwrap();
+        assert_eq!(
+            uri.as_str(),
+            "otpauth://totp/KeePassRX?secret=JBSWY3DPEHPK3PXP&period=45&digits=7"
+        );
+
+        let existing = "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP";
+        assert_eq!(normalize_otp_value(existing).unwrap().as_str(), existing);
+        assert!(normalize_otp_value("not an otp").is_none());
+    }
+
+    #[test]
+    fn honors_totp_parameters() {
+        // RFC 6238 test vectors, at 59 seconds.
+        let sha1 = "otpauth://totp/A?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&digits=8";
+        let sha256 = "otpauth://totp/A?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA&algorithm=SHA256&digits=8";
+        let sha512 = "otpauth://totp/A?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNA&algorithm=sha512&digits=7&period=30";
+
+        let codes: Vec<_> = [sha1, sha256, sha512]
+            .iter()
+            .map(|uri| {
+                RxTotpParams::from_uri(uri)
+                    .expect("Could not read TOTP URI")
+                    .code_at(59)
+            })
+            .collect();
+        assert_eq!(codes, ["94287082", "46119246", "0693936"]);
+
+        let params = RxTotpParams::from_uri(sha512).unwrap();
+        assert_eq!(params.algorithm_name(), "SHA512");
+        assert_eq!(params.digits, 7);
+        assert_eq!(params.remaining_at(59), 1);
+
+        let uri =
+            normalize_otp_value("key=JBSWY3DPEHPK3PXP&size=8&otpHashMode=Sha256").unwrap();
+        let params = RxTotpParams::from_uri(&uri).unwrap();
+        assert_eq!(params.algorithm_name(), "SHA256");
+        assert_eq!(params.digits, 8);
+
+        let steam = "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP&encoder=steam";
+        assert!(RxTotpParams::from_uri(steam).unwrap().is_steam());
+    }
+
+    #[test]
+    fn rejects_unsupported_otps() {
+        let otp_error = |uri: &str| {
+            RxTotpParams::from_uri(uri)
+                .err()
+                .and_then(|err| err.downcast_ref::<RxOtpError>().cloned())
+        };
+
+        let yandex = RxOtpError::UnsupportedType("Yandex".to_string());
+        assert_eq!(
+            otp_error("otpauth://yaotp/Yandex?secret=JBSWY3DPEHPK3PXP"),
+            Some(yandex.clone())
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/Yandex?secret=JBSWY3DPEHPK3PXP&encoder=yandex"),
+            Some(yandex)
+        );
+        assert_eq!(
+            otp_error("motp://Example?secret=1234567890abcdef"),
+            Some(RxOtpError::UnsupportedType("mOTP".to_string()))
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/A?secret=JBSWY3DPEHPK3PXP&algorithm=MD5"),
+            Some(RxOtpError::UnsupportedAlgorithm("MD5".to_string()))
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/A?secret=JBSWY3DPEHPK3PXP&digits=4"),
+            Some(RxOtpError::UnsupportedDigits("4".to_string()))
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/A?secret=JBSWY3DPEHPK3PXP&digits=10"),
+            Some(RxOtpError::UnsupportedDigits("10".to_string()))
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/A?secret=JBSWY3DPEHPK3PXP&period=0"),
+            Some(RxOtpError::UnsupportedPeriod("0".to_string()))
+        );
+        assert_eq!(
+            otp_error("otpauth://totp/A?digits=6"),
+            Some(RxOtpError::MissingSecret)
+        );
+    }
+
+    #[test]
+    fn converts_imported_otp_values() {
+        assert_eq!(
+            imported_otp_uri("jbsw y3dp ehpk 3pxp").unwrap().as_str(),
+            "otpauth://totp/KeePassRX?secret=JBSWY3DPEHPK3PXP&period=30&digits=6"
+        );
+
+        let steam = imported_otp_uri("steam://JBSWY3DPEHPK3PXP").unwrap();
+        assert!(RxTotpParams::from_uri(&steam).unwrap().is_steam());
+
+        let existing = "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP";
+        assert_eq!(imported_otp_uri(existing).unwrap().as_str(), existing);
+        assert!(imported_otp_uri("not base32!").is_none());
+    }
+
+    #[test]
+    fn generates_hotp_codes() {
+        // RFC 4226 test vectors.
+        let mut hotp = RxHotp::from_fields("12345678901234567890", SecretEncoding::Utf8, None)
+            .expect("Could not read HOTP fields");
+
+        let codes: Vec<_> = (0..4)
+            .map(|counter| {
+                hotp.counter = counter;
+                hotp.code()
+            })
+            .collect();
+        assert_eq!(codes, ["755224", "287082", "359152", "969429"]);
+
+        let hex = RxHotp::from_fields(
+            "3132333435363738393031323334353637383930",
+            SecretEncoding::Hex,
+            Some("9"),
+        )
+        .expect("Could not read hex secret");
+        assert_eq!(hex.code(), "520489");
+
+        let uri = "otpauth://hotp/VPN?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=1";
+        let from_uri = RxHotp::from_uri(uri).expect("Could not read HOTP URI");
+        assert_eq!(from_uri.counter, 1);
+        assert_eq!(from_uri.code(), "287082");
+
+        let too_long = format!("{}&digits=10", uri);
+        assert!(RxHotp::from_uri(&too_long).is_err());
+    }
+
+    #[test]
+    fn labels_exported_uris() {
+        let uri = legacy_totp_uri("JBSWY3DPEHPK3PXP", None).unwrap();
+        assert_eq!(
+            with_label(&uri, "My Bank:me@example.com").as_str(),
+            "otpauth://totp/My%20Bank:me%40example.com?secret=JBSWY3DPEHPK3PXP&period=30&digits=6"
+        );
+
+        let existing = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP";
+        assert_eq!(with_label(existing, "Other").as_str(), existing);
+
+        let hotp =
+            RxHotp::from_fields("12345678901234567890", SecretEncoding::Utf8, Some("4"))
+                .expect("Could not read HOTP fields");
+        let uri = hotp.to_uri("VPN");
+        assert_eq!(
+            uri.as_str(),
+            "otpauth://hotp/VPN?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=4&digits=6&algorithm=SHA1"
+        );
+        assert_eq!(RxHotp::from_uri(&uri).unwrap().code(), hotp.code());
+    }
+
+    #[test]
+    fn renders_qr_codes() {
+        let uri = "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP";
+
+        let png = qr_code(uri, RxQrFormat::Png).expect("Could not render PNG");
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let svg = qr_code(uri, RxQrFormat::Svg).expect("Could not render SVG");
+        assert!(std::str::from_utf8(&svg).unwrap().contains("<svg"));
+    }
+
+    #[test]
+    fn rolls_over_on_period_boundary() {
+        let left = until_rollover(30);
+        assert!(left > Duration::ZERO && left <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn replaces_hotp_counter() {
+        let uri = "otpauth://hotp/VPN?secret=ABC&counter=9&digits=6";
+        assert_eq!(
+            with_hotp_counter(uri, 10).as_str(),
+            "otpauth://hotp/VPN?secret=ABC&digits=6&counter=10"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{Result, anyhow};
+use sha1::{Digest, Sha1};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Length of a hex encoded SHA-1 hash.
+const HASH_LEN: usize = 40;
+
+/// Looks up passwords in a local copy of the Have I Been Pwned
+/// password list, so no network access is needed. The file must be
+/// the SHA-1 list ordered by hash: one `HASH:COUNT` line per
+/// password, with upper case hex hashes. Lookups are a binary search
+/// over the file, so it is never loaded into memory.
+pub struct RxPwnedChecker {
+    reader: RefCell<BufReader<File>>,
+    len: u64,
+}
+
+impl RxPwnedChecker {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|err| anyhow!("Could not open {}: {}", path.display(), err))?;
+        let len = file.metadata()?.len();
+
+        Ok(Self {
+            reader: RefCell::new(BufReader::new(file)),
+            len,
+        })
+    }
+
+    /// How many times the password appears in breaches, or None if
+    /// it is not in the list.
+    pub fn lookup(&self, password: &[u8]) -> Result<Option<u64>> {
+        let digest = Sha1::digest(password);
+        let mut hash = Zeroizing::new(String::with_capacity(HASH_LEN));
+        for byte in digest.iter() {
+            hash.push_str(&format!("{:02X}", byte));
+        }
+
+        self.lookup_hash(&hash)
+    }
+
+    fn lookup_hash(&self, hash: &str) -> Result<Option<u64>> {
+        let mut reader = self.reader.borrow_mut();
+        let mut line = Zeroizing::new(String::new());
+
+        // Search over the byte offsets that lines start at. Seeking
+        // lands in the middle of a line, so move on to the start of
+        // the next one.
+        let (mut low, mut high) = (0u64, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let line_start = match mid {
+                0 => {
+                    reader.seek(SeekFrom::Start(0))?;
+                    0
+                }
+                _ => {
+                    reader.seek(SeekFrom::Start(mid - 1))?;
+                    line.clear();
+                    mid - 1 + reader.read_line(&mut line)? as u64
+                }
+            };
+
+            if line_start >= high {
+                high = mid;
+                continue;
+            }
+
+            line.clear();
+            let line_len = reader.read_line(&mut line)? as u64;
+            let (line_hash, count) = line
+                .trim_end()
+                .split_once(':')
+                .ok_or(anyhow!("Malformed line in pwned passwords file"))?;
+
+            match line_hash.cmp(hash) {
+                Ordering::Equal => return Ok(Some(count.parse()?)),
+                Ordering::Less => low = line_start + line_len,
+                Ordering::Greater => high = mid,
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_file(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("keepassrx-tests").join(format!(
+            "{}-{}",
+            name,
+            std::process::id()
+        ));
+
+        std::fs::create_dir_all(&dir).expect("Could not create test dir");
+        let path = dir.join("pwned.txt");
+        std::fs::write(&path, contents).expect("Could not write test file");
+        path
+    }
+
+    #[test]
+    fn finds_pwned_passwords() {
+        let path = test_file(
+            "finds_pwned_passwords",
+            "0000000A1D4B746FAA3FD526FF6D5BC8052FDB38:16\r\n\
+             5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n\
+             7C4A8D09CA3762AF61E59520943DC26494F8941B:2\r\n\
+             FFFFFFFEE791CBAC0F6305CAF0CEE06BBE131160:6\r\n",
+        );
+
+        let checker = RxPwnedChecker::open(&path).expect("Could not open checker");
+
+        assert_eq!(checker.lookup(b"password").unwrap(), Some(9659365));
+        assert_eq!(checker.lookup(b"123456").unwrap(), Some(2));
+        assert_eq!(checker.lookup(b"not in the list").unwrap(), None);
+
+        // First and last lines.
+        assert_eq!(
+            checker
+                .lookup_hash("0000000A1D4B746FAA3FD526FF6D5BC8052FDB38")
+                .unwrap(),
+            Some(16)
+        );
+        assert_eq!(
+            checker
+                .lookup_hash("FFFFFFFEE791CBAC0F6305CAF0CEE06BBE131160")
+                .unwrap(),
+            Some(6)
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
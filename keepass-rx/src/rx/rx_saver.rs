@@ -0,0 +1,156 @@
+use anyhow::{Result, anyhow};
+use keepass::{Database, DatabaseKey};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Path of the backup kept of the previous version of a database.
+pub fn backup_path(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or(anyhow!("No filename found"))?
+        .to_string_lossy();
+
+    Ok(path.with_file_name(format!("{}.bak", file_name)))
+}
+
+/// A hidden file next to the given path, with a name no other save
+/// (in this process or another) is using.
+fn unique_temp_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(format!(
+        ".{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Replace the backup with a copy of the database. The copy is
+/// written to a temporary file first and renamed into place, so there
+/// is never a half-written backup.
+fn backup_atomically(path: &Path, dir: &Path, file_name: &str) -> Result<()> {
+    let backup = backup_path(path)?;
+    let temp_backup = unique_temp_path(dir, file_name);
+    println!("Backing up {} to {}", path.display(), backup.display());
+
+    let copy_result = (|| -> Result<()> {
+        std::fs::copy(path, &temp_backup)?;
+        File::open(&temp_backup)?.sync_all()?;
+        std::fs::rename(&temp_backup, &backup)?;
+        Ok(())
+    })();
+
+    if copy_result.is_err() && temp_backup.exists() {
+        let _ = std::fs::remove_file(&temp_backup);
+    }
+
+    copy_result
+}
+
+/// Save a database to the given path as safely as possible:
+///  1. The database is written to a temporary file in the same
+///     directory, and synced to disk.
+///  2. The existing database (if any) is copied to a .bak file,
+///     which is itself replaced atomically.
+///  3. The temporary file is renamed over the original, which is an
+///     atomic operation.
+///
+/// If anything fails before the rename, the original is untouched.
+pub fn save_atomically(db: &Database, key: DatabaseKey, path: &Path) -> Result<()> {
+    let dir = path
+        .parent()
+        .ok_or(anyhow!("Database path has no parent directory"))?;
+
+    let file_name = path
+        .file_name()
+        .ok_or(anyhow!("No filename found"))?
+        .to_string_lossy();
+
+    let temp_path = unique_temp_path(dir, &file_name);
+
+    let write_result = (|| -> Result<()> {
+        let mut temp_file = File::create(&temp_path)?;
+        db.save(&mut temp_file, key)?;
+        temp_file.sync_all()?;
+        Ok(())
+    })();
+
+    if let Err(err) = write_result {
+        if temp_path.exists() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+
+        return Err(err);
+    }
+
+    if path.exists() {
+        if let Err(err) = backup_atomically(path, dir, &file_name) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(err);
+        }
+    }
+
+    std::fs::rename(&temp_path, path)?;
+
+    // Make sure the rename itself is persisted.
+    File::open(dir)?.sync_all()?;
+
+    println!("Saved database to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("keepassrx-tests").join(format!(
+            "{}-{}-{}",
+            name,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        std::fs::create_dir_all(&dir).expect("Could not create test dir");
+        dir
+    }
+
+    fn test_key() -> DatabaseKey {
+        DatabaseKey::new().with_password("test")
+    }
+
+    #[test]
+    fn saves_and_keeps_backup() {
+        let dir = test_dir("saves_and_keeps_backup");
+        let path = dir.join("db.kdbx");
+
+        let mut db = Database::new(Default::default());
+        db.root.name = "first".to_string();
+        save_atomically(&db, test_key(), &path).expect("First save failed");
+
+        db.root.name = "second".to_string();
+        save_atomically(&db, test_key(), &path).expect("Second save failed");
+
+        let saved = Database::open(&mut File::open(&path).unwrap(), test_key())
+            .expect("Could not open saved db");
+        let backup = Database::open(
+            &mut File::open(backup_path(&path).unwrap()).unwrap(),
+            test_key(),
+        )
+        .expect("Could not open backup db");
+
+        assert_eq!(saved.root.name, "second");
+        assert_eq!(backup.root.name, "first");
+
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "Temporary files were left behind");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
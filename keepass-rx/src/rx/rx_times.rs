@@ -0,0 +1,120 @@
+use chrono::NaiveDateTime;
+use keepass::db::Times;
+
+const DISPLAY_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Timestamps of an entry or group. KeePass stores all times in UTC.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct RxTimes {
+    pub created: Option<NaiveDateTime>,
+    pub last_modified: Option<NaiveDateTime>,
+    pub last_accessed: Option<NaiveDateTime>,
+    pub location_changed: Option<NaiveDateTime>,
+    pub expiry: Option<NaiveDateTime>,
+
+    /// The expiry time only applies if this is set.
+    pub expires: bool,
+    pub usage_count: usize,
+}
+
+impl RxTimes {
+    pub fn now() -> NaiveDateTime {
+        Times::now()
+    }
+
+    /// Times for a freshly created entry or group.
+    pub fn new() -> Self {
+        let now = Self::now();
+        Self {
+            created: Some(now),
+            last_modified: Some(now),
+            last_accessed: Some(now),
+            location_changed: Some(now),
+            expiry: None,
+            expires: false,
+            usage_count: 0,
+        }
+    }
+
+    pub fn from_keepass(times: &Times) -> Self {
+        Self {
+            created: times.get_creation().map(|t| t.to_owned()),
+            last_modified: times.get_last_modification().map(|t| t.to_owned()),
+            last_accessed: times.get_last_access().map(|t| t.to_owned()),
+            location_changed: times.get_location_changed().map(|t| t.to_owned()),
+            expiry: times.get_expiry().map(|t| t.to_owned()),
+            expires: times.expires,
+            usage_count: times.usage_count,
+        }
+    }
+
+    /// Write these times over the original KeePass times.
+    pub fn to_keepass(&self, mut times: Times) -> Times {
+        if let Some(created) = self.created {
+            times.set_creation(created);
+        }
+
+        if let Some(last_modified) = self.last_modified {
+            times.set_last_modification(last_modified);
+        }
+
+        if let Some(last_accessed) = self.last_accessed {
+            times.set_last_access(last_accessed);
+        }
+
+        if let Some(location_changed) = self.location_changed {
+            times.set_location_changed(location_changed);
+        }
+
+        if let Some(expiry) = self.expiry {
+            times.set_expiry(expiry);
+        }
+
+        times.expires = self.expires;
+        times.usage_count = self.usage_count;
+        times
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires && self.expiry.is_some_and(|expiry| expiry <= Self::now())
+    }
+
+    /// Format a time for the UI, as ISO 8601 in UTC (which QML can
+    /// parse into a Date).
+    pub fn format(time: &Option<NaiveDateTime>) -> Option<String> {
+        time.map(|t| t.format(DISPLAY_FORMAT).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn expiry_needs_expires_flag() {
+        let mut times = RxTimes::default();
+        times.expiry = Some(RxTimes::now() - Duration::days(1));
+        assert!(!times.is_expired());
+
+        times.expires = true;
+        assert!(times.is_expired());
+
+        times.expiry = Some(RxTimes::now() + Duration::days(1));
+        assert!(!times.is_expired());
+    }
+
+    #[test]
+    fn round_trips_keepass_times() {
+        let mut kp_times = Times::default();
+        kp_times.expires = true;
+        kp_times.usage_count = 3;
+
+        let times = RxTimes::from_keepass(&kp_times);
+        assert_eq!(times.usage_count, 3);
+        assert_eq!(
+            RxTimes::from_keepass(&times.to_keepass(Times::default())),
+            times
+        );
+    }
+}
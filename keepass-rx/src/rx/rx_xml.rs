@@ -0,0 +1,470 @@
+use anyhow::Result;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::NaiveDateTime;
+use keepass::db::{AutoType, Color, CustomData, Entry, Group, Meta, Node, Times, Value};
+use std::io::{BufWriter, Write};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use super::{RxDatabase, RxTimes, ZeroableDatabase};
+
+/// Standard fields come first in each entry, like KeePass writes
+/// them. The rest follow by name.
+const STANDARD_FIELDS: [&str; 5] = ["Title", "UserName", "Password", "URL", "Notes"];
+
+/// Icon of folders, for groups that do not have one.
+const DEFAULT_GROUP_ICON: usize = 48;
+
+fn encode_uuid(uuid: &Uuid) -> String {
+    BASE64_STANDARD.encode(uuid.as_bytes())
+}
+
+fn encode_time(time: Option<&NaiveDateTime>) -> String {
+    RxTimes::format(&time.copied()).unwrap_or_default()
+}
+
+fn encode_color(color: &Color) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+}
+
+fn encode_bool(value: bool) -> &'static str {
+    match value {
+        true => "True",
+        false => "False",
+    }
+}
+
+/// Write text with XML's special characters escaped. Characters that
+/// XML does not allow at all are left out. Nothing is copied, so
+/// secrets are not left behind in temporary strings.
+fn write_escaped<W: Write>(out: &mut W, text: &str) -> Result<()> {
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        let replacement = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\t' | '\n' | '\r' => continue,
+            c if c < ' ' || c == '\u{fffe}' || c == '\u{ffff}' => "",
+            _ => continue,
+        };
+
+        out.write_all(text[start..index].as_bytes())?;
+        out.write_all(replacement.as_bytes())?;
+        start = index + c.len_utf8();
+    }
+
+    out.write_all(text[start..].as_bytes())?;
+    Ok(())
+}
+
+/// Writes the KeePass 2.x XML document, indented with tabs like
+/// KeePass does it.
+struct XmlWriter<'a, W: Write> {
+    out: &'a mut W,
+    depth: usize,
+}
+
+impl<W: Write> XmlWriter<'_, W> {
+    fn indent(&mut self) -> Result<()> {
+        for _ in 0..self.depth {
+            self.out.write_all(b"\t")?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, tag: &str) -> Result<()> {
+        self.indent()?;
+        writeln!(self.out, "<{}>", tag)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn close(&mut self, tag: &str) -> Result<()> {
+        self.depth -= 1;
+        self.indent()?;
+        writeln!(self.out, "</{}>", tag)?;
+        Ok(())
+    }
+
+    fn element_with(&mut self, tag: &str, attributes: &str, text: &str) -> Result<()> {
+        self.indent()?;
+        write!(self.out, "<{}{}>", tag, attributes)?;
+        write_escaped(self.out, text)?;
+        writeln!(self.out, "</{}>", tag)?;
+        Ok(())
+    }
+
+    fn element(&mut self, tag: &str, text: &str) -> Result<()> {
+        self.element_with(tag, "", text)
+    }
+
+    fn times(&mut self, times: &Times) -> Result<()> {
+        self.open("Times")?;
+        self.element("CreationTime", &encode_time(times.get_creation()))?;
+        self.element(
+            "LastModificationTime",
+            &encode_time(times.get_last_modification()),
+        )?;
+        self.element("LastAccessTime", &encode_time(times.get_last_access()))?;
+        self.element("ExpiryTime", &encode_time(times.get_expiry()))?;
+        self.element("Expires", encode_bool(times.expires))?;
+        self.element("UsageCount", &times.usage_count.to_string())?;
+        self.element(
+            "LocationChanged",
+            &encode_time(times.get_location_changed()),
+        )?;
+        self.close("Times")
+    }
+
+    fn custom_data(&mut self, custom_data: &CustomData) -> Result<()> {
+        if custom_data.items.is_empty() {
+            return Ok(());
+        }
+
+        self.open("CustomData")?;
+        for (key, item) in custom_data.items.iter() {
+            let value = match &item.value {
+                Some(Value::Unprotected(value)) => value.as_str(),
+                Some(Value::Protected(value)) => std::str::from_utf8(value.unsecure())?,
+                Some(Value::Bytes(_)) | None => continue,
+            };
+
+            self.open("Item")?;
+            self.element("Key", key)?;
+            self.element("Value", value)?;
+            if let Some(time) = &item.last_modification_time {
+                self.element("LastModificationTime", &encode_time(Some(time)))?;
+            }
+            self.close("Item")?;
+        }
+        self.close("CustomData")
+    }
+
+    fn autotype(&mut self, autotype: &AutoType) -> Result<()> {
+        self.open("AutoType")?;
+        self.element("Enabled", encode_bool(autotype.enabled))?;
+        self.element("DataTransferObfuscation", "0")?;
+        if let Some(sequence) = &autotype.sequence {
+            self.element("DefaultSequence", sequence)?;
+        }
+        for association in autotype.associations.iter() {
+            self.open("Association")?;
+            self.element("Window", association.window.as_deref().unwrap_or_default())?;
+            self.element(
+                "KeystrokeSequence",
+                association.sequence.as_deref().unwrap_or_default(),
+            )?;
+            self.close("Association")?;
+        }
+        self.close("AutoType")
+    }
+
+    fn field(&mut self, name: &str, value: &Value) -> Result<()> {
+        match value {
+            // Attachments are written inline, which KeePass and
+            // KeePassXC both read, rather than in a shared pool.
+            Value::Bytes(data) => {
+                self.open("Binary")?;
+                self.element("Key", name)?;
+                self.element("Value", &Zeroizing::new(BASE64_STANDARD.encode(data)))?;
+                self.close("Binary")
+            }
+            Value::Protected(value) => {
+                self.open("String")?;
+                self.element("Key", name)?;
+                self.element_with(
+                    "Value",
+                    " ProtectInMemory=\"True\"",
+                    std::str::from_utf8(value.unsecure())?,
+                )?;
+                self.close("String")
+            }
+            Value::Unprotected(value) => {
+                self.open("String")?;
+                self.element("Key", name)?;
+                self.element("Value", value)?;
+                self.close("String")
+            }
+        }
+    }
+
+    fn entry(&mut self, entry: &Entry, in_history: bool) -> Result<()> {
+        self.open("Entry")?;
+        self.element("UUID", &encode_uuid(&entry.uuid))?;
+        self.element("IconID", &entry.icon_id.unwrap_or_default().to_string())?;
+        if let Some(icon_uuid) = &entry.custom_icon_uuid {
+            self.element("CustomIconUUID", &encode_uuid(icon_uuid))?;
+        }
+        if let Some(color) = &entry.foreground_color {
+            self.element("ForegroundColor", &encode_color(color))?;
+        }
+        if let Some(color) = &entry.background_color {
+            self.element("BackgroundColor", &encode_color(color))?;
+        }
+        if let Some(url) = &entry.override_url {
+            self.element("OverrideURL", url)?;
+        }
+        self.element("Tags", &entry.tags.join(";"))?;
+        self.times(&entry.times)?;
+
+        let mut names: Vec<_> = entry
+            .fields
+            .keys()
+            .filter(|name| !STANDARD_FIELDS.contains(&name.as_str()))
+            .collect();
+        names.sort();
+
+        let standard = STANDARD_FIELDS.iter().copied();
+        for name in standard.chain(names.into_iter().map(String::as_str)) {
+            if let Some(value) = entry.fields.get(name) {
+                self.field(name, value)?;
+            }
+        }
+
+        if let Some(autotype) = &entry.autotype {
+            self.autotype(autotype)?;
+        }
+        self.custom_data(&entry.custom_data)?;
+
+        // History entries have no history of their own.
+        let history = entry.history.as_ref().filter(|_| !in_history);
+        if let Some(history) = history.filter(|history| !history.get_entries().is_empty()) {
+            self.open("History")?;
+            for revision in history.get_entries() {
+                self.entry(revision, true)?;
+            }
+            self.close("History")?;
+        }
+
+        self.close("Entry")
+    }
+
+    /// Write the group and everything in it. Returns the number of
+    /// entries written.
+    fn group(&mut self, group: &Group) -> Result<usize> {
+        self.open("Group")?;
+        self.element("UUID", &encode_uuid(&group.uuid))?;
+        self.element("Name", &group.name)?;
+        if let Some(notes) = &group.notes {
+            self.element("Notes", notes)?;
+        }
+        self.element(
+            "IconID",
+            &group.icon_id.unwrap_or(DEFAULT_GROUP_ICON).to_string(),
+        )?;
+        if let Some(icon_uuid) = &group.custom_icon_uuid {
+            self.element("CustomIconUUID", &encode_uuid(icon_uuid))?;
+        }
+        self.times(&group.times)?;
+        self.element("IsExpanded", encode_bool(group.is_expanded))?;
+        self.custom_data(&group.custom_data)?;
+
+        let mut count = 0;
+        for node in group.children.iter() {
+            match node {
+                Node::Entry(entry) => {
+                    self.entry(entry, false)?;
+                    count += 1;
+                }
+                Node::Group(subgroup) => count += self.group(subgroup)?,
+            }
+        }
+
+        self.close("Group")?;
+        Ok(count)
+    }
+
+    fn meta(&mut self, meta: &Meta) -> Result<()> {
+        self.open("Meta")?;
+        self.element("Generator", "KeePassRX")?;
+        if let Some(name) = &meta.database_name {
+            self.element("DatabaseName", name)?;
+        }
+        if let Some(description) = &meta.database_description {
+            self.element("DatabaseDescription", description)?;
+        }
+
+        if let Some(protection) = &meta.memory_protection {
+            self.open("MemoryProtection")?;
+            self.element("ProtectTitle", encode_bool(protection.protect_title))?;
+            self.element("ProtectUserName", encode_bool(protection.protect_username))?;
+            self.element("ProtectPassword", encode_bool(protection.protect_password))?;
+            self.element("ProtectURL", encode_bool(protection.protect_url))?;
+            self.element("ProtectNotes", encode_bool(protection.protect_notes))?;
+            self.close("MemoryProtection")?;
+        }
+
+        if !meta.custom_icons.icons.is_empty() {
+            self.open("CustomIcons")?;
+            for icon in meta.custom_icons.icons.iter() {
+                self.open("Icon")?;
+                self.element("UUID", &encode_uuid(&icon.uuid))?;
+                self.element("Data", &BASE64_STANDARD.encode(&icon.data))?;
+                self.close("Icon")?;
+            }
+            self.close("CustomIcons")?;
+        }
+
+        // KeePass turns the recycle bin on unless told otherwise.
+        self.element(
+            "RecycleBinEnabled",
+            encode_bool(meta.recyclebin_enabled.unwrap_or(true)),
+        )?;
+        if let Some(bin_uuid) = &meta.recyclebin_uuid {
+            self.element("RecycleBinUUID", &encode_uuid(bin_uuid))?;
+        }
+        if let Some(templates_uuid) = &meta.entry_templates_group {
+            self.element("EntryTemplatesGroup", &encode_uuid(templates_uuid))?;
+        }
+        if let Some(max_items) = meta.history_max_items {
+            self.element("HistoryMaxItems", &max_items.to_string())?;
+        }
+
+        self.custom_data(&meta.custom_data)?;
+        self.close("Meta")
+    }
+}
+
+/// Write the whole database as KeePass 2.x XML, the unencrypted
+/// format that KeePass and KeePassXC can import. Returns the number
+/// of entries written, not counting their history.
+pub fn export_xml<W: Write>(db: &RxDatabase, out: &mut W) -> Result<usize> {
+    // Wiped when dropped, like a loaded database.
+    let kp_db = Zeroizing::new(ZeroableDatabase(db.to_keepass()?));
+
+    // The XML is written in many small pieces.
+    let mut out = BufWriter::new(out);
+    let mut xml = XmlWriter {
+        out: &mut out,
+        depth: 0,
+    };
+
+    xml.out
+        .write_all(b"<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n")?;
+    xml.open("KeePassFile")?;
+    xml.meta(&kp_db.meta)?;
+
+    xml.open("Root")?;
+    let count = xml.group(&kp_db.root)?;
+
+    if !kp_db.deleted_objects.objects.is_empty() {
+        xml.open("DeletedObjects")?;
+        for deleted in kp_db.deleted_objects.objects.iter() {
+            xml.open("DeletedObject")?;
+            xml.element("UUID", &encode_uuid(&deleted.uuid))?;
+            xml.element("DeletionTime", &encode_time(Some(&deleted.deletion_time)))?;
+            xml.close("DeletedObject")?;
+        }
+        xml.close("DeletedObjects")?;
+    }
+
+    xml.close("Root")?;
+    xml.close("KeePassFile")?;
+    xml.out.flush()?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rx::test_support::{keepass_db, load_db};
+    use secstr::SecStr;
+
+    #[test]
+    fn escapes_xml_text() {
+        let mut out = vec![];
+        write_escaped(&mut out, "a<b> & \"c\"\u{1}\n").unwrap();
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            "a&lt;b&gt; &amp; &quot;c&quot;\n"
+        );
+    }
+
+    #[test]
+    fn exports_keepass_xml() {
+        let mut root = keepass::db::Group::new("Root");
+        let mut work = keepass::db::Group::new("Work & Play");
+
+        let mut old = keepass::db::Entry::new();
+        old.fields.insert(
+            "Password".to_string(),
+            Value::Protected(SecStr::from("old password")),
+        );
+
+        let mut entry = keepass::db::Entry::new();
+        entry
+            .fields
+            .insert("Title".to_string(), Value::Unprotected("Mail".to_string()));
+        entry.fields.insert(
+            "Password".to_string(),
+            Value::Protected(SecStr::from("hunter2")),
+        );
+        entry
+            .fields
+            .insert("PIN".to_string(), Value::Protected(SecStr::from("1234")));
+        entry
+            .fields
+            .insert("key.txt".to_string(), Value::Bytes(b"secret".to_vec()));
+        entry.tags = vec!["work".to_string(), "mail".to_string()];
+        entry.icon_id = Some(19);
+        entry.foreground_color = Some(Color {
+            r: 255,
+            g: 0,
+            b: 16,
+        });
+        entry.override_url = Some("cmd://mail".to_string());
+        entry.autotype = Some(AutoType {
+            enabled: true,
+            sequence: Some("{USERNAME}{TAB}{PASSWORD}{ENTER}".to_string()),
+            associations: vec![keepass::db::AutoTypeAssociation {
+                window: Some("Mail*".to_string()),
+                sequence: Some("{PASSWORD}".to_string()),
+            }],
+        });
+
+        let mut history = keepass::db::History::default();
+        history.add_entry(old);
+        entry.history = Some(history);
+
+        work.add_child(keepass::db::Node::Entry(entry));
+        root.add_child(keepass::db::Node::Group(work));
+        let mut db = keepass_db(root);
+        db.meta.recyclebin_enabled = Some(false);
+
+        let rx_db = load_db(db);
+        let mut xml = Zeroizing::new(Vec::new());
+        let count = export_xml(&rx_db, &mut *xml).expect("Could not export");
+        assert_eq!(count, 1);
+
+        let xml = std::str::from_utf8(&xml).unwrap();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<Name>Work &amp; Play</Name>"));
+        assert!(xml.contains("<Value ProtectInMemory=\"True\">hunter2</Value>"));
+        assert!(xml.contains("<Key>PIN</Key>"));
+        assert!(xml.contains("<Tags>work;mail</Tags>"));
+        assert!(xml.contains("<IconID>19</IconID>"));
+        assert!(xml.contains("<ForegroundColor>#FF0010</ForegroundColor>"));
+        assert!(xml.contains("<OverrideURL>cmd://mail</OverrideURL>"));
+        assert!(
+            xml.contains(
+                "<DefaultSequence>{USERNAME}{TAB}{PASSWORD}{ENTER}</DefaultSequence>"
+            )
+        );
+        assert!(xml.contains("<Window>Mail*</Window>"));
+        assert!(xml.contains("<RecycleBinEnabled>False</RecycleBinEnabled>"));
+        assert!(xml.contains(&format!(
+            "<Value>{}</Value>",
+            BASE64_STANDARD.encode("secret")
+        )));
+        assert!(xml.contains("<History>"));
+        assert!(xml.contains("old password"));
+
+        // The title comes before the other fields.
+        assert!(xml.find("<Key>Title</Key>") < xml.find("<Key>PIN</Key>"));
+        assert!(xml.trim_end().ends_with("</KeePassFile>"));
+    }
+}
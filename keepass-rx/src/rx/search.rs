@@ -5,7 +5,9 @@ use std::{cmp::Ordering, ops::Deref, rc::Rc};
 use unicase::UniCase;
 use uuid::Uuid;
 
-use super::{RxContainedRef, RxDatabase, RxEntry, RxGroup, RxTag, RxTemplate};
+use super::{
+    RxCategory, RxContainedRef, RxDatabase, RxEntry, RxGroup, RxSavedSearch, RxTag, RxTemplate,
+};
 
 #[cfg(feature = "gui")]
 use qmetaobject::{QEnum, QMetaType, QString};
@@ -17,6 +19,8 @@ pub enum RxSearchType {
     #[default]
     CaseInsensitive,
     Fuzzy,
+    /// KeePassXC query language (field operators, negation, regex).
+    Advanced,
 }
 
 #[cfg(feature = "gui")]
@@ -24,6 +28,7 @@ fn search_type_from_string(qval: &QString) -> RxSearchType {
     match qval.to_string().as_str() {
         "CaseInsensitive" => RxSearchType::CaseInsensitive,
         "Fuzzy" => RxSearchType::Fuzzy,
+        "Advanced" => RxSearchType::Advanced,
         _ => panic!("Invalid search type: {}", qval),
     }
 }
@@ -33,6 +38,7 @@ fn search_type_to_string(search_type: &RxSearchType) -> QString {
     match search_type {
         RxSearchType::CaseInsensitive => "CaseInsensitive",
         RxSearchType::Fuzzy => "Fuzzy",
+        RxSearchType::Advanced => "Advanced",
     }
     .into()
 }
@@ -84,6 +90,34 @@ impl Search for FuzzySearch<&RxTag> {
     }
 }
 
+impl Search for CaseInsensitiveSearch<&RxSavedSearch> {
+    fn matches(&self, term: &str) -> bool {
+        UniCase::new(&self.name).to_folded_case().contains(term)
+    }
+}
+
+impl Search for FuzzySearch<&RxSavedSearch> {
+    fn matches(&self, term: &str) -> bool {
+        SkimMatcherV2::default()
+            .fuzzy_match(&self.name, term)
+            .is_some()
+    }
+}
+
+impl Search for CaseInsensitiveSearch<&RxCategory> {
+    fn matches(&self, term: &str) -> bool {
+        UniCase::new(&self.name).to_folded_case().contains(term)
+    }
+}
+
+impl Search for FuzzySearch<&RxCategory> {
+    fn matches(&self, term: &str) -> bool {
+        SkimMatcherV2::default()
+            .fuzzy_match(&self.name, term)
+            .is_some()
+    }
+}
+
 impl Search for CaseInsensitiveSearch<&RxGroup> {
     fn matches(&self, term: &str) -> bool {
         UniCase::new(&self.name).to_folded_case().contains(term)
@@ -185,6 +219,10 @@ impl Search for CaseInsensitiveSearch<&RxContainedRef> {
                 CaseInsensitiveSearch(template.as_ref()).matches(term)
             }
             RxContainedRef::Tag(tag) => CaseInsensitiveSearch(tag).matches(term),
+            RxContainedRef::SavedSearch(search) => CaseInsensitiveSearch(search).matches(term),
+            RxContainedRef::Category(category) => {
+                CaseInsensitiveSearch(category).matches(term)
+            }
             RxContainedRef::VirtualRoot(_) => true,
         }
     }
@@ -197,6 +235,8 @@ impl Search for FuzzySearch<&RxContainedRef> {
             RxContainedRef::Group(group) => FuzzySearch(group.as_ref()).matches(term),
             RxContainedRef::Template(template) => FuzzySearch(template.as_ref()).matches(term),
             RxContainedRef::Tag(tag) => FuzzySearch(tag).matches(term),
+            RxContainedRef::SavedSearch(search) => FuzzySearch(search).matches(term),
+            RxContainedRef::Category(category) => FuzzySearch(category).matches(term),
             RxContainedRef::VirtualRoot(_) => true,
         }
     }
@@ -210,5 +250,9 @@ pub fn search_contained_ref(
     match search_type {
         RxSearchType::CaseInsensitive => CaseInsensitiveSearch(contained_ref).matches(term),
         RxSearchType::Fuzzy => FuzzySearch(contained_ref).matches(term),
+        // Advanced queries are run by VirtualHierarchy::search, as
+        // they need to know the group of an entry. Fall back to a
+        // plain search if one ends up here.
+        RxSearchType::Advanced => CaseInsensitiveSearch(contained_ref).matches(term),
     }
 }
@@ -0,0 +1,20 @@
+use keepass::db::{Database, Group};
+use keyring::set_default_credential_builder;
+use zeroize::Zeroizing;
+
+use super::{RxDatabase, ZeroableDatabase};
+
+/// An empty KeePass database with the given root group, for tests to
+/// fill in.
+pub(crate) fn keepass_db(root: Group) -> Database {
+    let mut db = Database::new(Default::default());
+    db.root = root;
+    db
+}
+
+/// Load a KeePass database like the app does. The keyring is mocked,
+/// so tests never touch the real one.
+pub(crate) fn load_db(db: Database) -> RxDatabase {
+    set_default_credential_builder(keyring::mock::default_credential_builder());
+    RxDatabase::new(Zeroizing::new(ZeroableDatabase(db)))
+}
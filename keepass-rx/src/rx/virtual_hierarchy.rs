@@ -1,8 +1,15 @@
 use gettextrs::{gettext, pgettext};
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 use uuid::Uuid;
 
-use super::{RxContainedRef, RxContainer, RxDatabase, RxRoot, RxSearchType, RxTag};
+use super::{
+    PasswordQuality, RxCategory, RxContainedRef, RxContainer, RxDatabase, RxPasswordHealth,
+    RxPasswordReuse, RxQuery, RxRoot, RxSavedSearch, RxSearchType, RxTag,
+    evaluate_saved_search,
+};
 
 /// A setting that controls how an RxListItem is rendered in the UI.
 /// Note that the UI container of the list item must also have the
@@ -48,13 +55,74 @@ pub trait VirtualHierarchy {
             .and_then(|c| c.get_ref())
     }
 
-    /// Search for child containers in the virtual hierarchy.
+    /// Search for child containers in the virtual hierarchy. Advanced
+    /// queries work the same in every view; other searches are up to
+    /// the view (see search_simple).
     fn search(
+        &self,
+        db: &RxDatabase,
+        search_type: RxSearchType,
+        container_uuid: Uuid,
+        search_term: Option<&str>,
+    ) -> Vec<RxContainedRef> {
+        match (search_type, search_term) {
+            (RxSearchType::Advanced, Some(query)) => {
+                self.search_advanced(db, container_uuid, query)
+            }
+            _ => self.search_simple(search_type, container_uuid, search_term),
+        }
+    }
+
+    /// Search by name (case insensitive or fuzzy), or list the
+    /// children of the container if there is no search term.
+    fn search_simple(
         &self,
         search_type: RxSearchType,
         container_uuid: Uuid,
         search_term: Option<&str>,
     ) -> Vec<RxContainedRef>;
+
+    /// Search with the KeePassXC query language. Only entries can
+    /// match, and they are found anywhere below the container. The
+    /// group: operator matches the entry's group in the database,
+    /// whether or not this hierarchy shows groups.
+    fn search_advanced(
+        &self,
+        db: &RxDatabase,
+        container_uuid: Uuid,
+        query: &str,
+    ) -> Vec<RxContainedRef> {
+        let Some(query) = RxQuery::parse(query) else {
+            return vec![];
+        };
+
+        // An entry can show up more than once (e.g. under several
+        // tags), but should only be in the results once.
+        let mut seen = HashSet::new();
+
+        self.root()
+            .get_container(container_uuid)
+            .map(|container| {
+                container
+                    .child_containers_recursive()
+                    .into_values()
+                    .filter_map(|child| match child.get_ref() {
+                        Some(RxContainedRef::Entry(entry)) => {
+                            let group = db.get_group(entry.parent_group);
+                            let group_name = group.as_ref().map(|group| group.name.as_str());
+
+                            match query.matches(&entry, group_name) && seen.insert(entry.uuid)
+                            {
+                                true => Some(RxContainedRef::Entry(entry)),
+                                false => None,
+                            }
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Clone)]
@@ -83,7 +151,7 @@ impl VirtualHierarchy for AllTemplates {
         &self.0
     }
 
-    fn search(
+    fn search_simple(
         &self,
         search_type: RxSearchType,
         container_uuid: Uuid,
@@ -132,7 +200,7 @@ impl VirtualHierarchy for DefaultView {
         )
     }
 
-    fn search(
+    fn search_simple(
         &self,
         search_type: RxSearchType,
         container_uuid: Uuid,
@@ -197,7 +265,7 @@ impl VirtualHierarchy for TotpEntries {
         RxViewFeature::DisplayTwoFactorAuth
     }
 
-    fn search(
+    fn search_simple(
         &self,
         search_type: RxSearchType,
         container_uuid: Uuid,
@@ -246,7 +314,223 @@ impl VirtualHierarchy for AllTags {
         &self.0
     }
 
-    fn search(
+    fn search_simple(
+        &self,
+        search_type: RxSearchType,
+        container_uuid: Uuid,
+        search_term: Option<&str>,
+    ) -> Vec<RxContainedRef> {
+        self.root()
+            .get_container(container_uuid)
+            .map(|container| container.search_children_immediate(search_type, search_term))
+            .unwrap_or_default()
+    }
+}
+
+pub struct SavedSearches(RxRoot);
+
+impl SavedSearches {
+    pub fn new(db: &RxDatabase) -> Self {
+        let children: Vec<_> = db
+            .saved_searches()
+            .into_iter()
+            .map(|(name, query)| {
+                let entry_uuids = evaluate_saved_search(db, &query);
+                RxContainer::from(RxSavedSearch::new(name, query, entry_uuids), db)
+            })
+            .collect();
+
+        let root = RxRoot::virtual_root(
+            &pgettext(
+                "Searches saved in the database by KeePassXC",
+                "Saved Searches",
+            ),
+            children,
+        );
+
+        SavedSearches(root)
+    }
+}
+
+impl VirtualHierarchy for SavedSearches {
+    fn name(&self) -> String {
+        pgettext(
+            "Searches saved in the database by KeePassXC",
+            "Saved Searches",
+        )
+    }
+
+    fn root(&self) -> &RxRoot {
+        &self.0
+    }
+
+    fn search_simple(
+        &self,
+        search_type: RxSearchType,
+        container_uuid: Uuid,
+        search_term: Option<&str>,
+    ) -> Vec<RxContainedRef> {
+        self.root()
+            .get_container(container_uuid)
+            .map(|container| container.search_children_immediate(search_type, search_term))
+            .unwrap_or_default()
+    }
+}
+
+/// Translated name and explanation of a password quality bucket.
+fn quality_bucket_text(quality: PasswordQuality) -> (String, String) {
+    let context = "Password quality rating in the password health report";
+    match quality {
+        PasswordQuality::Bad => (
+            pgettext(context, "Bad"),
+            gettext("Trivial to guess. Change these passwords now."),
+        ),
+        PasswordQuality::Poor => (
+            pgettext(context, "Poor"),
+            gettext("Easy to guess. Change these passwords soon."),
+        ),
+        PasswordQuality::Weak => (
+            pgettext(context, "Weak"),
+            gettext("Could be guessed. Consider changing these passwords."),
+        ),
+        PasswordQuality::Good => (pgettext(context, "Good"), gettext("Hard to guess.")),
+        PasswordQuality::Excellent => (
+            pgettext(context, "Excellent"),
+            gettext("Very hard to guess."),
+        ),
+    }
+}
+
+/// Entries grouped by how strong their passwords are, worst first.
+/// Entries with breached passwords come before all of them. Empty
+/// buckets are left out.
+pub struct PasswordHealth(RxRoot);
+
+impl PasswordHealth {
+    pub fn new(db: &RxDatabase) -> Self {
+        let health = RxPasswordHealth::new(db);
+
+        let mut children = vec![];
+
+        if !health.pwned_entries().is_empty() {
+            let name = pgettext(
+                "Password quality rating in the password health report",
+                "Compromised",
+            );
+            let description = gettext("Found in a data breach. Change these passwords now.");
+            let entry_uuids = health.pwned_entries().to_vec();
+
+            children.push(RxContainer::from(
+                RxCategory::new("pwned", name, description, entry_uuids),
+                db,
+            ));
+        }
+
+        let buckets = PasswordQuality::ALL
+            .into_iter()
+            .map(|quality| (quality, health.entries_with(quality)))
+            .filter(|(_, entry_uuids)| !entry_uuids.is_empty())
+            .map(|(quality, entry_uuids)| {
+                let (name, description) = quality_bucket_text(quality);
+                let id = format!("quality:{:?}", quality);
+                RxContainer::from(RxCategory::new(&id, name, description, entry_uuids), db)
+            });
+
+        children.extend(buckets);
+
+        let root = RxRoot::virtual_root(
+            &pgettext("Report on the strength of passwords", "Password Health"),
+            children,
+        );
+
+        PasswordHealth(root)
+    }
+}
+
+impl VirtualHierarchy for PasswordHealth {
+    fn name(&self) -> String {
+        pgettext("Report on the strength of passwords", "Password Health")
+    }
+
+    fn root(&self) -> &RxRoot {
+        &self.0
+    }
+
+    fn search_simple(
+        &self,
+        search_type: RxSearchType,
+        container_uuid: Uuid,
+        search_term: Option<&str>,
+    ) -> Vec<RxContainedRef> {
+        self.root()
+            .get_container(container_uuid)
+            .map(|container| container.search_children_immediate(search_type, search_term))
+            .unwrap_or_default()
+    }
+}
+
+/// Number of entry titles used to name a reused password cluster.
+const REUSE_CLUSTER_TITLES: usize = 3;
+
+/// One container per password that is shared by several entries,
+/// largest cluster first. Each cluster is named after its entries.
+pub struct ReusedPasswords(RxRoot);
+
+impl ReusedPasswords {
+    pub fn new(db: &RxDatabase) -> Self {
+        let reuse = RxPasswordReuse::new(db).unwrap_or_else(|err| {
+            println!("Could not check for reused passwords: {}", err);
+            RxPasswordReuse::default()
+        });
+
+        let children: Vec<_> = reuse
+            .clusters()
+            .iter()
+            .map(|entry_uuids| {
+                let mut titles: Vec<_> = entry_uuids
+                    .iter()
+                    .filter_map(|uuid| db.get_entry(*uuid))
+                    .map(|entry| RxContainedRef::Entry(entry).name())
+                    .take(REUSE_CLUSTER_TITLES)
+                    .collect();
+
+                if entry_uuids.len() > REUSE_CLUSTER_TITLES {
+                    titles.push("…".to_string());
+                }
+
+                let description = pgettext(
+                    "Several entries that have the same password",
+                    "Same password",
+                );
+
+                // Each entry has one password, so it is in at most
+                // one cluster.
+                let id = format!("reused:{}", entry_uuids[0]);
+                let category =
+                    RxCategory::new(&id, titles.join(", "), description, entry_uuids.clone());
+                RxContainer::from(category, db)
+            })
+            .collect();
+
+        let root = RxRoot::virtual_root(
+            &pgettext("Entries that share a password", "Reused Passwords"),
+            children,
+        );
+
+        ReusedPasswords(root)
+    }
+}
+
+impl VirtualHierarchy for ReusedPasswords {
+    fn name(&self) -> String {
+        pgettext("Entries that share a password", "Reused Passwords")
+    }
+
+    fn root(&self) -> &RxRoot {
+        &self.0
+    }
+
+    fn search_simple(
         &self,
         search_type: RxSearchType,
         container_uuid: Uuid,
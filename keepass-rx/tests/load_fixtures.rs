@@ -85,3 +85,22 @@ fn loads_supported_database_formats_into_rx_database() {
         assert_eq!(rx_db.all_entries_iter().count(), case.total_entries, "{}", case.name);
     }
 }
+
+#[test]
+fn loads_saved_searches_from_kpxc_fixture() {
+    set_default_credential_builder(keyring::mock::default_credential_builder());
+
+    let mut file = File::open(fixture_path("test_saved_searches.kdbx")).expect("open fixture");
+    let db = Database::open(&mut file, DatabaseKey::new().with_password("somePassw0rd"))
+        .expect("open keepass db");
+
+    let rx_db = RxDatabase::new(Zeroizing::new(ZeroableDatabase(db)));
+    let searches = rx_db.saved_searches();
+
+    assert!(!searches.is_empty(), "no saved searches found");
+
+    for (name, query) in searches {
+        assert!(!name.is_empty());
+        assert!(!query.is_empty(), "empty query for {}", name);
+    }
+}